@@ -0,0 +1,10 @@
+use std::path::Path;
+
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path) {
+    let doc = input::load_valid_document(file, "listing degrees");
+
+    output::print_degrees(&doc, &tree_doc_core::degrees(&doc));
+}
@@ -0,0 +1,12 @@
+use std::path::Path;
+
+use crate::input;
+use crate::output;
+
+pub fn run(old: &Path, new: &Path) {
+    let old_doc = input::load_document(old);
+    let new_doc = input::load_document(new);
+
+    let diff = tree_doc_core::diff(&old_doc, &new_doc);
+    output::print_diff(&diff, old, new);
+}
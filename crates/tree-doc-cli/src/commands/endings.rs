@@ -0,0 +1,10 @@
+use std::path::Path;
+
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path) {
+    let doc = input::load_valid_document(file, "listing endings");
+
+    output::print_endings(&tree_doc_core::endings(&doc));
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::Path;
+
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output::ExportFormat;
+
+pub fn run(
+    file: &Path,
+    format: ExportFormat,
+    out_dir: &Path,
+    edge_type: Option<&str>,
+    trunk_only: bool,
+) {
+    if trunk_only && !matches!(format, ExportFormat::Dot | ExportFormat::Mermaid) {
+        eprintln!("--trunk-only only applies to the dot and mermaid formats");
+        ExitCode::Usage.exit();
+    }
+
+    let mut doc = input::load_valid_document(file, "exporting");
+    if let Some(edge_type) = edge_type {
+        doc = tree_doc_core::filter_edge_type(&doc, &edge_type.into());
+    }
+
+    let rendered = match format {
+        ExportFormat::Dot if trunk_only => tree_doc_core::to_dot_trunk(&doc),
+        ExportFormat::Dot => tree_doc_core::to_dot(&doc),
+        ExportFormat::Mermaid if trunk_only => tree_doc_core::to_mermaid_trunk(&doc),
+        ExportFormat::Mermaid => tree_doc_core::to_mermaid(&doc),
+        ExportFormat::Graphml => tree_doc_core::to_graphml(&doc),
+        ExportFormat::Markdown => exported(tree_doc_core::to_markdown(&doc), file),
+        ExportFormat::Html => exported(tree_doc_core::to_html(&doc), file),
+        ExportFormat::Csv => {
+            let (nodes, edges) = tree_doc_core::to_csv(&doc);
+            write_csv(out_dir, &[("nodes.csv", nodes), ("edges.csv", edges)]);
+            return;
+        }
+    };
+    print!("{rendered}");
+}
+
+/// Unwrap a trunk-based export, exiting with code 1 if it failed.
+fn exported(rendered: Result<String, String>, file: &Path) -> String {
+    rendered.unwrap_or_else(|e| {
+        eprintln!("Error exporting '{}': {e}", file.display());
+        ExitCode::ValidationFailed.exit();
+    })
+}
+
+fn write_csv(out_dir: &Path, tables: &[(&str, String)]) {
+    if let Err(e) = fs::create_dir_all(out_dir) {
+        eprintln!("Error creating directory '{}': {e}", out_dir.display());
+        ExitCode::IoError.exit();
+    }
+
+    for (name, contents) in tables {
+        let path = out_dir.join(name);
+        if let Err(e) = fs::write(&path, contents) {
+            eprintln!("Error writing file '{}': {e}", path.display());
+            ExitCode::IoError.exit();
+        }
+        println!("Wrote {}", path.display());
+    }
+}
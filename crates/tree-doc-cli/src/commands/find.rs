@@ -0,0 +1,11 @@
+use std::path::Path;
+
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path, query: &str, ignore_case: bool) {
+    let doc = input::load_valid_document(file, "searching");
+
+    let found = tree_doc_core::find_nodes(&doc, query, ignore_case);
+    output::print_found(&found, query, ignore_case);
+}
@@ -0,0 +1,31 @@
+use std::fs;
+use std::path::Path;
+
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path, write: bool) {
+    if write && file == Path::new("-") {
+        eprintln!("Cannot use --write when reading from stdin");
+        ExitCode::Usage.exit();
+    }
+    if write && input::is_gz_path(file) {
+        eprintln!("Cannot use --write on a gzipped file");
+        ExitCode::Usage.exit();
+    }
+
+    let original = input::load_document(file);
+
+    let mut fixed = original.clone();
+    let rules = tree_doc_core::autofix(&mut fixed);
+    let diff = tree_doc_core::diff(&original, &fixed);
+    output::print_fixes(&rules, &diff, file, write);
+
+    if write && !rules.is_empty() {
+        if let Err(e) = fs::write(file, fixed.to_canonical_json()) {
+            eprintln!("Error writing file '{}': {e}", file.display());
+            ExitCode::IoError.exit();
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::fs;
+use std::path::Path;
+
+use crate::exit::ExitCode;
+use crate::input;
+
+pub fn run(file: &Path, write: bool) {
+    if write && file == Path::new("-") {
+        eprintln!("Cannot use --write when reading from stdin");
+        ExitCode::Usage.exit();
+    }
+    if write && input::is_gz_path(file) {
+        eprintln!("Cannot use --write on a gzipped file");
+        ExitCode::Usage.exit();
+    }
+
+    let json_str = input::read_or_exit(file);
+    let doc = input::parse_or_exit(file, &json_str);
+
+    let formatted = tree_doc_core::canonicalize(&doc).to_canonical_json();
+    if !write {
+        print!("{formatted}");
+        return;
+    }
+
+    if formatted != json_str {
+        if let Err(e) = fs::write(file, formatted) {
+            eprintln!("Error writing file '{}': {e}", file.display());
+            ExitCode::IoError.exit();
+        }
+    }
+}
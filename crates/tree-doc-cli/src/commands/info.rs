@@ -1,24 +1,40 @@
-use std::path::PathBuf;
-use std::process;
+use std::path::Path;
 
+use clap::ValueEnum;
+use tree_doc_core::DocumentStats;
+
+use crate::input;
 use crate::output;
 
-pub fn run(file: &PathBuf) {
-    let json_str = match std::fs::read_to_string(file) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error reading file '{}': {e}", file.display());
-            process::exit(2);
-        }
-    };
+/// A single statistic `info --count` can print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum InfoCount {
+    /// Number of nodes
+    Nodes,
+    /// Number of edges
+    Edges,
+    /// Length of the trunk, in edges
+    Trunk,
+    /// Number of non-trunk edges
+    Branches,
+}
 
-    let result = match tree_doc_core::validate_document(&json_str) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Error parsing '{}': {e}", file.display());
-            process::exit(2);
+impl InfoCount {
+    fn of(self, stats: &DocumentStats) -> usize {
+        match self {
+            InfoCount::Nodes => stats.node_count,
+            InfoCount::Edges => stats.edge_count,
+            InfoCount::Trunk => stats.trunk_length,
+            InfoCount::Branches => stats.branch_count,
         }
-    };
+    }
+}
+
+pub fn run(file: &Path, count: Option<InfoCount>) {
+    let (result, _) = input::load_validated(file);
 
-    output::print_info(&result, file);
+    match count {
+        Some(count) => println!("{}", count.of(&result.stats)),
+        None => output::print_info(&result, file),
+    }
 }
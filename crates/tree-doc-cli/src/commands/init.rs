@@ -0,0 +1,25 @@
+use std::fs;
+use std::path::Path;
+
+use crate::exit::ExitCode;
+
+pub fn run(out: &Path, tier: u8, force: bool) {
+    if out.exists() && !force {
+        eprintln!(
+            "File '{}' already exists; pass --force to overwrite it",
+            out.display()
+        );
+        ExitCode::Usage.exit();
+    }
+
+    let Some(doc) = tree_doc_core::scaffold(tier) else {
+        eprintln!("No scaffold for tier {tier}");
+        ExitCode::Usage.exit();
+    };
+
+    if let Err(e) = fs::write(out, doc.to_canonical_json()) {
+        eprintln!("Error writing file '{}': {e}", out.display());
+        ExitCode::IoError.exit();
+    }
+    println!("Wrote {}", out.display());
+}
@@ -1,3 +1,16 @@
+pub mod degrees;
+pub mod diff;
+pub mod endings;
+pub mod export;
+pub mod find;
+pub mod fix;
+pub mod fmt;
 pub mod info;
+pub mod init;
+pub mod order;
+pub mod path;
+pub mod paths;
+pub mod reachable;
+pub mod stats;
 pub mod validate;
 pub mod view;
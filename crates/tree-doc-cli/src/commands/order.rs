@@ -0,0 +1,17 @@
+use std::path::Path;
+
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path) {
+    let doc = input::load_valid_document(file, "ordering nodes");
+
+    match tree_doc_core::topo_order(&doc) {
+        Ok(order) => output::print_order(&doc, &order),
+        Err(cycle) => {
+            eprintln!("No topological order: cycle among {}", cycle.join(", "));
+            ExitCode::ValidationFailed.exit();
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path, from: &str, to: &str) {
+    let doc = input::load_valid_document(file, "querying paths");
+
+    for id in [from, to] {
+        if doc.node(id).is_none() {
+            eprintln!("Node '{id}' not found in '{}'", file.display());
+            ExitCode::ValidationFailed.exit();
+        }
+    }
+
+    match tree_doc_core::shortest_path(&doc, from, to) {
+        Some(path) => output::print_path(&doc, &path),
+        None => {
+            eprintln!("No path from '{from}' to '{to}'");
+            ExitCode::ValidationFailed.exit();
+        }
+    }
+}
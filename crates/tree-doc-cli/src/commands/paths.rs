@@ -0,0 +1,13 @@
+use std::path::Path;
+
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path, limit: usize) {
+    let doc = input::load_valid_document(file, "listing paths");
+
+    let mut paths = tree_doc_core::all_paths(&doc, limit.saturating_add(1));
+    let truncated = paths.len() > limit;
+    paths.truncate(limit);
+    output::print_paths(&paths, limit, truncated);
+}
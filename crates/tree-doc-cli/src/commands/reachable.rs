@@ -0,0 +1,17 @@
+use std::path::Path;
+
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path, from: &str) {
+    let doc = input::load_valid_document(file, "querying reachability");
+
+    match tree_doc_core::reachable_from(&doc, from) {
+        Ok(reachable) => output::print_reachable(&doc, from, &reachable),
+        Err(e) => {
+            eprintln!("Error querying '{}': {e}", file.display());
+            ExitCode::ValidationFailed.exit();
+        }
+    }
+}
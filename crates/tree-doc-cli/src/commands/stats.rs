@@ -0,0 +1,10 @@
+use std::path::Path;
+
+use crate::input;
+use crate::output;
+
+pub fn run(file: &Path) {
+    let doc = input::load_document(file);
+
+    output::print_stats(&tree_doc_core::compute_stats(&doc), file);
+}
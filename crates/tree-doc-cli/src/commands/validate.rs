@@ -1,30 +1,109 @@
-use std::path::PathBuf;
-use std::process;
+use std::path::{Path, PathBuf};
 
-use crate::output;
+use clap::ValueEnum;
+use serde::Deserialize;
+use tree_doc_core::parse::ParseError;
+use tree_doc_core::{ValidationOptions, ValidationResult};
 
-pub fn run(file: &PathBuf) {
-    let json_str = match std::fs::read_to_string(file) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error reading file '{}': {e}", file.display());
-            process::exit(2);
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output::{self, OutputFormat, Verbosity};
+
+/// Lowest severity that makes `validate` exit with code 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailOn {
+    /// Only errors fail (the document is invalid)
+    Error,
+    /// Errors or warnings fail
+    Warning,
+    /// Any diagnostic fails
+    Advisory,
+}
+
+impl FailOn {
+    fn fails(self, result: &ValidationResult) -> bool {
+        match self {
+            FailOn::Error => !result.errors.is_empty(),
+            FailOn::Warning => !result.errors.is_empty() || !result.warnings.is_empty(),
+            FailOn::Advisory => {
+                !result.errors.is_empty()
+                    || !result.warnings.is_empty()
+                    || !result.advisories.is_empty()
+            }
         }
-    };
+    }
+}
 
-    let result = match tree_doc_core::validate_document(&json_str) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Error parsing '{}': {e}", file.display());
-            process::exit(2);
+pub fn run(
+    files: &[PathBuf],
+    format: OutputFormat,
+    verbosity: Verbosity,
+    fail_on: FailOn,
+    options: &ValidationOptions,
+) {
+    let mut results = Vec::new();
+    let mut input_error = None;
+
+    for file in files {
+        match validate_file(file, options) {
+            Ok(result) => results.push((file.as_path(), result)),
+            Err(code) => input_error = input_error.max(Some(code)),
         }
-    };
+    }
 
-    output::print_validation_result(&result, file);
+    match format {
+        OutputFormat::Text => {
+            let mut printed_any = false;
+            for (file, result) in &results {
+                if verbosity == Verbosity::Quiet && result.is_valid {
+                    continue;
+                }
+                if printed_any {
+                    println!();
+                }
+                output::print_validation_result(result, file, verbosity);
+                printed_any = true;
+            }
+            if files.len() > 1 && verbosity != Verbosity::Quiet {
+                // Files that couldn't be read or parsed count as invalid
+                let valid = results.iter().filter(|(_, r)| r.is_valid).count();
+                output::print_batch_summary(files.len(), files.len() - valid);
+            }
+        }
+        OutputFormat::Json => match results.as_slice() {
+            [(_, result)] if files.len() == 1 => output::print_validation_json(result),
+            _ => output::print_batch_validation_json(&results),
+        },
+    }
 
-    if result.is_valid {
-        process::exit(0);
+    if let Some(code) = input_error {
+        code.exit();
+    }
+    if !results.iter().any(|(_, r)| fail_on.fails(r)) {
+        ExitCode::Ok.exit();
     } else {
-        process::exit(1);
+        ExitCode::ValidationFailed.exit();
     }
 }
+
+/// Read and validate one file, reporting read/parse failures on stderr and
+/// returning the exit code they call for.
+///
+/// Validation goes through the same per-file helper as
+/// [`tree_doc_core::validate_files`]; only reading differs, to allow stdin.
+fn validate_file(file: &Path, options: &ValidationOptions) -> Result<ValidationResult, ExitCode> {
+    let read_error = |e: &dyn std::fmt::Display| {
+        eprintln!("Error reading file '{}': {e}", file.display());
+        ExitCode::IoError
+    };
+    let bytes = input::read_bytes(file).map_err(|e| read_error(&e))?;
+
+    tree_doc_core::validate_bytes_with_options(bytes, options).map_err(|e| match e {
+        ParseError::Io(e) => read_error(&e),
+        e => {
+            eprintln!("Error parsing '{}': {e}", file.display());
+            ExitCode::Usage
+        }
+    })
+}
@@ -1,46 +1,61 @@
-use std::path::PathBuf;
-use std::process;
+use std::path::Path;
 
-use crate::output;
+use tree_doc_core::types::{Edge, EdgeType};
 
-pub fn run(file: &PathBuf) {
-    let json_str = match std::fs::read_to_string(file) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error reading file '{}': {e}", file.display());
-            process::exit(2);
-        }
-    };
+use crate::exit::ExitCode;
+use crate::input;
+use crate::output::{self, Verbosity};
 
-    // Validate first
-    let result = match tree_doc_core::validate_document(&json_str) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Error parsing '{}': {e}", file.display());
-            process::exit(2);
-        }
-    };
+pub fn run(file: &Path, full: bool, force: bool, edge_type: Option<&str>, tree: Option<&str>) {
+    // Validate first, keeping the parsed document for the view
+    let (result, doc) = input::load_validated(file);
 
     if !result.is_valid {
-        output::print_validation_result(&result, file);
-        eprintln!("\nDocument has errors. Fix them before viewing.");
-        process::exit(1);
+        output::print_validation_result(&result, file, Verbosity::Normal);
+        if !force {
+            eprintln!("\nDocument has errors. Fix them before viewing.");
+            ExitCode::ValidationFailed.exit();
+        }
+        eprintln!("\nDocument has errors. Rendering anyway because of --force.\n");
     }
 
-    // Parse and build trunk view
-    let doc = match tree_doc_core::parse(&json_str) {
-        Ok(d) => d,
-        Err(e) => {
-            eprintln!("Error parsing '{}': {e}", file.display());
-            process::exit(2);
-        }
+    // Only reachable with --force: schema errors kept the document from parsing
+    let Some(doc) = doc else {
+        eprintln!("Error parsing '{}': not a tree document", file.display());
+        ExitCode::Usage.exit();
     };
 
-    let view = match tree_doc_core::build_trunk_view(&doc) {
+    if let Some(tree) = tree {
+        match tree_doc_core::build_tree_view(&doc, tree) {
+            Ok(view) => output::print_trunk_view(&view),
+            Err(e) => {
+                eprintln!("Error building tree view: {e}");
+                ExitCode::Usage.exit();
+            }
+        }
+        return;
+    }
+
+    let edge_type = edge_type.map(EdgeType::from);
+    let keep = |e: &Edge| edge_type.as_ref().is_none_or(|t| e.has_type(t));
+
+    if full {
+        let view = match tree_doc_core::build_full_view_filtered(&doc, keep) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("Error building full view: {e}");
+                ExitCode::Usage.exit();
+            }
+        };
+        output::print_full_view(&view);
+        return;
+    }
+
+    let view = match tree_doc_core::build_trunk_view_filtered(&doc, keep) {
         Ok(v) => v,
         Err(e) => {
             eprintln!("Error building trunk view: {e}");
-            process::exit(2);
+            ExitCode::Usage.exit();
         }
     };
 
@@ -0,0 +1,91 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Deserialize;
+use tree_doc_core::error::Rule;
+use tree_doc_core::ValidationOptions;
+
+use crate::commands::validate::FailOn;
+
+/// Name of the optional config file, looked up in the working directory.
+pub const CONFIG_FILE: &str = ".tree-doc.toml";
+
+/// Team settings for `validate`, read from `.tree-doc.toml`.
+///
+/// ```toml
+/// allow = ["orphan-node", "TDF005"]
+/// fail-on = "warning"
+/// statuses = ["review"]
+/// features = ["audio"]
+/// max-content-len = 2000
+/// max-trunk-length = 40
+/// ```
+#[derive(Debug, Default)]
+pub struct Config {
+    pub allowed_rules: Vec<Rule>,
+    pub fail_on: Option<FailOn>,
+    pub statuses: Vec<String>,
+    pub features: Vec<String>,
+    pub max_content_len: Option<usize>,
+    pub max_trunk_length: Option<usize>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+struct RawConfig {
+    allow: Vec<String>,
+    fail_on: Option<FailOn>,
+    statuses: Vec<String>,
+    features: Vec<String>,
+    max_content_len: Option<usize>,
+    max_trunk_length: Option<usize>,
+}
+
+impl Config {
+    /// Load `.tree-doc.toml` from `dir`. A missing file yields the defaults.
+    pub fn load(dir: &Path) -> Result<Config, String> {
+        let path = dir.join(CONFIG_FILE);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(format!("Error reading '{}': {e}", path.display())),
+        };
+
+        let raw: RawConfig = toml::from_str(&text)
+            .map_err(|e| format!("Error parsing '{}': {e}", path.display()))?;
+        let allowed_rules = raw
+            .allow
+            .iter()
+            .map(|name| name.parse())
+            .collect::<Result<_, String>>()
+            .map_err(|e| format!("Error in '{}': {e}", path.display()))?;
+
+        Ok(Config {
+            allowed_rules,
+            fail_on: raw.fail_on,
+            statuses: raw.statuses,
+            features: raw.features,
+            max_content_len: raw.max_content_len,
+            max_trunk_length: raw.max_trunk_length,
+        })
+    }
+
+    /// Merge with command-line flags: flag thresholds win, and flag rule
+    /// allowances add to the configured ones.
+    pub fn merge(&self, allowed: &[Rule], fail_on: Option<FailOn>) -> (ValidationOptions, FailOn) {
+        let options = ValidationOptions {
+            allowed_rules: self.allowed_rules.iter().chain(allowed).cloned().collect(),
+            extra_statuses: self.statuses.iter().cloned().collect(),
+            extra_features: self.features.iter().cloned().collect(),
+            max_content_len: self.max_content_len,
+            extra_schema: None,
+            strict: false,
+            max_trunk_length: self.max_trunk_length,
+            group_dangling_edges: false,
+            cycles_are_errors: false,
+        };
+        let fail_on = fail_on.or(self.fail_on).unwrap_or(FailOn::Error);
+        (options, fail_on)
+    }
+}
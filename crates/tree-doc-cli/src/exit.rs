@@ -0,0 +1,25 @@
+use std::process;
+
+/// Process exit codes, shared by every command.
+///
+/// When several outcomes apply, as in a batch `validate`, the highest code
+/// wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExitCode {
+    /// Success.
+    Ok = 0,
+    /// The document has errors, or a query on it has no answer (no path
+    /// between two nodes, no topological order).
+    ValidationFailed = 1,
+    /// Bad arguments or input that is not a tree document, such as malformed
+    /// JSON. Clap also exits with 2 for invalid command lines.
+    Usage = 2,
+    /// A file could not be read or written.
+    IoError = 3,
+}
+
+impl ExitCode {
+    pub fn exit(self) -> ! {
+        process::exit(self as i32)
+    }
+}
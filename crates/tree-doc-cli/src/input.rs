@@ -0,0 +1,88 @@
+use std::io::{self, Read};
+use std::path::Path;
+
+use tree_doc_core::parse::is_gzip;
+use tree_doc_core::{TreeDocument, ValidationResult};
+
+use crate::exit::ExitCode;
+use crate::output::{self, Verbosity};
+
+/// Read a document from `path`, or from stdin when the path is `-`.
+///
+/// Gzipped input is decompressed transparently, whatever its name. A file
+/// named `*.gz` must actually be gzipped.
+pub fn read_input(path: &Path) -> io::Result<String> {
+    tree_doc_core::decode_maybe_gzip(read_bytes(path)?)
+}
+
+/// Read the raw bytes of a document from `path`, or from stdin when the path
+/// is `-`, without decompressing them. A file named `*.gz` must actually be
+/// gzipped.
+pub fn read_bytes(path: &Path) -> io::Result<Vec<u8>> {
+    let bytes = if path == Path::new("-") {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        buf
+    } else {
+        std::fs::read(path)?
+    };
+
+    if is_gz_path(path) && !is_gzip(&bytes) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "file has a .gz extension but is not gzip-compressed",
+        ));
+    }
+    Ok(bytes)
+}
+
+/// [`read_input`], exiting with [`ExitCode::IoError`] when `file` cannot be
+/// read.
+pub fn read_or_exit(file: &Path) -> String {
+    read_input(file).unwrap_or_else(|e| {
+        eprintln!("Error reading file '{}': {e}", file.display());
+        ExitCode::IoError.exit();
+    })
+}
+
+/// Parse `json_str`, read from `file`, exiting with [`ExitCode::Usage`] when
+/// it is not a tree document.
+pub fn parse_or_exit(file: &Path, json_str: &str) -> TreeDocument {
+    tree_doc_core::parse(json_str).unwrap_or_else(|e| {
+        eprintln!("Error parsing '{}': {e}", file.display());
+        ExitCode::Usage.exit();
+    })
+}
+
+/// Read and parse a document without validating it.
+pub fn load_document(file: &Path) -> TreeDocument {
+    parse_or_exit(file, &read_or_exit(file))
+}
+
+/// Read, validate, and parse a document, parsing it only once. The document
+/// is `None` when schema errors kept it from parsing.
+pub fn load_validated(file: &Path) -> (ValidationResult, Option<TreeDocument>) {
+    let json_str = read_or_exit(file);
+    tree_doc_core::validate_and_parse(&json_str).unwrap_or_else(|e| {
+        eprintln!("Error parsing '{}': {e}", file.display());
+        ExitCode::Usage.exit();
+    })
+}
+
+/// [`load_validated`] for commands that need a valid document. If it has
+/// errors they are printed, followed by "Fix them before {action}.", and the
+/// process exits with [`ExitCode::ValidationFailed`].
+pub fn load_valid_document(file: &Path, action: &str) -> TreeDocument {
+    let (result, doc) = load_validated(file);
+    if !result.is_valid {
+        output::print_validation_result(&result, file, Verbosity::Normal);
+        eprintln!("\nDocument has errors. Fix them before {action}.");
+        ExitCode::ValidationFailed.exit();
+    }
+    doc.expect("a valid document always parses")
+}
+
+/// Whether `path` names a gzipped file by its `.gz` extension.
+pub fn is_gz_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
@@ -1,42 +1,298 @@
-use std::path::PathBuf;
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use clap::{Parser, Subcommand};
+use tree_doc_core::error::Rule;
+
+use crate::commands::info::InfoCount;
+use crate::commands::validate::FailOn;
+use crate::config::Config;
+use crate::exit::ExitCode;
+use crate::output::{ExportFormat, OutputFormat, Verbosity};
 
 mod commands;
+mod config;
+mod exit;
+mod input;
 mod output;
 
 #[derive(Parser)]
 #[command(name = "tree-doc", about = "Tree Document Format validator and viewer")]
 struct Cli {
+    /// Disable colored output (also honored via the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Validate a .tree.json file
+    /// Validate one or more .tree.json files
     Validate {
-        /// Path to the .tree.json file
-        file: PathBuf,
+        /// Paths to the .tree.json files, or `-` to read from stdin
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// Print nothing for valid files and only errors for invalid ones (text format)
+        #[arg(long, short)]
+        quiet: bool,
+        /// Print only each file's status line and diagnostic counts (text format)
+        #[arg(long, conflicts_with = "quiet")]
+        summary: bool,
+        /// Lowest severity that makes the command exit with code 1 [default: error]
+        #[arg(long, value_enum)]
+        fail_on: Option<FailOn>,
+        /// Do not report a rule, by name or code (e.g. `orphan-node` or `TDF006`); repeatable
+        #[arg(long = "allow", value_name = "RULE")]
+        allowed: Vec<Rule>,
+        /// Extra JSON Schema to check alongside the built-in tier schema
+        #[arg(long)]
+        schema: Option<PathBuf>,
+        /// Warn about fields the format does not define
+        #[arg(long)]
+        strict: bool,
+        /// Warn when the trunk has more than N edges
+        #[arg(long, value_name = "N")]
+        max_trunk_length: Option<usize>,
+        /// Report each missing node once instead of every edge that uses it
+        #[arg(long)]
+        group_dangling: bool,
+        /// Treat any cycle as an error, requiring a strict DAG
+        #[arg(long)]
+        strict_acyclic: bool,
     },
     /// View the trunk path of a .tree.json file
     View {
-        /// Path to the .tree.json file
+        /// Path to the .tree.json file, or `-` to read from stdin
         file: PathBuf,
+        /// Render the entire branching tree instead of just the trunk
+        #[arg(long)]
+        full: bool,
+        /// Render even if the document has errors, listing them first
+        #[arg(long)]
+        force: bool,
+        /// Only follow branch edges whose `type` or `linkType` is this; the
+        /// trunk is always shown
+        #[arg(long)]
+        edge_type: Option<String>,
+        /// Walk the trunk of one tree in a multi-tree document
+        #[arg(long, conflicts_with_all = ["full", "edge_type"])]
+        tree: Option<String>,
     },
     /// Show summary information about a .tree.json file
     Info {
-        /// Path to the .tree.json file
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Print only this number, for use in scripts
+        #[arg(long, value_enum)]
+        count: Option<InfoCount>,
+    },
+    /// Compare two .tree.json files structurally
+    Diff {
+        /// Path to the old version
+        old: PathBuf,
+        /// Path to the new version
+        new: PathBuf,
+    },
+    /// Repair dangling edges, duplicate edges, and orphan nodes
+    Fix {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Overwrite the file with the repaired document
+        #[arg(long)]
+        write: bool,
+    },
+    /// Reformat a .tree.json file with canonical field order, node and edge
+    /// order, and indentation
+    Fmt {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Overwrite the file instead of printing to stdout
+        #[arg(long)]
+        write: bool,
+    },
+    /// Show the shortest route between two nodes
+    Path {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Id of the starting node
+        from: String,
+        /// Id of the destination node
+        to: String,
+    },
+    /// List every node reachable from a starting node
+    Reachable {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Id of the starting node
+        from: String,
+    },
+    /// List every path from the root to an ending
+    Paths {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Stop after this many paths
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// Export a .tree.json file to another format
+    Export {
+        /// Path to the .tree.json file, or `-` to read from stdin
         file: PathBuf,
+        /// Target format; all but csv print to stdout
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// Directory to write csv tables into
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+        /// Only export branch edges whose `type` or `linkType` is this; trunk
+        /// edges are always kept
+        #[arg(long)]
+        edge_type: Option<String>,
+        /// Only draw the trunk path from the root (dot and mermaid)
+        #[arg(long, conflicts_with = "edge_type")]
+        trunk_only: bool,
     },
+    /// Show shape metrics: branching, depth, endings, and components
+    Stats {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+    },
+    /// List nodes in topological order (acyclic documents only)
+    Order {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+    },
+    /// List every node where a reading path ends
+    Endings {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+    },
+    /// Search node content for a substring
+    Find {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+        /// Text to look for
+        query: String,
+        /// Match regardless of case
+        #[arg(long, short = 'i')]
+        ignore_case: bool,
+    },
+    /// Show each node's in-degree and out-degree, busiest first
+    Degrees {
+        /// Path to the .tree.json file, or `-` to read from stdin
+        file: PathBuf,
+    },
+    /// Write a minimal valid .tree.json file to start from
+    Init {
+        /// Path of the file to create
+        out: PathBuf,
+        /// Format tier to scaffold: 0, 1 (metadata and features), or 2 (trees)
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=2))]
+        tier: u8,
+        /// Overwrite the file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Color is off when asked for via `--no-color` or `NO_COLOR`, and otherwise
+/// only used when stdout is a terminal.
+fn use_color(no_color_flag: bool) -> bool {
+    if no_color_flag || env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    io::stdout().is_terminal()
+}
+
+/// Read and compile a `--schema` file, exiting on failure.
+fn load_schema(path: &Path) -> jsonschema::Validator {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        eprintln!("Error reading schema '{}': {e}", path.display());
+        ExitCode::IoError.exit();
+    });
+    tree_doc_core::compile_schema(&text).unwrap_or_else(|e| {
+        eprintln!("Error compiling schema '{}': {e}", path.display());
+        ExitCode::Usage.exit();
+    })
 }
 
 fn main() {
     let cli = Cli::parse();
+    colored::control::set_override(use_color(cli.no_color));
 
     match &cli.command {
-        Commands::Validate { file } => commands::validate::run(file),
-        Commands::View { file } => commands::view::run(file),
-        Commands::Info { file } => commands::info::run(file),
+        Commands::Validate {
+            files,
+            format,
+            quiet,
+            summary,
+            fail_on,
+            allowed,
+            schema,
+            strict,
+            max_trunk_length,
+            group_dangling,
+            strict_acyclic,
+        } => {
+            let config = Config::load(Path::new(".")).unwrap_or_else(|e| {
+                eprintln!("{e}");
+                ExitCode::Usage.exit();
+            });
+            let (mut options, fail_on) = config.merge(allowed, *fail_on);
+            if let Some(path) = schema {
+                options.extra_schema = Some(Arc::new(load_schema(path)));
+            }
+            options.strict |= *strict;
+            options.group_dangling_edges = *group_dangling;
+            options.cycles_are_errors = *strict_acyclic;
+            if max_trunk_length.is_some() {
+                options.max_trunk_length = *max_trunk_length;
+            }
+            let verbosity = if *quiet {
+                Verbosity::Quiet
+            } else if *summary {
+                Verbosity::Summary
+            } else {
+                Verbosity::Normal
+            };
+            commands::validate::run(files, *format, verbosity, fail_on, &options)
+        }
+        Commands::View {
+            file,
+            full,
+            force,
+            edge_type,
+            tree,
+        } => commands::view::run(file, *full, *force, edge_type.as_deref(), tree.as_deref()),
+        Commands::Info { file, count } => commands::info::run(file, *count),
+        Commands::Diff { old, new } => commands::diff::run(old, new),
+        Commands::Fix { file, write } => commands::fix::run(file, *write),
+        Commands::Fmt { file, write } => commands::fmt::run(file, *write),
+        Commands::Path { file, from, to } => commands::path::run(file, from, to),
+        Commands::Reachable { file, from } => commands::reachable::run(file, from),
+        Commands::Paths { file, limit } => commands::paths::run(file, *limit),
+        Commands::Export {
+            file,
+            format,
+            out_dir,
+            edge_type,
+            trunk_only,
+        } => commands::export::run(file, *format, out_dir, edge_type.as_deref(), *trunk_only),
+        Commands::Stats { file } => commands::stats::run(file),
+        Commands::Order { file } => commands::order::run(file),
+        Commands::Endings { file } => commands::endings::run(file),
+        Commands::Find {
+            file,
+            query,
+            ignore_case,
+        } => commands::find::run(file, query, *ignore_case),
+        Commands::Degrees { file } => commands::degrees::run(file),
+        Commands::Init { out, tier, force } => commands::init::run(out, *tier, *force),
     }
 }
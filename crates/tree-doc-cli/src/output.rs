@@ -1,10 +1,56 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
-use colored::Colorize;
-use tree_doc_core::error::ValidationResult;
-use tree_doc_core::viewer::TrunkView;
+use clap::ValueEnum;
+use colored::{Color, Colorize};
+use tree_doc_core::diff::TreeDiff;
+use tree_doc_core::error::{Rule, ValidationResult};
+use tree_doc_core::stats::ExtendedStats;
+use tree_doc_core::types::Node;
+use tree_doc_core::viewer::{FullView, FullViewNode, TrunkView};
+use tree_doc_core::TreeDocument;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored, human-readable output
+    Text,
+    /// Machine-readable JSON (same shape as the WASM `validate` payload)
+    Json,
+}
+
+/// How much of a validation report to print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Status line, every diagnostic, and a summary
+    Normal,
+    /// Nothing for valid documents; only errors for invalid ones
+    Quiet,
+    /// Status line and the summary counts, without individual diagnostics
+    Summary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+    /// GraphML XML
+    Graphml,
+    /// Markdown outline of the trunk
+    Markdown,
+    /// Standalone HTML page with collapsible branches
+    Html,
+    /// `nodes.csv` and `edges.csv`, written to --out-dir
+    Csv,
+}
+
+pub fn print_validation_result(result: &ValidationResult, file: &Path, verbosity: Verbosity) {
+    let quiet = verbosity == Verbosity::Quiet;
+    if quiet && result.is_valid {
+        return;
+    }
 
-pub fn print_validation_result(result: &ValidationResult, file: &PathBuf) {
     if result.is_valid {
         println!(
             "{} {} is valid ({} nodes, {} edges, tier {})",
@@ -22,34 +68,43 @@ pub fn print_validation_result(result: &ValidationResult, file: &PathBuf) {
         );
     }
 
-    for diag in &result.errors {
-        println!(
-            "  {} {}: {}",
-            "error".red().bold(),
-            format!("[{}]", diag.rule).dimmed(),
-            diag.message,
-        );
-        println!("    {} {}", "at".dimmed(), diag.location);
+    let summary_only = verbosity == Verbosity::Summary;
+    if !summary_only {
+        for diag in &result.errors {
+            println!(
+                "  {} {}: {}",
+                "error".red().bold(),
+                format!("[{} {}]", diag.rule.code(), diag.rule).dimmed(),
+                diag.message,
+            );
+            println!("    {} {}", "at".dimmed(), diag.location);
+        }
     }
 
-    for diag in &result.warnings {
-        println!(
-            "  {} {}: {}",
-            "warning".yellow().bold(),
-            format!("[{}]", diag.rule).dimmed(),
-            diag.message,
-        );
-        println!("    {} {}", "at".dimmed(), diag.location);
+    if quiet {
+        return;
     }
 
-    for diag in &result.advisories {
-        println!(
-            "  {} {}: {}",
-            "advisory".blue().bold(),
-            format!("[{}]", diag.rule).dimmed(),
-            diag.message,
-        );
-        println!("    {} {}", "at".dimmed(), diag.location);
+    if !summary_only {
+        for diag in &result.warnings {
+            println!(
+                "  {} {}: {}",
+                "warning".yellow().bold(),
+                format!("[{} {}]", diag.rule.code(), diag.rule).dimmed(),
+                diag.message,
+            );
+            println!("    {} {}", "at".dimmed(), diag.location);
+        }
+
+        for diag in &result.advisories {
+            println!(
+                "  {} {}: {}",
+                "advisory".blue().bold(),
+                format!("[{} {}]", diag.rule.code(), diag.rule).dimmed(),
+                diag.message,
+            );
+            println!("    {} {}", "at".dimmed(), diag.location);
+        }
     }
 
     // Summary line
@@ -58,7 +113,9 @@ pub fn print_validation_result(result: &ValidationResult, file: &PathBuf) {
     let advisory_count = result.advisories.len();
     let total = error_count + warning_count + advisory_count;
     if total > 0 {
-        println!();
+        if !summary_only {
+            println!();
+        }
         let mut parts = Vec::new();
         if error_count > 0 {
             parts.push(format!("{} error{}", error_count, if error_count == 1 { "" } else { "s" }));
@@ -73,6 +130,42 @@ pub fn print_validation_result(result: &ValidationResult, file: &PathBuf) {
     }
 }
 
+pub fn print_validation_json(result: &ValidationResult) {
+    let json = serde_json::to_string_pretty(result).expect("validation result serializes to JSON");
+    println!("{json}");
+}
+
+/// Print one JSON array covering several files: `[{"file": ..., "result": ...}]`.
+pub fn print_batch_validation_json(results: &[(&Path, ValidationResult)]) {
+    let entries: Vec<_> = results
+        .iter()
+        .map(|(file, result)| {
+            serde_json::json!({
+                "file": file.display().to_string(),
+                "result": result,
+            })
+        })
+        .collect();
+    let json =
+        serde_json::to_string_pretty(&entries).expect("validation results serialize to JSON");
+    println!("{json}");
+}
+
+pub fn print_batch_summary(file_count: usize, invalid_count: usize) {
+    println!();
+    let summary = format!(
+        "{} file{}, {} invalid",
+        file_count,
+        if file_count == 1 { "" } else { "s" },
+        invalid_count
+    );
+    if invalid_count > 0 {
+        println!("{}", summary.red().bold());
+    } else {
+        println!("{}", summary.green().bold());
+    }
+}
+
 pub fn print_trunk_view(view: &TrunkView) {
     println!("{}", view.title.bold());
     println!("{}", "─".repeat(view.title.len()).dimmed());
@@ -94,11 +187,16 @@ pub fn print_trunk_view(view: &TrunkView) {
             );
         }
 
-        if step.branch_count > 0 {
-            let badge = format!("+{} branch{}", step.branch_count, if step.branch_count == 1 { "" } else { "es" });
+        if !step.branches.is_empty() {
+            let count = step.branches.len();
+            let badge = format!("+{count} branch{}", if count == 1 { "" } else { "es" });
             println!("  {} {}", "└──".dimmed(), badge.yellow());
-            for label in &step.branch_labels {
+            for branch in &step.branches {
+                let Some(label) = &branch.label else { continue };
                 println!("      {} {}", "·".dimmed(), label);
+                if let Some(description) = &branch.description {
+                    println!("        {}", description.dimmed());
+                }
             }
         }
 
@@ -108,7 +206,63 @@ pub fn print_trunk_view(view: &TrunkView) {
     }
 }
 
-pub fn print_info(result: &ValidationResult, file: &PathBuf) {
+pub fn print_full_view(view: &FullView) {
+    println!("{}", view.title.bold());
+    println!("{}", "─".repeat(view.title.len()).dimmed());
+    println!("{}", view.stats.dimmed());
+    println!();
+
+    println!(
+        "{} {}",
+        format!("[{}]", view.root.node_id).cyan(),
+        view.root.content
+    );
+    print_full_view_children(&view.root);
+}
+
+/// Print the tree below `root` depth-first, keeping pending nodes on an
+/// explicit stack rather than recursing once per level.
+fn print_full_view_children(root: &FullViewNode) {
+    // (node, prefix for its children, index of the next child to print)
+    let mut stack = vec![(root, String::new(), 0)];
+    while let Some((node, prefix, next)) = stack.last_mut() {
+        let node: &FullViewNode = node;
+        let Some(child) = node.children.get(*next) else {
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+        let is_last = *next == node.children.len();
+        let connector = if is_last { "└──" } else { "├──" };
+
+        let mut line = format!("{prefix}{} ", connector.dimmed());
+        if child.via_trunk {
+            line.push_str(&format!("{} ", "[trunk]".green()));
+        }
+        if let Some(label) = &child.via_label {
+            line.push_str(&format!("{} ", format!("({label})").yellow()));
+        }
+
+        if child.is_back_edge {
+            println!("{line}{}", format!("↻ -> {}", child.node_id).dimmed());
+            continue;
+        }
+
+        println!(
+            "{line}{} {}",
+            format!("[{}]", child.node_id).cyan(),
+            child.content
+        );
+
+        let child_prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+        if let Some(description) = &child.via_description {
+            println!("{child_prefix}{}", description.dimmed());
+        }
+        stack.push((child, child_prefix, 0));
+    }
+}
+
+pub fn print_info(result: &ValidationResult, file: &Path) {
     let stats = &result.stats;
     println!("{}", file.display().to_string().bold());
     println!("{}", "─".repeat(file.display().to_string().len()).dimmed());
@@ -117,6 +271,13 @@ pub fn print_info(result: &ValidationResult, file: &PathBuf) {
     println!("  {:<16} {}", "Edges:".dimmed(), stats.edge_count);
     println!("  {:<16} {}", "Trunk length:".dimmed(), stats.trunk_length);
     println!("  {:<16} {}", "Branches:".dimmed(), stats.branch_count);
+    println!("  {:<16} {}", "Components:".dimmed(), stats.component_count);
+    println!(
+        "  {:<16} {}",
+        "Longest path:".dimmed(),
+        stats.longest_path_len
+    );
+    println!("  {:<16} {}", "Max out-degree:".dimmed(), stats.max_out_degree);
     println!(
         "  {:<16} {}",
         "Valid:".dimmed(),
@@ -126,4 +287,291 @@ pub fn print_info(result: &ValidationResult, file: &PathBuf) {
             "no".red().to_string()
         }
     );
+
+    // Diagnostic counts, colored like `validate` output; zero counts are omitted
+    for (label, count, color) in [
+        ("Errors:", result.errors.len(), Color::Red),
+        ("Warnings:", result.warnings.len(), Color::Yellow),
+        ("Advisories:", result.advisories.len(), Color::Blue),
+    ] {
+        if count > 0 {
+            println!("  {:<16} {}", label.dimmed(), count.to_string().color(color).bold());
+        }
+    }
+}
+
+pub fn print_stats(stats: &ExtendedStats, file: &Path) {
+    let or_na = |value: Option<usize>| value.map_or("n/a".to_string(), |v| v.to_string());
+
+    println!("{}", file.display().to_string().bold());
+    println!("{}", "─".repeat(file.display().to_string().len()).dimmed());
+    println!("  {:<16} {}", "Nodes:".dimmed(), stats.node_count);
+    println!("  {:<16} {}", "Edges:".dimmed(), stats.edge_count);
+    println!("  {:<16} {:.2}", "Avg out-degree:".dimmed(), stats.average_out_degree);
+    println!("  {:<16} {}", "Max depth:".dimmed(), or_na(stats.max_depth));
+    println!("  {:<16} {}", "Longest path:".dimmed(), or_na(stats.longest_path));
+    println!("  {:<16} {}", "Endings:".dimmed(), stats.ending_count);
+    println!("  {:<16} {}", "Components:".dimmed(), stats.component_count);
+    println!(
+        "  {:<16} {:.1}%",
+        "Trunk coverage:".dimmed(),
+        stats.trunk_coverage * 100.0
+    );
+}
+
+pub fn print_diff(diff: &TreeDiff, old: &Path, new: &Path) {
+    let header = format!("{} -> {}", old.display(), new.display());
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+
+    if diff.is_empty() {
+        println!("  {}", "No structural differences".dimmed());
+        return;
+    }
+
+    for id in &diff.removed_nodes {
+        println!("  {} node {}", "-".red().bold(), id.red());
+    }
+    for id in &diff.added_nodes {
+        println!("  {} node {}", "+".green().bold(), id.green());
+    }
+    for id in &diff.changed_content {
+        println!(
+            "  {} node {} {}",
+            "~".yellow().bold(),
+            id.yellow(),
+            "(content changed)".dimmed()
+        );
+    }
+    for edge in &diff.removed_edges {
+        let edge = format!("{} -> {}", edge.source, edge.target);
+        println!("  {} edge {}", "-".red().bold(), edge.red());
+    }
+    for edge in &diff.added_edges {
+        let edge = format!("{} -> {}", edge.source, edge.target);
+        println!("  {} edge {}", "+".green().bold(), edge.green());
+    }
+    if let Some(trunk) = &diff.trunk_change {
+        println!("  {} trunk", "~".yellow().bold());
+        println!("      {} {}", "-".red().bold(), trunk.old.join(" -> ").red());
+        println!("      {} {}", "+".green().bold(), trunk.new.join(" -> ").green());
+    }
+}
+
+pub fn print_path(doc: &TreeDocument, path: &[String]) {
+    let hops = path.len() - 1;
+    let header = format!(
+        "{} -> {} ({hops} step{})",
+        path[0],
+        path[hops],
+        if hops == 1 { "" } else { "s" }
+    );
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+
+    let contents = node_contents(doc);
+    for (i, id) in path.iter().enumerate() {
+        let content = contents.get(id.as_str()).copied().unwrap_or("");
+        println!("{} {}", format!("[{id}]").cyan(), content);
+        if i < hops {
+            println!("  {}", "↓".dimmed());
+        }
+    }
+}
+
+/// Content by node id, so listings need not scan `doc.nodes` for every line.
+fn node_contents(doc: &TreeDocument) -> HashMap<&str, &str> {
+    doc.nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.content.as_str()))
+        .collect()
+}
+
+pub fn print_reachable(doc: &TreeDocument, from: &str, reachable: &HashSet<String>) {
+    let header = format!(
+        "{} node{} reachable from {from}",
+        reachable.len(),
+        if reachable.len() == 1 { "" } else { "s" }
+    );
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+
+    for node in doc.nodes.iter().filter(|n| reachable.contains(&n.id)) {
+        println!("{} {}", format!("[{}]", node.id).cyan(), node.content);
+    }
+}
+
+/// Print one numbered line per path. `truncated` means more paths exist
+/// beyond `limit`.
+pub fn print_paths(paths: &[Vec<String>], limit: usize, truncated: bool) {
+    let mut header = format!(
+        "{} path{} to an ending",
+        paths.len(),
+        if paths.len() == 1 { "" } else { "s" }
+    );
+    if truncated {
+        header.push_str(&format!(" (stopped at --limit {limit})"));
+    }
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+
+    let width = paths.len().to_string().len();
+    for (i, path) in paths.iter().enumerate() {
+        println!(
+            "{} {}",
+            format!("{:>width$}.", i + 1).dimmed(),
+            path.join(&format!(" {} ", "→".dimmed()))
+        );
+    }
+}
+
+pub fn print_order(doc: &TreeDocument, order: &[String]) {
+    let width = order.len().to_string().len();
+    let contents = node_contents(doc);
+    for (i, id) in order.iter().enumerate() {
+        let content = contents.get(id.as_str()).copied().unwrap_or("");
+        println!(
+            "{} {} {}",
+            format!("{:>width$}.", i + 1).dimmed(),
+            format!("[{id}]").cyan(),
+            content
+        );
+    }
+}
+
+pub fn print_endings(endings: &[&Node]) {
+    let header = format!(
+        "{} ending{}",
+        endings.len(),
+        if endings.len() == 1 { "" } else { "s" }
+    );
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+
+    for node in endings {
+        println!("{} {}", format!("[{}]", node.id).cyan(), node.content);
+    }
+}
+
+/// Print each matching node with a one-line snippet of its content around the
+/// first match, which is highlighted.
+pub fn print_found(nodes: &[&Node], query: &str, ignore_case: bool) {
+    let header = format!(
+        "{} node{} matching \"{query}\"",
+        nodes.len(),
+        if nodes.len() == 1 { "" } else { "s" }
+    );
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+
+    for node in nodes {
+        let content = node.content.replace('\n', " ");
+        let snippet = match match_range(&content, query, ignore_case) {
+            Some((start, end)) => format!(
+                "{}{}{}",
+                snippet_before(&content[..start]),
+                content[start..end].yellow().bold(),
+                snippet_after(&content[end..])
+            ),
+            None => snippet_after(&content),
+        };
+        println!("{} {}", format!("[{}]", node.id).cyan(), snippet);
+    }
+}
+
+/// Characters of context kept on each side of a match.
+const SNIPPET_CONTEXT: usize = 30;
+
+fn snippet_before(text: &str) -> String {
+    let count = text.chars().count();
+    if count <= SNIPPET_CONTEXT {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - SNIPPET_CONTEXT).collect();
+    format!("…{}", kept.trim_start())
+}
+
+fn snippet_after(text: &str) -> String {
+    if text.chars().count() <= SNIPPET_CONTEXT {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(SNIPPET_CONTEXT).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// Byte range of the first occurrence of `needle` in `haystack`. Ignoring
+/// case compares lowercased characters but returns the range in the original
+/// text, whose length may differ from the needle's.
+fn match_range(haystack: &str, needle: &str, ignore_case: bool) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return None;
+    }
+    if !ignore_case {
+        return haystack.find(needle).map(|start| (start, start + needle.len()));
+    }
+    let needle = needle.to_lowercase();
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut folded = String::new();
+        for (offset, c) in haystack[start..].char_indices() {
+            folded.extend(c.to_lowercase());
+            if folded == needle {
+                return Some((start, start + offset + c.len_utf8()));
+            }
+            if !needle.starts_with(&folded) {
+                return None;
+            }
+        }
+        None
+    })
+}
+
+/// Print a degree table, highest total degree first and ties in document
+/// order.
+pub fn print_degrees(doc: &TreeDocument, degrees: &HashMap<String, (usize, usize)>) {
+    let mut seen = HashSet::new();
+    let mut rows: Vec<(&str, usize, usize)> = Vec::new();
+    for node in doc.nodes.iter().filter(|n| seen.insert(n.id.as_str())) {
+        if let Some(&(in_degree, out_degree)) = degrees.get(&node.id) {
+            rows.push((&node.id, in_degree, out_degree));
+        }
+    }
+    rows.sort_by_key(|&(_, in_degree, out_degree)| std::cmp::Reverse(in_degree + out_degree));
+
+    let width = rows.iter().map(|(id, _, _)| id.chars().count()).max().unwrap_or(0).max(4);
+    let header = format!("{:<width$}  {:>4}  {:>4}  {:>5}", "Node", "In", "Out", "Total");
+    println!("{}", header.bold());
+    println!("{}", "─".repeat(header.chars().count()).dimmed());
+    for (id, in_degree, out_degree) in rows {
+        println!(
+            "{}  {in_degree:>4}  {out_degree:>4}  {:>5}",
+            format!("{id:<width$}").cyan(),
+            in_degree + out_degree
+        );
+    }
+}
+
+pub fn print_fixes(rules: &[Rule], diff: &TreeDiff, file: &Path, written: bool) {
+    if rules.is_empty() {
+        println!("{} {} has nothing to fix", "✓".green().bold(), file.display());
+        return;
+    }
+
+    let verb = if written { "Fixed" } else { "Would fix" };
+    println!("{} {}", format!("{verb}:").bold(), file.display());
+    for rule in rules {
+        println!("  {} {}", "·".dimmed(), format!("[{} {rule}]", rule.code()).dimmed());
+    }
+
+    for id in &diff.removed_nodes {
+        println!("  {} node {}", "-".red().bold(), id.red());
+    }
+    for edge in &diff.removed_edges {
+        let edge = format!("{} -> {}", edge.source, edge.target);
+        println!("  {} edge {}", "-".red().bold(), edge.red());
+    }
+
+    if !written {
+        println!();
+        println!("{}", "Run with --write to apply these changes.".dimmed());
+    }
 }
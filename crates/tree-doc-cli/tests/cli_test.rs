@@ -0,0 +1,906 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn tree_doc() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_tree-doc"))
+}
+
+fn example(name: &str) -> String {
+    format!("{}/../../examples/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn validate_json_output_valid() {
+    let output = tree_doc()
+        .args([
+            "validate",
+            "--format",
+            "json",
+            &example("minimal.tree.json"),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["isValid"], true);
+    assert_eq!(value["stats"]["nodeCount"], 3);
+    assert_eq!(value["stats"]["edgeCount"], 2);
+    assert!(value["errors"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn validate_json_output_invalid() {
+    let output = tree_doc()
+        .args([
+            "validate",
+            "--format",
+            "json",
+            &example("invalid/trunk-cycle.tree.json"),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["isValid"], false);
+    let error = &value["errors"][0];
+    assert_eq!(error["rule"], "trunk-cycle");
+    assert_eq!(error["severity"], "error");
+    assert_eq!(error["location"]["kind"], "path");
+    assert_eq!(error["code"], "TDF004");
+}
+
+#[test]
+fn view_force_renders_despite_errors() {
+    let file = example("invalid/dangling-edge.tree.json");
+    let output = tree_doc().args(["view", &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!String::from_utf8(output.stdout).unwrap().contains("(end of trunk)"));
+
+    let output = tree_doc().args(["view", "--force", &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let error_at = stdout.find("TDF003").unwrap();
+    let trunk_at = stdout.find("[n1] Start node").unwrap();
+    assert!(error_at < trunk_at, "{stdout}");
+    assert!(stdout.contains("(end of trunk)"));
+}
+
+#[test]
+fn view_tree_walks_one_tree() {
+    let file = example("anthology.tree.json");
+    let output = tree_doc().args(["view", "--tree", "market", &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("Market Day\n"), "{stdout}");
+    assert!(stdout.contains("[supper]"));
+    assert!(!stdout.contains("[harbor]"));
+
+    let output = tree_doc().args(["view", "--tree", "nowhere", &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Tree 'nowhere' not found in trees"));
+}
+
+#[test]
+fn view_full_shows_more_than_trunk() {
+    let trunk = tree_doc()
+        .args(["view", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    let full = tree_doc()
+        .args(["view", "--full", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(trunk.status.code(), Some(0));
+    assert_eq!(full.status.code(), Some(0));
+
+    // Count lines that render a node (`[id] content`)
+    let ids = [
+        "start", "enter", "climb", "fountain", "explore", "wish", "ending",
+    ];
+    let node_lines = |text: &str| {
+        text.lines()
+            .filter(|l| ids.iter().any(|id| l.contains(&format!("[{id}]"))))
+            .count()
+    };
+    let full_text = String::from_utf8(full.stdout).unwrap();
+    let trunk_text = String::from_utf8(trunk.stdout).unwrap();
+    assert_eq!(node_lines(&trunk_text), 5);
+    assert_eq!(node_lines(&full_text), 7);
+    assert!(full_text.contains("[climb]"));
+    assert!(full_text.contains("↻ -> ending"));
+}
+
+#[test]
+fn validate_reads_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let json = std::fs::read_to_string(example("minimal.tree.json")).unwrap();
+    let mut child = tree_doc()
+        .args(["validate", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(json.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("is valid"));
+}
+
+#[test]
+fn validate_missing_file_fails() {
+    let output = tree_doc()
+        .args(["validate", &example("does-not-exist.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Error reading file"));
+}
+
+#[test]
+fn validate_multiple_files() {
+    let output = tree_doc()
+        .args([
+            "validate",
+            &example("minimal.tree.json"),
+            &example("invalid/duplicate-ids.tree.json"),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("minimal.tree.json is valid"));
+    assert!(stdout.contains("duplicate-ids.tree.json has validation errors"));
+    assert!(stdout.contains("2 files, 1 invalid"));
+}
+
+#[test]
+fn validate_multiple_files_all_valid() {
+    let output = tree_doc()
+        .args([
+            "validate",
+            &example("minimal.tree.json"),
+            &example("story.tree.json"),
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("2 files, 0 invalid"));
+}
+
+/// A per-test directory in the system temp directory, removed on drop so a
+/// failing assertion doesn't leave it behind.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("tree-doc-cli-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Write `contents` to `name` inside the directory and return its path.
+    fn file(&self, name: &str, contents: impl AsRef<[u8]>) -> String {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.display().to_string()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn diff_reports_changes() {
+    let old = std::fs::read_to_string(example("minimal.tree.json")).unwrap();
+    let new = old.replace("A quiet village appears ahead.", "A storm is coming.");
+    let dir = TempDir::new("diff");
+    let new_path = dir.file("diff-new.tree.json", &new);
+
+    let output = tree_doc()
+        .args(["diff", &example("minimal.tree.json"), &new_path])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("~ node n2 (content changed)"));
+    assert!(!stdout.contains("+ node"));
+}
+
+#[test]
+fn path_finds_route() {
+    let output = tree_doc()
+        .args(["path", &example("story.tree.json"), "start", "ending"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("start -> ending (3 steps)"));
+    let ids: Vec<_> = stdout
+        .lines()
+        .filter_map(|l| l.strip_prefix('[')?.split(']').next())
+        .collect();
+    assert_eq!(ids, ["start", "enter", "explore", "ending"]);
+}
+
+#[test]
+fn path_unreachable_fails() {
+    let output = tree_doc()
+        .args(["path", &example("story.tree.json"), "ending", "start"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("No path from 'ending' to 'start'"));
+}
+
+#[test]
+fn info_count_prints_only_the_number() {
+    let output = tree_doc()
+        .args(["info", "--count", "nodes", &example("minimal.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "3\n");
+}
+
+#[test]
+fn info_counts_diagnostics() {
+    let output = tree_doc()
+        .args(["info", &example("invalid/orphan-node.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let row = |name: &str| {
+        stdout
+            .lines()
+            .find(|l| l.trim_start().starts_with(name))
+            .and_then(|l| l.split_whitespace().last())
+            .map(str::to_string)
+    };
+    assert_eq!(row("Valid:").as_deref(), Some("yes"));
+    assert_eq!(row("Advisories:").as_deref(), Some("5"));
+    assert_eq!(row("Errors:"), None, "zero counts are hidden");
+    assert_eq!(row("Warnings:"), None);
+}
+
+#[test]
+fn reachable_lists_downstream_nodes() {
+    let output = tree_doc()
+        .args(["reachable", &example("story.tree.json"), "fountain"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("3 nodes reachable from fountain"));
+    let ids: Vec<_> = stdout
+        .lines()
+        .filter_map(|l| l.strip_prefix('[')?.split(']').next())
+        .collect();
+    assert_eq!(ids, ["fountain", "wish", "ending"]);
+
+    let output = tree_doc()
+        .args(["reachable", &example("story.tree.json"), "nowhere"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn paths_lists_every_playthrough() {
+    let output = tree_doc()
+        .args(["paths", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("3 paths to an ending"));
+    assert!(stdout.contains("1. start → enter → fountain → wish → ending"));
+    assert!(stdout.contains("3. start → climb"));
+
+    let output = tree_doc()
+        .args(["paths", "--limit", "1", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("1 path to an ending (stopped at --limit 1)"));
+    assert!(!stdout.contains("climb"));
+}
+
+#[test]
+fn find_matches_content() {
+    let found = |args: &[&str]| {
+        let output = tree_doc()
+            .arg("find")
+            .args(args)
+            .arg(example("story.tree.json"))
+            .arg("FOUNTAIN")
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(found(&[]).starts_with("0 nodes matching \"FOUNTAIN\""));
+
+    let stdout = found(&["--ignore-case"]);
+    assert!(stdout.starts_with("3 nodes matching \"FOUNTAIN\""));
+    let line = |id: &str| stdout.lines().find(|l| l.starts_with(id)).unwrap();
+    assert_eq!(
+        line("[fountain]"),
+        "[fountain] You approach the fountain. A coin glints at the bottom…"
+    );
+    assert_eq!(
+        line("[enter]"),
+        "[enter] …gate open and step inside. A fountain glistens at the center."
+    );
+}
+
+#[test]
+fn degrees_sorted_by_total() {
+    let output = tree_doc()
+        .args(["degrees", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip(2)
+        .map(|l| l.split_whitespace().collect())
+        .collect();
+    assert_eq!(rows.len(), 7);
+    assert_eq!(rows[0], ["enter", "1", "2", "3"]);
+    assert_eq!(rows[6], ["climb", "1", "0", "1"]);
+}
+
+#[test]
+fn export_csv_writes_both_tables() {
+    let dir = TempDir::new("csv");
+    let out_dir = dir.path();
+    let output = tree_doc()
+        .args(["export", "--format", "csv", "--out-dir"])
+        .arg(out_dir)
+        .arg(example("story.tree.json"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let nodes = std::fs::read_to_string(out_dir.join("nodes.csv")).unwrap();
+    let edges = std::fs::read_to_string(out_dir.join("edges.csv")).unwrap();
+    assert!(nodes.starts_with("id,content,status\r\n"));
+    assert_eq!(nodes.lines().count(), 8);
+    assert!(edges.contains("start,enter,true,Open the gate,\r\n"));
+    assert_eq!(edges.lines().count(), 8);
+}
+
+#[test]
+fn edge_type_filters_view_and_export() {
+    let file = example("multi-tree.tree.json");
+    let output = tree_doc()
+        .args(["view", "--full", "--edge-type", "cross", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("[stairs]"));
+
+    let output = tree_doc()
+        .args(["view", "--full", "--edge-type", "other", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("[fountain]"));
+    assert!(!stdout.contains("[stairs]"), "{stdout}");
+
+    let output = tree_doc()
+        .args(["export", "--format", "dot", "--edge-type", "other", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"stairs\" -> \"barrels\""), "{stdout}");
+    assert!(!stdout.contains("\"gate\" -> \"stairs\""), "{stdout}");
+}
+
+#[test]
+fn export_dot_prints_to_stdout() {
+    let output = tree_doc()
+        .args(["export", "--format", "dot", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("digraph tree {"));
+}
+
+#[test]
+fn export_trunk_only_drops_branches() {
+    let story = example("story.tree.json");
+    let output = tree_doc()
+        .args(["export", "--format", "dot", "--trunk-only", &story])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("->").count(), 4);
+
+    let output = tree_doc()
+        .args(["export", "--format", "graphml", "--trunk-only", &story])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn export_html_prints_a_page() {
+    let output = tree_doc()
+        .args(["export", "--format", "html", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("<!DOCTYPE html>"));
+    assert!(stdout.contains("<details>"));
+}
+
+#[test]
+fn stats_reports_depth_and_endings() {
+    let output = tree_doc()
+        .args(["stats", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let row = |name: &str| {
+        stdout
+            .lines()
+            .find(|l| l.contains(name))
+            .and_then(|l| l.split_whitespace().last())
+            .map(str::to_string)
+    };
+    assert_eq!(row("Max depth:").as_deref(), Some("3"));
+    assert_eq!(row("Longest path:").as_deref(), Some("4"));
+    assert_eq!(row("Endings:").as_deref(), Some("2"));
+    assert_eq!(row("Trunk coverage:").as_deref(), Some("71.4%"));
+}
+
+#[test]
+fn order_lists_nodes_topologically() {
+    let output = tree_doc()
+        .args(["order", &example("minimal.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let ids: Vec<_> = stdout
+        .lines()
+        .filter_map(|l| l.split_once('[')?.1.split(']').next())
+        .collect();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids[0], "n1");
+
+    let output = tree_doc()
+        .args(["order", &example("invalid/general-cycle.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cycle among n2, n3, n4"));
+}
+
+#[test]
+fn endings_lists_every_leaf() {
+    let output = tree_doc()
+        .args(["endings", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("2 endings"));
+    let ids: Vec<_> = stdout
+        .lines()
+        .filter_map(|l| l.strip_prefix('[')?.split(']').next())
+        .collect();
+    assert_eq!(ids, ["climb", "ending"]);
+}
+
+#[test]
+fn fix_write_repairs_dangling_edge() {
+    let json = std::fs::read_to_string(example("invalid/dangling-edge.tree.json")).unwrap();
+    let dir = TempDir::new("fix");
+    let path = dir.file("fix-dangling.tree.json", &json);
+
+    let output = tree_doc().args(["fix", &path]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("dangling-edge"));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), json, "dry run must not write");
+
+    let output = tree_doc().args(["fix", "--write", &path]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = tree_doc().args(["validate", &path]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn fmt_is_idempotent() {
+    let output = tree_doc()
+        .args(["fmt", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let once = String::from_utf8(output.stdout).unwrap();
+
+    let dir = TempDir::new("fmt");
+    let path = dir.file("fmt-once.tree.json", &once);
+    let output = tree_doc().args(["fmt", "--write", &path]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), once);
+
+    // Nodes come out sorted by id
+    let climb = once.find("\"id\": \"climb\"").unwrap();
+    let start = once.find("\"id\": \"start\"").unwrap();
+    assert!(climb < start);
+}
+
+#[test]
+fn validate_reports_reader_too_old() {
+    let json = std::fs::read_to_string(example("story.tree.json"))
+        .unwrap()
+        .replace("\"minReaderVersion\": \"1.0\"", "\"minReaderVersion\": \"3.0\"");
+    let dir = TempDir::new("reader");
+    let path = dir.file("future-reader.tree.json", &json);
+
+    let output = tree_doc()
+        .args(["validate", "--format", "json", &path])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["errors"][0]["rule"], "reader-too-old");
+
+    let output = tree_doc().args(["validate", &path]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("requires reader version 3.0"));
+}
+
+#[test]
+fn no_color_disables_escape_codes() {
+    let run = |envs: &[(&str, &str)], extra: &[&str]| {
+        let output = tree_doc()
+            .env_remove("NO_COLOR")
+            .envs(envs.iter().copied())
+            .arg("validate")
+            .args(extra)
+            .arg(example("invalid/trunk-cycle.tree.json"))
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(!run(&[("NO_COLOR", "1")], &[]).contains('\x1b'));
+    assert!(!run(&[], &["--no-color"]).contains('\x1b'));
+    // Piped output is never colored by default
+    assert!(!run(&[], &[]).contains('\x1b'));
+}
+
+#[test]
+fn quiet_validate_prints_only_errors() {
+    let output = tree_doc()
+        .args(["validate", "--quiet", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    let output = tree_doc()
+        .args(["validate", "-q", &example("invalid/trunk-cycle.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("trunk-cycle"));
+    assert!(!stdout.contains("general-cycle"), "warnings are suppressed");
+}
+
+#[test]
+fn summary_validate_prints_only_counts() {
+    let output = tree_doc()
+        .args(["validate", "--summary", &example("invalid/trunk-cycle.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].contains("has validation errors"));
+    assert!(lines[1].contains("1 error"));
+    assert!(!stdout.contains("trunk-cycle]"), "diagnostics are suppressed");
+
+    let output = tree_doc()
+        .args(["validate", "--summary", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("is valid"));
+    assert!(stdout.contains("5 advisories"));
+    assert!(!stdout.contains("at node"));
+}
+
+#[test]
+fn fail_on_warning_fails_warning_only_document() {
+    let file = example("invalid/self-loop.tree.json");
+
+    let output = tree_doc().args(["validate", &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = tree_doc()
+        .args(["validate", "--fail-on", "warning", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = tree_doc()
+        .args(["validate", "--fail-on", "warning", &example("invalid/orphan-node.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "advisories are below the threshold");
+}
+
+#[test]
+fn allow_suppresses_rule() {
+    let file = example("invalid/orphan-node.tree.json");
+
+    let output = tree_doc().args(["validate", &file]).output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("TDF006 orphan-node"));
+
+    let output = tree_doc()
+        .args(["validate", "--allow", "orphan-node", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("TDF006"));
+    assert!(stdout.contains("dead-end-node"), "other rules are still reported");
+
+    let output = tree_doc()
+        .args(["validate", "--allow", "not-a-rule", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn config_file_sets_fail_on_threshold() {
+    let dir = TempDir::new("config");
+    dir.file(
+        ".tree-doc.toml",
+        "fail-on = \"warning\"\nallow = [\"dead-end-node\"]\n",
+    );
+    let file = example("invalid/self-loop.tree.json");
+
+    let output = tree_doc()
+        .current_dir(dir.path())
+        .args(["validate", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1), "config raises the threshold");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("dead-end-node"));
+
+    let output = tree_doc()
+        .current_dir(dir.path())
+        .args(["validate", "--fail-on", "error", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "flags win over the config");
+
+    dir.file(".tree-doc.toml", "fail-on = \"sometimes\"\n");
+    let output = tree_doc()
+        .current_dir(dir.path())
+        .args(["validate", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(".tree-doc.toml"));
+}
+
+#[test]
+fn custom_schema_is_checked() {
+    let dir = TempDir::new("schema");
+    let schema = dir.file(
+        "require-author.schema.json",
+        r#"{"required": ["metadata"], "properties": {"metadata": {"required": ["author"]}}}"#,
+    );
+
+    let output = tree_doc()
+        .args(["validate", "--schema", &schema, &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = tree_doc()
+        .args(["validate", "--schema", &schema, &example("minimal.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("\"metadata\" is a required property"));
+
+    let bad = dir.file("bad.schema.json", r#"{"type": 12}"#);
+    let output = tree_doc()
+        .args(["validate", "--schema", &bad, &example("minimal.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Error compiling schema"));
+}
+
+#[test]
+fn strict_flag_reports_unknown_fields() {
+    let dir = TempDir::new("strict");
+    let file = dir.file(
+        "typo.tree.json",
+        r#"{"formatVersion": "1.0", "rootNodeId": "n1", "rootNodId": "n1",
+            "nodes": [{"id": "n1", "content": "hi"}], "edges": []}"#,
+    );
+
+    let output = tree_doc().args(["validate", &file]).output().unwrap();
+    assert!(!String::from_utf8(output.stdout).unwrap().contains("TDF025"));
+
+    let output = tree_doc()
+        .args(["validate", "--strict", &file])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("TDF025"), "{stdout}");
+    assert!(stdout.contains("rootNodId"));
+}
+
+#[test]
+fn max_trunk_length_flag() {
+    let output = tree_doc()
+        .args(["validate", "--max-trunk-length", "2", &example("story.tree.json")])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("TDF026"), "{stdout}");
+}
+
+#[test]
+fn strict_acyclic_fails_on_cycles() {
+    let file = example("invalid/general-cycle.tree.json");
+    let output = tree_doc().args(["validate", &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = tree_doc()
+        .args(["validate", "--strict-acyclic", &file])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn init_writes_valid_scaffold_for_each_tier() {
+    let dir = TempDir::new("init");
+
+    for tier in ["0", "1", "2"] {
+        let path = dir.path().join(format!("tier{tier}.tree.json"));
+        let _ = std::fs::remove_file(&path);
+        let output = tree_doc()
+            .args(["init", "--tier", tier])
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+
+        let output = tree_doc()
+            .args(["validate", "--format", "json"])
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(result["stats"]["tier"].to_string(), tier);
+    }
+
+    // An existing file is not overwritten without --force
+    let path = dir.path().join("tier0.tree.json");
+    let output = tree_doc().arg("init").arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let output = tree_doc().args(["init", "--force"]).arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn exit_codes_distinguish_failures() {
+    let code = |args: &[&str]| tree_doc().args(args).output().unwrap().status.code();
+    let missing = example("does-not-exist.tree.json");
+    let dir = TempDir::new("exit-codes");
+    let malformed = dir.file("malformed.tree.json", "{not valid json}");
+    let invalid = example("invalid/dangling-edge.tree.json");
+
+    for command in ["validate", "view", "info"] {
+        assert_eq!(code(&[command, &missing]), Some(3), "{command}");
+        assert_eq!(code(&[command, &malformed]), Some(2), "{command}");
+    }
+    assert_eq!(code(&["validate", &invalid]), Some(1));
+    assert_eq!(code(&["view", &invalid]), Some(1));
+
+    // With a missing file and a malformed one, the I/O error wins
+    assert_eq!(code(&["validate", &malformed, &missing]), Some(3));
+}
+
+#[test]
+fn validate_reads_gzipped_documents() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let json = std::fs::read(example("minimal.tree.json")).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&json).unwrap();
+    let gzipped = encoder.finish().unwrap();
+
+    let dir = TempDir::new("gzip");
+    let path = dir.file("minimal.tree.json.gz", &gzipped);
+
+    let output = tree_doc().arg("validate").arg(&path).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8(output.stdout).unwrap().contains("is valid"));
+
+    // Detected by content on stdin, where there is no file name
+    let mut child = tree_doc()
+        .args(["info", "--count", "nodes", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&gzipped).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "3\n");
+
+    // A .gz name on plain JSON is rejected
+    let fake = dir.file("plain.tree.json.gz", &json);
+    let output = tree_doc().arg("validate").arg(&fake).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+}
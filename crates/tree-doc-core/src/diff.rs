@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::types::TreeDocument;
+use crate::viewer::trunk_path;
+
+/// Structural differences between two versions of a document.
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeDiff {
+    /// Node ids present only in the new document, in its order.
+    pub added_nodes: Vec<String>,
+    /// Node ids present only in the old document, in its order.
+    pub removed_nodes: Vec<String>,
+    pub added_edges: Vec<EdgeRef>,
+    pub removed_edges: Vec<EdgeRef>,
+    /// Ids of nodes present in both documents whose `content` differs.
+    pub changed_content: Vec<String>,
+    /// Set when the trunk walk from the root visits different nodes.
+    pub trunk_change: Option<TrunkChange>,
+}
+
+/// An edge identified by its endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct EdgeRef {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TrunkChange {
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+impl TreeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_content.is_empty()
+            && self.trunk_change.is_none()
+    }
+}
+
+/// Compare two documents by node id and edge endpoints.
+pub fn diff(old: &TreeDocument, new: &TreeDocument) -> TreeDiff {
+    let old_nodes: HashMap<&str, &str> = old
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.content.as_str()))
+        .collect();
+    let new_nodes: HashMap<&str, &str> = new
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), n.content.as_str()))
+        .collect();
+
+    let added_nodes = new
+        .nodes
+        .iter()
+        .filter(|n| !old_nodes.contains_key(n.id.as_str()))
+        .map(|n| n.id.clone())
+        .collect();
+    let removed_nodes = old
+        .nodes
+        .iter()
+        .filter(|n| !new_nodes.contains_key(n.id.as_str()))
+        .map(|n| n.id.clone())
+        .collect();
+    let changed_content = new
+        .nodes
+        .iter()
+        .filter(|n| matches!(old_nodes.get(n.id.as_str()), Some(&c) if c != n.content))
+        .map(|n| n.id.clone())
+        .collect();
+
+    let old_edges = edge_refs(old);
+    let new_edges = edge_refs(new);
+    let old_edge_set: HashSet<&EdgeRef> = old_edges.iter().collect();
+    let new_edge_set: HashSet<&EdgeRef> = new_edges.iter().collect();
+    let added_edges = new_edges
+        .iter()
+        .filter(|e| !old_edge_set.contains(e))
+        .cloned()
+        .collect();
+    let removed_edges = old_edges
+        .iter()
+        .filter(|e| !new_edge_set.contains(e))
+        .cloned()
+        .collect();
+
+    let old_trunk = trunk_path(old);
+    let new_trunk = trunk_path(new);
+    let trunk_change = (old_trunk != new_trunk).then(|| TrunkChange {
+        old: old_trunk.iter().map(|s| s.to_string()).collect(),
+        new: new_trunk.iter().map(|s| s.to_string()).collect(),
+    });
+
+    TreeDiff {
+        added_nodes,
+        removed_nodes,
+        added_edges,
+        removed_edges,
+        changed_content,
+        trunk_change,
+    }
+}
+
+/// Unique edge endpoints in document order.
+fn edge_refs(doc: &TreeDocument) -> Vec<EdgeRef> {
+    let mut seen = HashSet::new();
+    doc.edges
+        .iter()
+        .filter(|e| seen.insert((e.source.as_str(), e.target.as_str())))
+        .map(|e| EdgeRef {
+            source: e.source.clone(),
+            target: e.target.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn edge(source: &str, target: &str) -> EdgeRef {
+        EdgeRef {
+            source: source.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn identical_documents_have_no_diff() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        assert!(diff(&doc, &doc).is_empty());
+    }
+
+    #[test]
+    fn node_rename() {
+        let old = parse::parse(include_str!("../../../examples/minimal.tree.json")).unwrap();
+        let new = parse::parse(
+            &include_str!("../../../examples/minimal.tree.json").replace("\"n2\"", "\"village\""),
+        )
+        .unwrap();
+        let d = diff(&old, &new);
+
+        assert_eq!(d.added_nodes, vec!["village"]);
+        assert_eq!(d.removed_nodes, vec!["n2"]);
+        assert_eq!(d.added_edges, vec![edge("n1", "village")]);
+        assert_eq!(d.removed_edges, vec![edge("n1", "n2")]);
+        assert!(d.changed_content.is_empty());
+        let trunk = d.trunk_change.unwrap();
+        assert_eq!(trunk.old, vec!["n1", "n2"]);
+        assert_eq!(trunk.new, vec!["n1", "village"]);
+    }
+
+    #[test]
+    fn added_branch_edge() {
+        let old = parse::parse(include_str!("../../../examples/minimal.tree.json")).unwrap();
+        let mut new = old.clone();
+        let mut branch = new.edges[1].clone();
+        branch.source = "n2".to_string();
+        new.edges.push(branch);
+        let d = diff(&old, &new);
+
+        assert_eq!(d.added_edges, vec![edge("n2", "n3")]);
+        assert!(d.removed_edges.is_empty());
+        assert!(d.added_nodes.is_empty());
+        assert!(d.trunk_change.is_none());
+    }
+
+    #[test]
+    fn content_edit() {
+        let old = parse::parse(include_str!("../../../examples/minimal.tree.json")).unwrap();
+        let mut new = old.clone();
+        new.nodes[2].content = "You take the right path. It starts to rain.".to_string();
+        let d = diff(&old, &new);
+
+        assert_eq!(d.changed_content, vec!["n3"]);
+        assert!(d.added_nodes.is_empty());
+        assert!(d.removed_nodes.is_empty());
+        assert!(d.added_edges.is_empty());
+        assert!(d.trunk_change.is_none());
+    }
+}
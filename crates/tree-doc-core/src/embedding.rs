@@ -0,0 +1,144 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::types::TreeDocument;
+
+#[derive(Debug, Error)]
+pub enum EmbeddingError {
+    #[error("embeddingRef has no path")]
+    MissingPath,
+    #[error("embedding path '{}' leaves the document directory", .0.display())]
+    OutsideBaseDir(PathBuf),
+    #[error("embedding file not found: {}", .0.display())]
+    NotFound(PathBuf),
+    #[error("embedding file '{}' is not in format '{format}'", path.display())]
+    FormatMismatch { path: PathBuf, format: String },
+    #[error("failed to read embedding file '{}': {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+/// Load the external data `embeddingRef` points at.
+///
+/// `path` is resolved relative to `base_dir`, normally the directory holding
+/// the document, and may not leave it: absolute paths and `..` components are
+/// rejected. Returns `None` when the document has no `embeddingRef`.
+///
+/// The bytes are checked against `format`: `npy` files must start with the
+/// NumPy magic string and `json` files must parse as JSON. Other formats are
+/// only checked against the file extension.
+pub fn resolve_embedding(
+    doc: &TreeDocument,
+    base_dir: &Path,
+) -> Result<Option<Vec<u8>>, EmbeddingError> {
+    let Some(embedding) = &doc.embedding_ref else {
+        return Ok(None);
+    };
+    let relative = Path::new(embedding.path.as_ref().ok_or(EmbeddingError::MissingPath)?);
+    if relative.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    }) {
+        return Err(EmbeddingError::OutsideBaseDir(relative.to_path_buf()));
+    }
+    let path = base_dir.join(relative);
+
+    let bytes = fs::read(&path).map_err(|source| match source.kind() {
+        io::ErrorKind::NotFound => EmbeddingError::NotFound(path.clone()),
+        _ => EmbeddingError::Io {
+            path: path.clone(),
+            source,
+        },
+    })?;
+
+    if !matches_format(&embedding.format, &path, &bytes) {
+        return Err(EmbeddingError::FormatMismatch {
+            path,
+            format: embedding.format.clone(),
+        });
+    }
+    Ok(Some(bytes))
+}
+
+fn matches_format(format: &str, path: &Path, bytes: &[u8]) -> bool {
+    match format.to_ascii_lowercase().as_str() {
+        "npy" => bytes.starts_with(b"\x93NUMPY"),
+        "json" => serde_json::from_slice::<serde_json::Value>(bytes).is_ok(),
+        _ => path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(format)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{document_with, TempDir};
+    use crate::parse::parse;
+
+    fn with_embedding(format: &str, path: &str) -> TreeDocument {
+        let json = document_with(serde_json::json!({
+            "embeddingRef": {"format": format, "path": path}
+        }));
+        parse(&json).unwrap()
+    }
+
+    #[test]
+    fn resolves_present_embedding() {
+        let dir = TempDir::new("embedding-present");
+        let npy = b"\x93NUMPY\x01\x00rest of the file".to_vec();
+        fs::write(dir.join("vectors.npy"), &npy).unwrap();
+
+        let doc = with_embedding("npy", "vectors.npy");
+        assert_eq!(resolve_embedding(&doc, &dir).unwrap(), Some(npy));
+
+        fs::write(dir.join("fake.npy"), "not numpy").unwrap();
+        let doc = with_embedding("npy", "fake.npy");
+        let err = resolve_embedding(&doc, &dir).unwrap_err();
+        assert!(matches!(err, EmbeddingError::FormatMismatch { .. }), "{err}");
+    }
+
+    #[test]
+    fn missing_file_is_not_found() {
+        let dir = TempDir::new("embedding-missing");
+        let doc = with_embedding("npy", "nowhere.npy");
+        let err = resolve_embedding(&doc, &dir).unwrap_err();
+        match err {
+            EmbeddingError::NotFound(path) => assert_eq!(path, dir.join("nowhere.npy")),
+            other => panic!("expected NotFound, got {other}"),
+        }
+    }
+
+    #[test]
+    fn path_outside_base_dir_is_rejected() {
+        let dir = TempDir::new("embedding-outside");
+        fs::write(dir.join("vectors.npy"), b"\x93NUMPY").unwrap();
+        let base = dir.join("docs");
+        fs::create_dir_all(&base).unwrap();
+
+        for path in ["../vectors.npy", "nested/../../vectors.npy"] {
+            let doc = with_embedding("npy", path);
+            let err = resolve_embedding(&doc, &base).unwrap_err();
+            assert!(matches!(err, EmbeddingError::OutsideBaseDir(_)), "{err}");
+        }
+        let absolute = dir.join("vectors.npy");
+        let doc = with_embedding("npy", absolute.to_str().unwrap());
+        let err = resolve_embedding(&doc, &base).unwrap_err();
+        assert!(matches!(err, EmbeddingError::OutsideBaseDir(_)), "{err}");
+    }
+
+    #[test]
+    fn absent_embedding_ref_is_none() {
+        let doc = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        assert!(resolve_embedding(&doc, Path::new(".")).unwrap().is_none());
+
+        let mut doc = with_embedding("npy", "vectors.npy");
+        doc.embedding_ref.as_mut().unwrap().path = None;
+        let err = resolve_embedding(&doc, Path::new(".")).unwrap_err();
+        assert!(matches!(err, EmbeddingError::MissingPath));
+    }
+}
@@ -1,6 +1,12 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     Error,
     Warning,
@@ -17,7 +23,8 @@ impl fmt::Display for Severity {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Rule {
     SchemaValidation,
     DuplicateNodeId,
@@ -26,6 +33,115 @@ pub enum Rule {
     GeneralCycle,
     OrphanNode,
     DanglingBeginEnd,
+    SelfLoop,
+    MissingRoot,
+    AmbiguousTrunk,
+    DanglingTreeRoot,
+    UndefinedTree,
+    UnknownStatus,
+    UnlabeledBranch,
+    DuplicateEdge,
+    DeadEndNode,
+    UnsupportedVersion,
+    ReaderTooOld,
+    MalformedReaderVersion,
+    UnknownFeature,
+    UndeclaredFeature,
+    OffTrunk,
+    EmptyContent,
+    ContentTooLong,
+    UnknownField,
+    TrunkTooLong,
+    UndefinedNode,
+    MalformedMetadata,
+    TrunkNotLinear,
+    DisconnectedTrunk,
+    IgnoredTreeIds,
+    UnknownLinkType,
+    UnmarkedCrossTreeEdge,
+    IncompleteBranch,
+}
+
+impl Rule {
+    /// Every rule, in code order.
+    pub const ALL: &'static [Rule] = &[
+        Rule::SchemaValidation,
+        Rule::DuplicateNodeId,
+        Rule::DanglingEdge,
+        Rule::TrunkCycle,
+        Rule::GeneralCycle,
+        Rule::OrphanNode,
+        Rule::DanglingBeginEnd,
+        Rule::SelfLoop,
+        Rule::MissingRoot,
+        Rule::AmbiguousTrunk,
+        Rule::DanglingTreeRoot,
+        Rule::UndefinedTree,
+        Rule::UnknownStatus,
+        Rule::UnlabeledBranch,
+        Rule::DuplicateEdge,
+        Rule::DeadEndNode,
+        Rule::UnsupportedVersion,
+        Rule::ReaderTooOld,
+        Rule::MalformedReaderVersion,
+        Rule::UnknownFeature,
+        Rule::UndeclaredFeature,
+        Rule::OffTrunk,
+        Rule::EmptyContent,
+        Rule::ContentTooLong,
+        Rule::UnknownField,
+        Rule::TrunkTooLong,
+        Rule::UndefinedNode,
+        Rule::MalformedMetadata,
+        Rule::TrunkNotLinear,
+        Rule::DisconnectedTrunk,
+        Rule::IgnoredTreeIds,
+        Rule::UnknownLinkType,
+        Rule::UnmarkedCrossTreeEdge,
+        Rule::IncompleteBranch,
+    ];
+
+    /// Stable machine-readable code for this rule, e.g. `TDF003`.
+    ///
+    /// Codes are never reused or renumbered; new rules take the next free code.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::SchemaValidation => "TDF001",
+            Rule::DuplicateNodeId => "TDF002",
+            Rule::DanglingEdge => "TDF003",
+            Rule::TrunkCycle => "TDF004",
+            Rule::GeneralCycle => "TDF005",
+            Rule::OrphanNode => "TDF006",
+            Rule::DanglingBeginEnd => "TDF007",
+            Rule::SelfLoop => "TDF008",
+            Rule::MissingRoot => "TDF009",
+            Rule::AmbiguousTrunk => "TDF010",
+            Rule::DanglingTreeRoot => "TDF011",
+            Rule::UndefinedTree => "TDF012",
+            Rule::UnknownStatus => "TDF013",
+            Rule::UnlabeledBranch => "TDF014",
+            Rule::DuplicateEdge => "TDF015",
+            Rule::DeadEndNode => "TDF016",
+            Rule::UnsupportedVersion => "TDF017",
+            Rule::ReaderTooOld => "TDF018",
+            Rule::MalformedReaderVersion => "TDF019",
+            Rule::UnknownFeature => "TDF020",
+            Rule::UndeclaredFeature => "TDF021",
+            Rule::OffTrunk => "TDF022",
+            Rule::EmptyContent => "TDF023",
+            Rule::ContentTooLong => "TDF024",
+            Rule::UnknownField => "TDF025",
+            Rule::TrunkTooLong => "TDF026",
+            Rule::UndefinedNode => "TDF027",
+            Rule::MalformedMetadata => "TDF028",
+            Rule::TrunkNotLinear => "TDF029",
+            Rule::DisconnectedTrunk => "TDF030",
+            Rule::IgnoredTreeIds => "TDF031",
+            Rule::UnknownLinkType => "TDF032",
+            Rule::UnmarkedCrossTreeEdge => "TDF033",
+            Rule::IncompleteBranch => "TDF034",
+        }
+    }
 }
 
 impl fmt::Display for Rule {
@@ -38,16 +154,79 @@ impl fmt::Display for Rule {
             Rule::GeneralCycle => write!(f, "general-cycle"),
             Rule::OrphanNode => write!(f, "orphan-node"),
             Rule::DanglingBeginEnd => write!(f, "dangling-begin-end"),
+            Rule::SelfLoop => write!(f, "self-loop"),
+            Rule::MissingRoot => write!(f, "missing-root"),
+            Rule::AmbiguousTrunk => write!(f, "ambiguous-trunk"),
+            Rule::DanglingTreeRoot => write!(f, "dangling-tree-root"),
+            Rule::UndefinedTree => write!(f, "undefined-tree"),
+            Rule::UnknownStatus => write!(f, "unknown-status"),
+            Rule::UnlabeledBranch => write!(f, "unlabeled-branch"),
+            Rule::DuplicateEdge => write!(f, "duplicate-edge"),
+            Rule::DeadEndNode => write!(f, "dead-end-node"),
+            Rule::UnsupportedVersion => write!(f, "unsupported-version"),
+            Rule::ReaderTooOld => write!(f, "reader-too-old"),
+            Rule::MalformedReaderVersion => write!(f, "malformed-reader-version"),
+            Rule::UnknownFeature => write!(f, "unknown-feature"),
+            Rule::UndeclaredFeature => write!(f, "undeclared-feature"),
+            Rule::OffTrunk => write!(f, "off-trunk"),
+            Rule::EmptyContent => write!(f, "empty-content"),
+            Rule::ContentTooLong => write!(f, "content-too-long"),
+            Rule::UnknownField => write!(f, "unknown-field"),
+            Rule::TrunkTooLong => write!(f, "trunk-too-long"),
+            Rule::UndefinedNode => write!(f, "undefined-node"),
+            Rule::MalformedMetadata => write!(f, "malformed-metadata"),
+            Rule::TrunkNotLinear => write!(f, "trunk-not-linear"),
+            Rule::DisconnectedTrunk => write!(f, "disconnected-trunk"),
+            Rule::IgnoredTreeIds => write!(f, "ignored-tree-ids"),
+            Rule::UnknownLinkType => write!(f, "unknown-link-type"),
+            Rule::UnmarkedCrossTreeEdge => write!(f, "unmarked-cross-tree-edge"),
+            Rule::IncompleteBranch => write!(f, "incomplete-branch"),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Parses either the kebab-case name (`orphan-node`) or the code (`TDF006`,
+/// case-insensitive).
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .iter()
+            .find(|rule| rule.to_string() == s || rule.code().eq_ignore_ascii_case(s))
+            .cloned()
+            .ok_or_else(|| format!("unknown rule '{s}'"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Location {
     Root,
     Node(String),
-    Edge { source: String, target: String },
+    /// An edge by its endpoints and its position in `edges`, which tells
+    /// apart edges that share endpoints.
+    Edge {
+        source: String,
+        target: String,
+        index: usize,
+    },
     Path(Vec<String>),
+    /// A JSON Pointer into the raw document, e.g. `/nodes/0/id`.
+    Pointer(String),
+}
+
+impl Location {
+    /// Whether this location names `node_id`: as the node itself, as either
+    /// end of an edge, or anywhere along a path. JSON Pointers are not
+    /// resolved, so they never match.
+    pub fn mentions_node(&self, node_id: &str) -> bool {
+        match self {
+            Location::Node(id) => id == node_id,
+            Location::Edge { source, target, .. } => source == node_id || target == node_id,
+            Location::Path(ids) => ids.iter().any(|id| id == node_id),
+            Location::Root | Location::Pointer(_) => false,
+        }
+    }
 }
 
 impl fmt::Display for Location {
@@ -55,44 +234,172 @@ impl fmt::Display for Location {
         match self {
             Location::Root => write!(f, "(document root)"),
             Location::Node(id) => write!(f, "node '{id}'"),
-            Location::Edge { source, target } => write!(f, "edge '{source}' -> '{target}'"),
+            Location::Edge {
+                source,
+                target,
+                index,
+            } => write!(f, "edge '{source}' -> '{target}' (edges[{index}])"),
             Location::Path(ids) => write!(f, "path: {}", ids.join(" -> ")),
+            Location::Pointer(pointer) => write!(f, "{pointer}"),
+        }
+    }
+}
+
+/// Locations serialize as an object tagged by `kind`, e.g.
+/// `{"kind":"node","id":"n1"}`, `{"kind":"edge","source":"a","target":"b","index":3}`,
+/// `{"kind":"path","nodes":["a","b"]}`, or
+/// `{"kind":"pointer","pointer":"/nodes/0/id"}`.
+impl Serialize for Location {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        match self {
+            Location::Root => {
+                map.serialize_entry("kind", "root")?;
+            }
+            Location::Node(id) => {
+                map.serialize_entry("kind", "node")?;
+                map.serialize_entry("id", id)?;
+            }
+            Location::Edge {
+                source,
+                target,
+                index,
+            } => {
+                map.serialize_entry("kind", "edge")?;
+                map.serialize_entry("source", source)?;
+                map.serialize_entry("target", target)?;
+                map.serialize_entry("index", index)?;
+            }
+            Location::Path(ids) => {
+                map.serialize_entry("kind", "path")?;
+                map.serialize_entry("nodes", ids)?;
+            }
+            Location::Pointer(pointer) => {
+                map.serialize_entry("kind", "pointer")?;
+                map.serialize_entry("pointer", pointer)?;
+            }
         }
+        map.end()
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Diagnostic {
     pub rule: Rule,
     pub message: String,
     pub location: Location,
     pub severity: Severity,
+    /// Byte range `start..end` of the offending node or edge object in the
+    /// source text. Only filled in by `validate_document_with_spans`.
+    pub span: Option<(usize, usize)>,
 }
 
 impl fmt::Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "[{}] {}: {} (at {})",
-            self.severity, self.rule, self.message, self.location
+            "[{}] {} {}: {} (at {})",
+            self.severity,
+            self.rule.code(),
+            self.rule,
+            self.message,
+            self.location
         )
     }
 }
 
-#[derive(Debug, Clone)]
+/// Diagnostics serialize with the rule's stable `code` alongside its name.
+/// A span, when present, serializes as `{"start": 10, "end": 42}`.
+impl Serialize for Diagnostic {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Diagnostic", 6)?;
+        state.serialize_field("rule", &self.rule)?;
+        state.serialize_field("code", self.rule.code())?;
+        state.serialize_field("message", &self.message)?;
+        state.serialize_field("location", &self.location)?;
+        state.serialize_field("severity", &self.severity)?;
+        match self.span {
+            Some((start, end)) => state.serialize_field(
+                "span",
+                &serde_json::json!({ "start": start, "end": end }),
+            )?,
+            None => state.skip_field("span")?,
+        }
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DocumentStats {
     pub node_count: usize,
     pub edge_count: usize,
     pub trunk_length: usize,
     pub branch_count: usize,
+    pub component_count: usize,
+    /// Edges on the longest directed path from the root, ignoring edges that
+    /// close a cycle.
+    pub longest_path_len: usize,
+    /// Most outgoing edges on any single node.
+    pub max_out_degree: usize,
     pub tier: u8,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ValidationResult {
     pub is_valid: bool,
     pub errors: Vec<Diagnostic>,
     pub warnings: Vec<Diagnostic>,
     pub advisories: Vec<Diagnostic>,
     pub stats: DocumentStats,
+    /// Where nodes and edges sit in the document, for
+    /// [`sorted_diagnostics`](crate::sorted_diagnostics).
+    #[serde(skip)]
+    pub(crate) order: DocumentOrder,
+}
+
+/// The document order of a validated document's nodes and edges. Empty when
+/// the document could not be parsed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DocumentOrder {
+    /// Position of each node id's first occurrence in `nodes`.
+    pub(crate) nodes: HashMap<String, usize>,
+    /// Source node id of each edge, by position in `edges`.
+    pub(crate) edge_sources: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn rule_codes_are_unique() {
+        let codes: HashSet<_> = Rule::ALL.iter().map(Rule::code).collect();
+        assert_eq!(codes.len(), Rule::ALL.len());
+    }
+
+    #[test]
+    fn rule_parses_from_name_or_code() {
+        for rule in Rule::ALL {
+            assert_eq!(rule.to_string().parse::<Rule>().as_ref(), Ok(rule));
+            assert_eq!(rule.code().parse::<Rule>().as_ref(), Ok(rule));
+        }
+        assert_eq!("tdf006".parse::<Rule>(), Ok(Rule::OrphanNode));
+        assert!("no-such-rule".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn diagnostic_display_includes_code() {
+        let diag = Diagnostic {
+            rule: Rule::DanglingEdge,
+            message: "Edge target 'n9' does not exist".to_string(),
+            location: Location::Node("n9".to_string()),
+            severity: Severity::Error,
+            span: None,
+        };
+        assert!(diag.to_string().starts_with("[error] TDF003 dangling-edge:"));
+        assert_eq!(serde_json::to_value(&diag).unwrap()["code"], "TDF003");
+    }
 }
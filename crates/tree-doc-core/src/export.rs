@@ -0,0 +1,821 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::types::{Edge, EdgeType, Node, NodeStatus, TreeDocument};
+use crate::viewer::{build_trunk_view, trunk_path};
+
+/// Render a document as a Graphviz `digraph`.
+///
+/// Each node is labelled with its id and content. Trunk edges are drawn bold
+/// and colored, branch edges carry their `label`, and the root node gets a
+/// distinct shape.
+pub fn to_dot(doc: &TreeDocument) -> String {
+    let mut out = String::new();
+    out.push_str("digraph tree {\n");
+    out.push_str("  node [shape=box];\n");
+
+    for node in &doc.nodes {
+        let label = format!("{}\n{}", node.id, node.content);
+        let shape = if doc.root_node_id.as_deref() == Some(node.id.as_str()) {
+            ", shape=doubleoctagon"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            out,
+            "  \"{}\" [label=\"{}\"{}];",
+            escape_dot(&node.id),
+            escape_dot(&label),
+            shape
+        );
+    }
+
+    for edge in &doc.edges {
+        let mut attrs = Vec::new();
+        if edge.is_trunk == Some(true) {
+            attrs.push("style=bold".to_string());
+            attrs.push("color=\"#2da44e\"".to_string());
+        }
+        if let Some(label) = &edge.label {
+            attrs.push(format!("label=\"{}\"", escape_dot(label)));
+        }
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        };
+        let _ = writeln!(
+            out,
+            "  \"{}\" -> \"{}\"{};",
+            escape_dot(&edge.source),
+            escape_dot(&edge.target),
+            attrs
+        );
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Like [`to_dot`], but only the trunk path from the root: its nodes and the
+/// trunk edges joining them, without any branches.
+pub fn to_dot_trunk(doc: &TreeDocument) -> String {
+    to_dot(&trunk_document(doc))
+}
+
+/// Render a document as a Mermaid `flowchart TD` block.
+///
+/// Nodes become `id["content"]`, branch edges `a --> b` and trunk edges the
+/// thick `a ==> b`, with `|label|` when the edge has a label. Ids that Mermaid
+/// can't use verbatim are sanitized, and the original id is kept visible at the
+/// start of the node label.
+pub fn to_mermaid(doc: &TreeDocument) -> String {
+    let ids = mermaid_ids(doc);
+    let mut out = String::new();
+    out.push_str("flowchart TD\n");
+
+    for node in &doc.nodes {
+        let id = &ids[node.id.as_str()];
+        let label = if *id == node.id {
+            escape_mermaid(&node.content)
+        } else {
+            format!(
+                "{}: {}",
+                escape_mermaid(&node.id),
+                escape_mermaid(&node.content)
+            )
+        };
+        let _ = writeln!(out, "    {id}[\"{label}\"]");
+    }
+
+    for edge in &doc.edges {
+        let arrow = if edge.is_trunk == Some(true) {
+            "==>"
+        } else {
+            "-->"
+        };
+        let label = match &edge.label {
+            Some(label) => format!("|{}|", escape_mermaid(label)),
+            None => String::new(),
+        };
+        let _ = writeln!(
+            out,
+            "    {} {arrow}{label} {}",
+            ids[edge.source.as_str()],
+            ids[edge.target.as_str()]
+        );
+    }
+
+    out
+}
+
+/// Like [`to_mermaid`], but only the trunk path from the root: its nodes and
+/// the trunk edges joining them, without any branches.
+pub fn to_mermaid_trunk(doc: &TreeDocument) -> String {
+    to_mermaid(&trunk_document(doc))
+}
+
+/// A copy of `doc` reduced to the nodes along `trunk_path`, in trunk order,
+/// and the trunk edge that steps between each consecutive pair.
+fn trunk_document(doc: &TreeDocument) -> TreeDocument {
+    let path = trunk_path(doc);
+    let nodes = path.iter().filter_map(|id| doc.node(id)).cloned().collect();
+    let edges = path
+        .windows(2)
+        .filter_map(|pair| {
+            doc.edges
+                .iter()
+                .find(|e| e.is_trunk == Some(true) && e.source == pair[0] && e.target == pair[1])
+        })
+        .cloned()
+        .collect();
+
+    TreeDocument {
+        nodes,
+        edges,
+        ..doc.clone()
+    }
+}
+
+/// Map every node id (including ids only referenced by edges) to a unique
+/// Mermaid-safe identifier. Safe ids map to themselves.
+fn mermaid_ids(doc: &TreeDocument) -> HashMap<&str, String> {
+    let referenced = doc.nodes.iter().map(|n| n.id.as_str()).chain(
+        doc.edges
+            .iter()
+            .flat_map(|e| [e.source.as_str(), e.target.as_str()]),
+    );
+
+    let mut ids: HashMap<&str, String> = HashMap::new();
+    let mut used: HashSet<String> = doc
+        .nodes
+        .iter()
+        .filter(|n| is_mermaid_safe(&n.id))
+        .map(|n| n.id.clone())
+        .collect();
+
+    for id in referenced {
+        if ids.contains_key(id) {
+            continue;
+        }
+        if is_mermaid_safe(id) {
+            used.insert(id.to_string());
+            ids.insert(id, id.to_string());
+            continue;
+        }
+        let base: String = id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let mut candidate = format!("id_{base}");
+        let mut suffix = 1;
+        while used.contains(&candidate) {
+            suffix += 1;
+            candidate = format!("id_{base}_{suffix}");
+        }
+        used.insert(candidate.clone());
+        ids.insert(id, candidate);
+    }
+
+    ids
+}
+
+/// Whether an id can be used as a Mermaid node id without changes.
+fn is_mermaid_safe(id: &str) -> bool {
+    !id.is_empty() && id != "end" && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Escape text for a Mermaid quoted label or `|edge label|`.
+///
+/// Besides the delimiters, `#` is escaped so text that looks like an entity
+/// code survives, and `<` and `>` so neither a literal `<br/>` nor an arrow
+/// such as `-->` can appear inside a label.
+fn escape_mermaid(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("#quot;"),
+            '|' => escaped.push_str("#124;"),
+            '#' => escaped.push_str("#35;"),
+            '<' => escaped.push_str("#60;"),
+            '>' => escaped.push_str("#62;"),
+            '\n' => escaped.push_str("<br/>"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render a document as GraphML for tools such as Gephi and yEd.
+///
+/// Nodes carry `content` and (when set) `status` data; edges carry `isTrunk`
+/// and, when set, `label` and `type`. All `<key>` declarations come before the
+/// graph, as the GraphML schema requires.
+pub fn to_graphml(doc: &TreeDocument) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (id, target, kind) in [
+        ("content", "node", "string"),
+        ("status", "node", "string"),
+        ("label", "edge", "string"),
+        ("isTrunk", "edge", "boolean"),
+        ("type", "edge", "string"),
+    ] {
+        let _ = writeln!(
+            out,
+            "  <key id=\"{id}\" for=\"{target}\" attr.name=\"{id}\" attr.type=\"{kind}\"/>"
+        );
+    }
+    out.push_str("  <graph id=\"tree\" edgedefault=\"directed\">\n");
+
+    for node in &doc.nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", escape_xml(&node.id));
+        write_graphml_data(&mut out, "content", &node.content);
+        if let Some(status) = &node.status {
+            write_graphml_data(&mut out, "status", status.as_str());
+        }
+        out.push_str("    </node>\n");
+    }
+
+    for (i, edge) in doc.edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">",
+            escape_xml(&edge.source),
+            escape_xml(&edge.target)
+        );
+        if let Some(label) = &edge.label {
+            write_graphml_data(&mut out, "label", label);
+        }
+        let is_trunk = if edge.is_trunk == Some(true) { "true" } else { "false" };
+        write_graphml_data(&mut out, "isTrunk", is_trunk);
+        if let Some(edge_type) = &edge.edge_type {
+            write_graphml_data(&mut out, "type", edge_type.as_str());
+        }
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+/// Render the trunk as a Markdown outline for pasting into wikis.
+///
+/// The document title becomes an `#` heading and each trunk step a numbered
+/// item with its content. Branches leaving a step are nested bullets giving the
+/// branch label and target id, and the last trunk step is marked as the end.
+/// Fails for the same reasons as [`build_trunk_view`].
+pub fn to_markdown(doc: &TreeDocument) -> Result<String, String> {
+    let view = build_trunk_view(doc)?;
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", view.title);
+
+    for (i, step) in view.steps.iter().enumerate() {
+        let content = step.content.replace('\n', " ");
+        let end = if step.is_terminal { " **(end)**" } else { "" };
+        let _ = writeln!(out, "{}. {content}{end}", i + 1);
+
+        for branch in &step.branches {
+            let label = branch.label.as_deref().unwrap_or("_(unlabeled)_");
+            let _ = writeln!(out, "   - {label} → `{}`", branch.target);
+        }
+    }
+
+    Ok(out)
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; \
+padding: 0 1rem; line-height: 1.5; color: #222; }
+ol.trunk > li { margin-bottom: 1rem; padding-left: 0.5rem; border-left: 3px solid #2a7ae2; }
+.content { white-space: pre-wrap; margin: 0; }
+.end { font-weight: bold; color: #2a7ae2; }
+details { margin: 0.5rem 0 0 1rem; }
+summary { cursor: pointer; color: #555; }
+.label { font-style: italic; }
+.rejoin { color: #555; }
+";
+
+/// Render a document as a standalone HTML page for sharing.
+///
+/// The trunk is a numbered main column. Each trunk step with branches gets a
+/// collapsible `<details>` block following every branch until it ends, loops
+/// back, or rejoins the trunk, which links to the step it rejoins. The title
+/// comes from `metadata.title`, all content is HTML-escaped, and the page has
+/// inline CSS and no scripts or external resources. Fails for the same
+/// reasons as [`build_trunk_view`].
+pub fn to_html(doc: &TreeDocument) -> Result<String, String> {
+    let view = build_trunk_view(doc)?;
+    let steps: HashMap<&str, usize> = view
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, step)| (step.node_id.as_str(), i + 1))
+        .collect();
+    let title = escape_xml(&view.title);
+    let nodes: HashMap<&str, &Node> = doc.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let mut outgoing: HashMap<&str, Vec<&Edge>> = HashMap::new();
+    for edge in &doc.edges {
+        outgoing.entry(edge.source.as_str()).or_default().push(edge);
+    }
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<title>{title}</title>");
+    let _ = writeln!(out, "<style>\n{HTML_STYLE}</style>");
+    out.push_str("</head>\n<body>\n");
+    let _ = writeln!(out, "<h1>{title}</h1>");
+    let _ = writeln!(out, "<p>{}</p>", escape_xml(&view.stats));
+    out.push_str("<ol class=\"trunk\">\n");
+
+    for (i, step) in view.steps.iter().enumerate() {
+        let _ = writeln!(out, "<li id=\"step-{}\">", i + 1);
+        let _ = writeln!(out, "<p class=\"content\">{}</p>", escape_xml(&step.content));
+        if step.is_terminal {
+            out.push_str("<p class=\"end\">The end</p>\n");
+        }
+
+        let branches: Vec<&Edge> = outgoing
+            .get(step.node_id.as_str())
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|e| e.is_trunk != Some(true))
+            .collect();
+        if !branches.is_empty() {
+            let plural = if branches.len() == 1 { "" } else { "es" };
+            out.push_str("<details>\n");
+            let _ = writeln!(out, "<summary>{} branch{plural}</summary>", branches.len());
+            out.push_str("<ul>\n");
+            let mut visited = HashSet::new();
+            write_html_branches(&mut out, &branches, &outgoing, &nodes, &steps, &mut visited);
+            out.push_str("</ul>\n</details>\n");
+        }
+        out.push_str("</li>\n");
+    }
+
+    out.push_str("</ol>\n</body>\n</html>\n");
+    Ok(out)
+}
+
+/// Write one `<li>` per edge in `branches`, following the off-trunk nodes
+/// each leads to depth-first. Pending edges are kept on an explicit stack so
+/// long branches cannot overflow the call stack.
+fn write_html_branches<'a>(
+    out: &mut String,
+    branches: &[&'a Edge],
+    outgoing: &HashMap<&str, Vec<&'a Edge>>,
+    nodes: &HashMap<&str, &'a Node>,
+    steps: &HashMap<&str, usize>,
+    visited: &mut HashSet<&'a str>,
+) {
+    let mut stack = vec![branches.iter()];
+    while let Some(edges) = stack.last_mut() {
+        let Some(&edge) = edges.next() else {
+            stack.pop();
+            if !stack.is_empty() {
+                out.push_str("</ul>\n</li>\n");
+            }
+            continue;
+        };
+
+        out.push_str("<li>");
+        if let Some(label) = &edge.label {
+            let _ = write!(out, "<span class=\"label\">{}</span> → ", escape_xml(label));
+        }
+
+        let target = edge.target.as_str();
+        if let Some(step) = steps.get(target) {
+            let _ = writeln!(
+                out,
+                "<a class=\"rejoin\" href=\"#step-{step}\">rejoins the trunk at step {step}</a></li>"
+            );
+            continue;
+        }
+        let Some(node) = nodes.get(target) else {
+            let _ = writeln!(out, "<code>{}</code></li>", escape_xml(target));
+            continue;
+        };
+        let _ = write!(
+            out,
+            "<p class=\"content\">{}</p>",
+            escape_xml(&node.content)
+        );
+        if !visited.insert(target) {
+            out.push_str("<span class=\"rejoin\">(continues as above)</span></li>\n");
+            continue;
+        }
+
+        match outgoing.get(target) {
+            Some(next) => {
+                out.push_str("\n<ul>\n");
+                stack.push(next.iter());
+            }
+            None => out.push_str("</li>\n"),
+        }
+    }
+}
+
+/// Render a document as two CSV tables, returned as `(nodes, edges)`.
+///
+/// The nodes table has columns `id,content,status` and the edges table
+/// `source,target,isTrunk,label,type`, each with a header row. Absent fields
+/// are empty and `isTrunk` is `true` or `false`. Quoting follows RFC 4180:
+/// fields containing commas, quotes, or line breaks are wrapped in double
+/// quotes with embedded quotes doubled, and records end with CRLF.
+pub fn to_csv(doc: &TreeDocument) -> (String, String) {
+    let mut nodes = String::new();
+    write_csv_record(&mut nodes, &["id", "content", "status"]);
+    for node in &doc.nodes {
+        let status = node.status.as_ref().map_or("", NodeStatus::as_str);
+        write_csv_record(&mut nodes, &[&node.id, &node.content, status]);
+    }
+
+    let mut edges = String::new();
+    write_csv_record(&mut edges, &["source", "target", "isTrunk", "label", "type"]);
+    for edge in &doc.edges {
+        let is_trunk = if edge.is_trunk == Some(true) { "true" } else { "false" };
+        write_csv_record(
+            &mut edges,
+            &[
+                &edge.source,
+                &edge.target,
+                is_trunk,
+                edge.label.as_deref().unwrap_or(""),
+                edge.edge_type.as_ref().map_or("", EdgeType::as_str),
+            ],
+        );
+    }
+
+    (nodes, edges)
+}
+
+fn write_csv_record(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+fn write_graphml_data(out: &mut String, key: &str, value: &str) {
+    let _ = writeln!(out, "      <data key=\"{key}\">{}</data>", escape_xml(value));
+}
+
+/// Escape a string for XML text or a double-quoted attribute. Control
+/// characters that XML 1.0 cannot represent at all are dropped.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() && (c as u32) < 0x20 => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape a string for use inside a double-quoted DOT identifier or label.
+fn escape_dot(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn dot_contains_edges() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let dot = to_dot(&doc);
+
+        assert!(dot.starts_with("digraph tree {"));
+        assert!(dot.contains("\"n1\" -> \"n2\""));
+        assert!(dot.contains("\"n1\" -> \"n3\""));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn trunk_only_dot_has_just_the_trunk() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let dot = to_dot_trunk(&doc);
+
+        let edges: Vec<_> = dot.lines().filter(|l| l.contains("->")).collect();
+        assert_eq!(edges.len(), 4);
+        for (source, target) in [
+            ("start", "enter"),
+            ("enter", "fountain"),
+            ("fountain", "wish"),
+            ("wish", "ending"),
+        ] {
+            let edge = format!("\"{source}\" -> \"{target}\"");
+            assert!(edges.iter().any(|l| l.contains(&edge)), "{edge}");
+        }
+        assert!(edges.iter().all(|l| l.contains("style=bold")));
+        assert!(!dot.contains("\"climb\""));
+        assert!(!dot.contains("\"explore\""));
+    }
+
+    #[test]
+    fn trunk_only_mermaid_uses_thick_arrows() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let mermaid = to_mermaid_trunk(&doc);
+
+        assert_eq!(mermaid.matches("==>").count(), 4);
+        assert!(!mermaid.contains("-->"));
+        assert!(!mermaid.contains("climb"));
+    }
+
+    #[test]
+    fn dot_trunk_edges_are_bold() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let dot = to_dot(&doc);
+
+        let trunk_line = dot
+            .lines()
+            .find(|l| l.contains("\"start\" -> \"enter\""))
+            .unwrap();
+        assert!(trunk_line.contains("style=bold"));
+        assert!(trunk_line.contains("label=\"Open the gate\""));
+
+        let branch_line = dot
+            .lines()
+            .find(|l| l.contains("\"start\" -> \"climb\""))
+            .unwrap();
+        assert!(!branch_line.contains("style=bold"));
+        assert!(branch_line.contains("label=\"Climb the wall\""));
+    }
+
+    #[test]
+    fn dot_root_has_distinct_shape() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let dot = to_dot(&doc);
+
+        let root_line = dot.lines().find(|l| l.starts_with("  \"n1\" [")).unwrap();
+        assert!(root_line.contains("shape=doubleoctagon"));
+        let other_line = dot.lines().find(|l| l.starts_with("  \"n2\" [")).unwrap();
+        assert!(!other_line.contains("shape=doubleoctagon"));
+    }
+
+    #[test]
+    fn dot_escapes_quotes_and_newlines() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [{"id": "n1", "content": "She said \"hi\"\nthen left \\ quietly"}],
+            "edges": []
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let dot = to_dot(&doc);
+
+        assert!(dot.contains(r#"label="n1\nShe said \"hi\"\nthen left \\ quietly""#));
+    }
+
+    #[test]
+    fn mermaid_trunk_edges_use_thick_arrows() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let mermaid = to_mermaid(&doc);
+
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("    start ==>|Open the gate| enter\n"));
+        assert!(mermaid.contains("    wish ==> ending\n"));
+        assert!(mermaid.contains("    start -->|Climb the wall| climb\n"));
+        assert!(mermaid.contains("    explore --> ending\n"));
+    }
+
+    #[test]
+    fn mermaid_nodes_use_content_labels() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let mermaid = to_mermaid(&doc);
+
+        assert!(mermaid.contains("    n1[\"You stand at a crossroads in the forest.\"]\n"));
+    }
+
+    #[test]
+    fn mermaid_sanitizes_unsafe_ids() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "step-1",
+            "nodes": [
+                {"id": "step-1", "content": "Say \"hello\""},
+                {"id": "end", "content": "Bye"}
+            ],
+            "edges": [{"source": "step-1", "target": "end", "label": "a|b"}]
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let mermaid = to_mermaid(&doc);
+
+        assert!(mermaid.contains("    id_step_1[\"step-1: Say #quot;hello#quot;\"]\n"));
+        assert!(mermaid.contains("    id_end[\"end: Bye\"]\n"));
+        assert!(mermaid.contains("    id_step_1 -->|a#124;b| id_end\n"));
+    }
+
+    #[test]
+    fn graphml_is_valid_xml() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let graphml = to_graphml(&doc);
+
+        let xml = roxmltree::Document::parse(&graphml).unwrap();
+        let nodes = xml
+            .descendants()
+            .filter(|n| n.has_tag_name("node"))
+            .count();
+        let edges = xml
+            .descendants()
+            .filter(|n| n.has_tag_name("edge"))
+            .count();
+        assert_eq!(nodes, doc.nodes.len());
+        assert_eq!(edges, doc.edges.len());
+        assert!(graphml.contains("<data key=\"isTrunk\">true</data>"));
+        assert!(graphml.contains("<data key=\"label\">Open the gate</data>"));
+    }
+
+    #[test]
+    fn graphml_escapes_markup() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a&b",
+            "nodes": [{"id": "a&b", "content": "<b>\"Tom's\"</b>\u0001"}],
+            "edges": []
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let graphml = to_graphml(&doc);
+
+        let xml = roxmltree::Document::parse(&graphml).unwrap();
+        let node = xml.descendants().find(|n| n.has_tag_name("node")).unwrap();
+        assert_eq!(node.attribute("id"), Some("a&b"));
+        let content = node.descendants().find(|n| n.has_tag_name("data")).unwrap();
+        assert_eq!(content.text(), Some("<b>\"Tom's\"</b>"));
+    }
+
+    #[test]
+    fn markdown_outline_of_story() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let md = to_markdown(&doc).unwrap();
+
+        let lines: Vec<_> = md.lines().collect();
+        assert_eq!(lines[0], "# The Enchanted Garden");
+        assert_eq!(lines[1], "");
+        assert!(lines[2].starts_with("1. You discover a hidden gate"));
+        assert_eq!(lines[3], "   - Climb the wall → `climb`");
+        assert!(lines[4].starts_with("2. You push the gate open"));
+        assert_eq!(lines[5], "   - Wander the paths → `explore`");
+        assert!(lines[6].starts_with("3. "));
+        assert!(lines[7].starts_with("4. "));
+        assert!(lines[8].starts_with("5. The garden seems"));
+        assert!(lines[8].ends_with("**(end)**"));
+        assert_eq!(lines.len(), 9);
+    }
+
+    #[test]
+    fn html_has_one_details_per_branching_step() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let html = to_html(&doc).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>The Enchanted Garden</title>"));
+        assert_eq!(html.matches("<details>").count(), 2);
+        assert_eq!(html.matches("<li id=\"step-").count(), 5);
+        // The "explore" branch rejoins the trunk at its last step
+        assert!(html.contains("<a class=\"rejoin\" href=\"#step-5\">"));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn html_follows_long_branches() {
+        const LENGTH: usize = 100_000;
+        let mut nodes = vec![serde_json::json!({"id": "root", "content": "Root"})];
+        let mut edges = vec![serde_json::json!({"source": "root", "target": "b0"})];
+        for i in 0..LENGTH {
+            nodes.push(serde_json::json!({"id": format!("b{i}"), "content": format!("B{i}")}));
+            if i > 0 {
+                let source = format!("b{}", i - 1);
+                edges.push(serde_json::json!({"source": source, "target": format!("b{i}")}));
+            }
+        }
+        let doc: TreeDocument = serde_json::from_value(serde_json::json!({
+            "formatVersion": "1.0",
+            "rootNodeId": "root",
+            "nodes": nodes,
+            "edges": edges
+        }))
+        .unwrap();
+
+        let html = to_html(&doc).unwrap();
+        assert_eq!(html.matches("<ul>").count(), LENGTH);
+        assert_eq!(html.matches("</ul>").count(), LENGTH);
+        assert!(html.contains(&format!("<p class=\"content\">B{}</p>", LENGTH - 1)));
+    }
+
+    #[test]
+    fn html_escapes_content() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "metadata": {"title": "Tom & <Jerry>"},
+            "nodes": [
+                {"id": "a", "content": "<script>alert(1)</script>"},
+                {"id": "b", "content": "\"quoted\""}
+            ],
+            "edges": [{"source": "a", "target": "b", "label": "<b>go</b>"}]
+        }"#;
+        let html = to_html(&parse::parse(json).unwrap()).unwrap();
+
+        assert!(html.contains("<title>Tom &amp; &lt;Jerry&gt;</title>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(html.contains("&lt;b&gt;go&lt;/b&gt;"));
+        assert!(html.contains("&quot;quoted&quot;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn csv_tables_of_minimal() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let (nodes, edges) = to_csv(&doc);
+
+        let node_rows: Vec<_> = nodes.split("\r\n").collect();
+        assert_eq!(node_rows[0], "id,content,status");
+        assert_eq!(node_rows[1], "n1,You stand at a crossroads in the forest.,");
+        assert_eq!(node_rows.len(), 5, "header, three nodes, trailing empty split");
+
+        assert_eq!(
+            edges,
+            "source,target,isTrunk,label,type\r\n\
+             n1,n2,true,,\r\n\
+             n1,n3,false,,\r\n"
+        );
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "Left, right", "status": "draft"},
+                {"id": "b", "content": "She said \"go\""},
+                {"id": "c", "content": "Two\nlines"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "label": "Yes, \"now\"", "type": "choice"}
+            ]
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let (nodes, edges) = to_csv(&doc);
+
+        assert_eq!(
+            nodes,
+            "id,content,status\r\n\
+             a,\"Left, right\",draft\r\n\
+             b,\"She said \"\"go\"\"\",\r\n\
+             c,\"Two\nlines\",\r\n"
+        );
+        assert!(edges.ends_with("a,b,false,\"Yes, \"\"now\"\"\",choice\r\n"));
+    }
+
+    #[test]
+    fn markdown_requires_root() {
+        let json = r#"{"formatVersion": "1.0", "nodes": [], "edges": []}"#;
+        let doc = parse::parse(json).unwrap();
+        assert!(to_markdown(&doc).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::error::Rule;
+use crate::types::TreeDocument;
+
+/// Apply mechanical repairs that cannot change what the document means.
+///
+/// In order, this removes edges that reference nonexistent nodes, drops exact
+/// duplicate edges (keeping the first), and removes nodes unreachable from the
+/// root along with their edges. Edges that share a
+/// [`Edge::dedup_key`](crate::types::Edge::dedup_key) but differ in label or
+/// other annotations are kept, since choosing between them needs an author.
+/// Cycles, trunk problems, and anything else that needs an author's judgement
+/// are left alone.
+///
+/// Returns the rules that were acted on, each at most once, in the order the
+/// fixes were applied. An empty result means the document was not modified.
+pub fn autofix(doc: &mut TreeDocument) -> Vec<Rule> {
+    let mut fixed = Vec::new();
+
+    if remove_dangling_edges(doc) {
+        fixed.push(Rule::DanglingEdge);
+    }
+    if dedup_edges(doc) {
+        fixed.push(Rule::DuplicateEdge);
+    }
+    if remove_orphans(doc) {
+        fixed.push(Rule::OrphanNode);
+    }
+
+    fixed
+}
+
+fn remove_dangling_edges(doc: &mut TreeDocument) -> bool {
+    let node_ids: HashSet<String> = doc.nodes.iter().map(|n| n.id.clone()).collect();
+    let before = doc.edges.len();
+    doc.edges
+        .retain(|e| node_ids.contains(&e.source) && node_ids.contains(&e.target));
+    doc.edges.len() != before
+}
+
+fn dedup_edges(doc: &mut TreeDocument) -> bool {
+    let mut seen = HashSet::new();
+    let before = doc.edges.len();
+    doc.edges.retain(|e| seen.insert(e.clone()));
+    doc.edges.len() != before
+}
+
+/// Remove nodes unreachable from the root, using the same reachability as the
+/// `orphan-node` rule. Documents without a valid root are left untouched.
+fn remove_orphans(doc: &mut TreeDocument) -> bool {
+    let root_id = match &doc.root_node_id {
+        Some(id) if doc.node(id).is_some() => id.clone(),
+        _ => return false,
+    };
+
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &doc.edges {
+        adjacency
+            .entry(edge.source.as_str())
+            .or_default()
+            .push(edge.target.as_str());
+    }
+
+    let mut visited = HashSet::from([root_id.as_str()]);
+    let mut queue = VecDeque::from([root_id.as_str()]);
+    while let Some(current) = queue.pop_front() {
+        for &neighbor in adjacency.get(current).into_iter().flatten() {
+            if visited.insert(neighbor) {
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    let reachable: HashSet<String> = visited.into_iter().map(str::to_string).collect();
+    let before = doc.nodes.len();
+    doc.nodes.retain(|n| reachable.contains(&n.id));
+    doc.edges
+        .retain(|e| reachable.contains(&e.source) && reachable.contains(&e.target));
+    doc.nodes.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse;
+    use crate::validate::validate_document;
+
+    #[test]
+    fn removes_dangling_edges() {
+        let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+        let mut doc = parse(json).unwrap();
+        assert!(!validate_document(json).unwrap().is_valid);
+
+        let fixed = autofix(&mut doc);
+        assert_eq!(fixed.first(), Some(&Rule::DanglingEdge));
+
+        let repaired = serde_json::to_string(&doc).unwrap();
+        let result = validate_document(&repaired).unwrap();
+        assert!(result.is_valid, "{:?}", result.errors);
+    }
+
+    #[test]
+    fn dedups_identical_edges() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Next"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n2", "label": "Also"}
+            ]
+        }"#;
+        let mut doc = parse(json).unwrap();
+        assert_eq!(autofix(&mut doc), vec![Rule::DuplicateEdge]);
+        assert_eq!(doc.edges.len(), 2);
+    }
+
+    #[test]
+    fn keeps_edges_that_differ_by_label() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Next"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "label": "Go"},
+                {"source": "n1", "target": "n2", "label": "Run"}
+            ]
+        }"#;
+        let mut doc = parse(json).unwrap();
+        assert!(autofix(&mut doc).is_empty());
+        assert_eq!(doc.edges.len(), 2);
+    }
+
+    #[test]
+    fn drops_orphans_and_their_edges() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Orphan"}
+            ],
+            "edges": [
+                {"source": "n2", "target": "n1"}
+            ]
+        }"#;
+        let mut doc = parse(json).unwrap();
+        assert_eq!(autofix(&mut doc), vec![Rule::OrphanNode]);
+        assert_eq!(doc.nodes.len(), 1);
+        assert!(doc.edges.is_empty());
+    }
+
+    #[test]
+    fn leaves_cycles_alone() {
+        let json = include_str!("../../../examples/invalid/trunk-cycle.tree.json");
+        let mut doc = parse(json).unwrap();
+        let edges_before = doc.edges.len();
+        assert!(autofix(&mut doc).is_empty());
+        assert_eq!(doc.edges.len(), edges_before);
+    }
+}
@@ -0,0 +1,52 @@
+//! Document fixtures shared by unit tests.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+/// A document whose root `a` leads to the ending `b` along one trunk edge,
+/// with each top-level field in `fields` added or replaced. Without any
+/// fields it validates with no diagnostics at all.
+pub(crate) fn document_with(fields: Value) -> String {
+    let mut doc = json!({
+        "formatVersion": "1.0",
+        "rootNodeId": "a",
+        "nodes": [
+            {"id": "a", "content": "A"},
+            {"id": "b", "content": "B", "status": "ending"}
+        ],
+        "edges": [{"source": "a", "target": "b", "isTrunk": true}]
+    });
+    for (key, value) in fields.as_object().expect("fields must be an object") {
+        doc[key] = value.clone();
+    }
+    doc.to_string()
+}
+
+/// A per-test directory in the system temp directory, removed on drop so a
+/// failing assertion doesn't leave it behind.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("tree-doc-{name}-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::types::{Edge, Node, TreeDocument};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ImportError {
+    #[error("expected a `flowchart TD` header, found: {0}")]
+    MissingHeader(String),
+    #[error("line {line}: unsupported Mermaid syntax: {text}")]
+    Unsupported { line: usize, text: String },
+    #[error("line {line}: node '{id}' is declared more than once")]
+    DuplicateNode { line: usize, id: String },
+    #[error("flowchart declares no nodes")]
+    Empty,
+}
+
+/// Build a document from a simple Mermaid `flowchart TD` block.
+///
+/// The supported subset is what `to_mermaid` emits: `id["content"]` node
+/// declarations and `a --> b`, `a -->|label| b`, `a ==> b` edges, where the
+/// thick `==>` marks a trunk edge. Ids used by an edge but never declared
+/// become nodes whose content is the id. Blank lines and `%%` comments are
+/// skipped; any other construct is rejected with the offending line.
+///
+/// The root is the first declared node without inbound edges, falling back to
+/// the first declared node when every node has one.
+pub fn from_mermaid(src: &str) -> Result<TreeDocument, ImportError> {
+    let mut lines = src
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with("%%"));
+
+    match lines.next() {
+        Some((_, "flowchart TD" | "graph TD")) => {}
+        Some((_, other)) => return Err(ImportError::MissingHeader(other.to_string())),
+        None => return Err(ImportError::Empty),
+    }
+
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut declared: HashSet<String> = HashSet::new();
+    let mut edges: Vec<Edge> = Vec::new();
+
+    let mut ensure_node = |nodes: &mut Vec<Node>, id: &str| {
+        if !positions.contains_key(id) {
+            positions.insert(id.to_string(), nodes.len());
+            nodes.push(new_node(id, id.to_string()));
+        }
+        positions[id]
+    };
+
+    for (line_no, line) in lines {
+        let unsupported = || ImportError::Unsupported {
+            line: line_no,
+            text: line.to_string(),
+        };
+
+        if let Some((source, is_trunk, label, target)) = parse_edge(line) {
+            if !is_valid_id(source) || !is_valid_id(target) {
+                return Err(unsupported());
+            }
+            ensure_node(&mut nodes, source);
+            ensure_node(&mut nodes, target);
+            let mut edge = new_edge(source, target);
+            edge.is_trunk = is_trunk.then_some(true);
+            edge.label = label.map(unescape_mermaid);
+            edges.push(edge);
+        } else if let Some((id, content)) = parse_node(line) {
+            if !is_valid_id(id) {
+                return Err(unsupported());
+            }
+            if !declared.insert(id.to_string()) {
+                return Err(ImportError::DuplicateNode {
+                    line: line_no,
+                    id: id.to_string(),
+                });
+            }
+            let index = ensure_node(&mut nodes, id);
+            nodes[index].content = unescape_mermaid(content);
+        } else {
+            return Err(unsupported());
+        }
+    }
+
+    if nodes.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    let targets: HashSet<&str> = edges.iter().map(|e| e.target.as_str()).collect();
+    let root = nodes
+        .iter()
+        .find(|n| !targets.contains(n.id.as_str()))
+        .unwrap_or(&nodes[0])
+        .id
+        .clone();
+
+    Ok(TreeDocument {
+        format_version: "1.0".to_string(),
+        root_node_id: Some(root),
+        nodes,
+        edges,
+        min_reader_version: None,
+        features: None,
+        metadata: None,
+        trees: None,
+        embedding_ref: None,
+        extra: Default::default(),
+    })
+}
+
+/// Export `doc` with `to_mermaid` and import the result with [`from_mermaid`],
+/// to check what survives the trip.
+///
+/// Node ids, content, edge endpoints, edge labels, and trunk membership are
+/// preserved, except that ids Mermaid cannot use are replaced by generated
+/// ones. Everything else is lost: node and edge `status`, `metadata`,
+/// `treeIds`, the edge `type`, `description`, `treeId`, and `linkType`, and
+/// all document-level fields. `formatVersion` comes back as `1.0`, and the
+/// root is re-derived, so a root with inbound edges may not survive.
+pub fn round_trip_mermaid(doc: &TreeDocument) -> Result<TreeDocument, String> {
+    from_mermaid(&crate::export::to_mermaid(doc)).map_err(|e| e.to_string())
+}
+
+/// Split `a -->|label| b` / `a ==> b` into (source, is_trunk, label, target).
+///
+/// The arrow must come before any quoted or `|`-delimited label, so a node
+/// whose content contains `-->` is not mistaken for an edge.
+fn parse_edge(line: &str) -> Option<(&str, bool, Option<&str>, &str)> {
+    let head = &line[..line.find(['"', '[', '|']).unwrap_or(line.len())];
+    let (arrow_at, is_trunk) = match (head.find("-->"), head.find("==>")) {
+        (Some(i), Some(j)) if j < i => (j, true),
+        (Some(i), _) => (i, false),
+        (None, Some(j)) => (j, true),
+        (None, None) => return None,
+    };
+
+    let source = line[..arrow_at].trim();
+    let rest = &line[arrow_at + 3..];
+    let (label, target) = match rest.strip_prefix('|') {
+        Some(labelled) => {
+            let end = labelled.find('|')?;
+            (Some(&labelled[..end]), labelled[end + 1..].trim())
+        }
+        None => (None, rest.trim()),
+    };
+    Some((source, is_trunk, label, target))
+}
+
+/// Split `id["content"]` into (id, raw content).
+fn parse_node(line: &str) -> Option<(&str, &str)> {
+    let (id, rest) = line.split_once("[\"")?;
+    let content = rest.strip_suffix("\"]")?;
+    Some((id.trim(), content))
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Reverse the escaping `to_mermaid` applies to labels: `<br/>` becomes a
+/// line break, and `#quot;` and numeric codes such as `#35;` become the
+/// character they stand for. Anything else is kept as written.
+fn unescape_mermaid(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(at) = rest.find(['#', '<']) {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        if let Some(after) = rest.strip_prefix("<br/>") {
+            out.push('\n');
+            rest = after;
+            continue;
+        }
+        let entity = rest[1..].split_once(';').and_then(|(code, after)| {
+            let c = match code {
+                "quot" => '"',
+                _ => char::from_u32(code.parse().ok()?)?,
+            };
+            Some((c, after))
+        });
+        match entity {
+            Some((c, after)) => {
+                out.push(c);
+                rest = after;
+            }
+            None => {
+                out.push_str(&rest[..1]);
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn new_node(id: &str, content: String) -> Node {
+    Node {
+        id: id.to_string(),
+        content,
+        metadata: None,
+        status: None,
+        tree_ids: None,
+        extra: Default::default(),
+    }
+}
+
+fn new_edge(source: &str, target: &str) -> Edge {
+    Edge {
+        source: source.to_string(),
+        target: target.to_string(),
+        is_trunk: None,
+        label: None,
+        edge_type: None,
+        status: None,
+        description: None,
+        tree_id: None,
+        link_type: None,
+        extra: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_nodes() {
+        let doc = from_mermaid(
+            "flowchart TD\n    n1[\"Start\"]\n    n2[\"Say #quot;hi#quot;<br/>twice\"]\n",
+        )
+        .unwrap();
+        assert_eq!(doc.nodes.len(), 2);
+        assert_eq!(doc.nodes[0].content, "Start");
+        assert_eq!(doc.nodes[1].content, "Say \"hi\"\ntwice");
+        assert_eq!(doc.root_node_id.as_deref(), Some("n1"));
+    }
+
+    #[test]
+    fn imports_labeled_branches() {
+        let doc = from_mermaid(
+            "flowchart TD\n\
+             start[\"Start\"]\n\
+             left[\"Left\"]\n\
+             start -->|Go left| left\n\
+             start --> right\n",
+        )
+        .unwrap();
+        assert_eq!(doc.edges.len(), 2);
+        assert_eq!(doc.edges[0].label.as_deref(), Some("Go left"));
+        assert_eq!(doc.edges[0].is_trunk, None);
+        assert_eq!(doc.edges[1].label, None);
+        // Undeclared ids become nodes whose content is the id
+        assert_eq!(doc.node("right").unwrap().content, "right");
+    }
+
+    #[test]
+    fn thick_arrows_are_trunk() {
+        let doc = from_mermaid("flowchart TD\n a ==> b\n b ==>|Onward| c\n a --> c\n").unwrap();
+        let trunk: Vec<_> = doc.edges.iter().map(|e| e.is_trunk == Some(true)).collect();
+        assert_eq!(trunk, [true, true, false]);
+        assert_eq!(doc.edges[1].label.as_deref(), Some("Onward"));
+    }
+
+    #[test]
+    fn root_is_first_node_without_inbound_edges() {
+        let doc = from_mermaid("flowchart TD\n b[\"B\"]\n a[\"A\"]\n a ==> b\n").unwrap();
+        assert_eq!(doc.root_node_id.as_deref(), Some("a"));
+
+        let doc = from_mermaid("flowchart TD\n a --> b\n b --> a\n").unwrap();
+        assert_eq!(doc.root_node_id.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn round_trips_to_mermaid_output() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = crate::parse::parse(json).unwrap();
+        let imported = from_mermaid(&crate::export::to_mermaid(&doc)).unwrap();
+        assert_eq!(imported.nodes.len(), doc.nodes.len());
+        assert_eq!(imported.edges, doc.edges);
+        assert_eq!(imported.root_node_id, doc.root_node_id);
+    }
+
+    fn assert_structure_survives(json: &str) {
+        let doc = crate::parse::parse(json).unwrap();
+        let back = round_trip_mermaid(&doc).unwrap();
+
+        assert_eq!(back.nodes.len(), doc.nodes.len());
+        assert_eq!(back.edges.len(), doc.edges.len());
+        let trunk = |d: &TreeDocument| -> Vec<(String, String)> {
+            d.edges
+                .iter()
+                .filter(|e| e.is_trunk == Some(true))
+                .map(|e| (e.source.clone(), e.target.clone()))
+                .collect()
+        };
+        assert_eq!(trunk(&back), trunk(&doc));
+        assert_eq!(back.root_node_id, doc.root_node_id);
+    }
+
+    #[test]
+    fn round_trip_minimal() {
+        assert_structure_survives(include_str!("../../../examples/minimal.tree.json"));
+    }
+
+    #[test]
+    fn round_trip_story_drops_metadata() {
+        let json = include_str!("../../../examples/story.tree.json");
+        assert_structure_survives(json);
+
+        let back = round_trip_mermaid(&crate::parse::parse(json).unwrap()).unwrap();
+        assert_eq!(back.metadata, None);
+    }
+
+    #[test]
+    fn arrows_inside_labels_are_content() {
+        let doc = from_mermaid("flowchart TD\n a[\"Go --> there\"]\n b[\"x ==> y\"]\n").unwrap();
+        assert!(doc.edges.is_empty());
+        assert_eq!(doc.nodes[0].content, "Go --> there");
+        assert_eq!(doc.nodes[1].content, "x ==> y");
+    }
+
+    #[test]
+    fn round_trip_keeps_markup_in_text() {
+        let json = r##"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "Go --> there, then ==> back"},
+                {"id": "b", "content": "Literal #quot; and <br/> and #35;"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "label": "a -> b | <b>#1</b>"}
+            ]
+        }"##;
+        let doc = crate::parse::parse(json).unwrap();
+        let back = round_trip_mermaid(&doc).unwrap();
+        assert_eq!(back.nodes[0].content, doc.nodes[0].content);
+        assert_eq!(back.nodes[1].content, doc.nodes[1].content);
+        assert_eq!(back.edges[0].label, doc.edges[0].label);
+    }
+
+    #[test]
+    fn rejects_unsupported_syntax() {
+        let err = from_mermaid("flowchart TD\n a --> b\n subgraph one\n").unwrap_err();
+        assert_eq!(
+            err,
+            ImportError::Unsupported {
+                line: 3,
+                text: "subgraph one".to_string()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 3: unsupported Mermaid syntax: subgraph one"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_header() {
+        let err = from_mermaid("sequenceDiagram\n").unwrap_err();
+        assert!(matches!(err, ImportError::MissingHeader(_)));
+    }
+}
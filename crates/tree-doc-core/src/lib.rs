@@ -1,13 +1,54 @@
+pub mod diff;
+pub mod embedding;
 pub mod error;
+pub mod export;
+#[cfg(test)]
+mod fixtures;
+pub mod fix;
+pub mod import;
 pub mod parse;
+pub mod query;
+pub mod scaffold;
 pub mod schema;
+pub mod span;
+pub mod stats;
+pub mod transform;
 pub mod types;
 pub mod validate;
 pub mod viewer;
 
+pub use diff::{diff, TreeDiff};
+pub use embedding::{resolve_embedding, EmbeddingError};
 pub use error::{Diagnostic, DocumentStats, Severity, ValidationResult};
-pub use parse::{parse, parse_value};
-pub use schema::{detect_tier, validate_schema};
-pub use types::TreeDocument;
-pub use validate::validate_document;
-pub use viewer::{build_trunk_view, TrunkView};
+pub use export::{
+    to_csv, to_dot, to_dot_trunk, to_graphml, to_html, to_markdown, to_mermaid, to_mermaid_trunk,
+};
+pub use fix::autofix;
+pub use import::{from_mermaid, round_trip_mermaid, ImportError};
+pub use parse::{
+    decode_maybe_gzip, parse, parse_maybe_gzip, parse_maybe_gzip_with_limits, parse_value,
+    parse_with_limits, ParseLimits,
+};
+pub use query::{
+    all_paths, degrees, endings, find_nodes, longest_path, reachable_from, shortest_path,
+    topo_order,
+};
+pub use scaffold::scaffold;
+pub use schema::{compile_schema, detect_tier, validate_schema, validate_schema_with};
+pub use span::{attach_spans, line_column};
+pub use stats::{compute_stats, trunk_coverage, ExtendedStats};
+pub use transform::{
+    canonicalize, extract_subtree, filter_edge_type, merge, normalize_ids, prune_orphans,
+    MergeError,
+};
+pub use types::{DocumentMetadata, EdgeType, NodeStatus, TreeDocument};
+pub use validate::{
+    diagnostics_for_node, revalidate_node, sorted_diagnostics, validate_and_parse,
+    validate_bytes_with_options, validate_document, validate_document_with_spans,
+    validate_document_with_options, validate_files, validate_reader, ValidationCache,
+    ValidationOptions,
+};
+pub use viewer::{
+    build_branch_view, build_full_view, build_full_view_filtered, build_tree_view,
+    build_trunk_view, build_trunk_view_filtered, BranchInfo, FullView, TrunkView,
+};
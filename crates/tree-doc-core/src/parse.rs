@@ -1,10 +1,67 @@
-use crate::types::TreeDocument;
+use std::io::{self, Read};
+
+use flate2::read::GzDecoder;
 use thiserror::Error;
 
+use crate::types::TreeDocument;
+
 #[derive(Debug, Error)]
 pub enum ParseError {
-    #[error("invalid JSON: {0}")]
-    InvalidJson(#[from] serde_json::Error),
+    #[error("invalid JSON at line {line} column {column}: {message}")]
+    InvalidJson {
+        message: String,
+        line: usize,
+        column: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("document exceeds the {limit} limit ({actual} > {max})")]
+    LimitExceeded {
+        /// Which limit was crossed: `"bytes"`, `"nodes"`, or `"edges"`.
+        limit: &'static str,
+        actual: usize,
+        max: usize,
+    },
+    #[error("failed to read input: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Upper bounds enforced by [`parse_with_limits`] for untrusted input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    pub max_bytes: usize,
+    pub max_nodes: usize,
+    pub max_edges: usize,
+}
+
+impl Default for ParseLimits {
+    /// 16 MiB and 100,000 nodes/edges: far beyond hand-authored documents.
+    fn default() -> Self {
+        ParseLimits {
+            max_bytes: 16 * 1024 * 1024,
+            max_nodes: 100_000,
+            max_edges: 100_000,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(err: serde_json::Error) -> Self {
+        let line = err.line();
+        let column = err.column();
+        // serde_json appends the position to its message; we report it ourselves.
+        let full = err.to_string();
+        let message = full
+            .strip_suffix(&format!(" at line {line} column {column}"))
+            .unwrap_or(&full)
+            .to_string();
+        ParseError::InvalidJson {
+            message,
+            line,
+            column,
+            source: err,
+        }
+    }
 }
 
 pub fn parse(json_str: &str) -> Result<TreeDocument, ParseError> {
@@ -12,11 +69,95 @@ pub fn parse(json_str: &str) -> Result<TreeDocument, ParseError> {
     Ok(doc)
 }
 
+/// Parse like [`parse`], but reject input that crosses any of `limits`.
+///
+/// The byte length is checked before any parsing happens; node and edge
+/// counts are checked once the document has been deserialized.
+pub fn parse_with_limits(json_str: &str, limits: ParseLimits) -> Result<TreeDocument, ParseError> {
+    check_limit("bytes", json_str.len(), limits.max_bytes)?;
+    let doc = parse(json_str)?;
+    check_limit("nodes", doc.nodes.len(), limits.max_nodes)?;
+    check_limit("edges", doc.edges.len(), limits.max_edges)?;
+    Ok(doc)
+}
+
+fn check_limit(limit: &'static str, actual: usize, max: usize) -> Result<(), ParseError> {
+    if actual > max {
+        return Err(ParseError::LimitExceeded { limit, actual, max });
+    }
+    Ok(())
+}
+
 pub fn parse_value(json_str: &str) -> Result<serde_json::Value, ParseError> {
     let value: serde_json::Value = serde_json::from_str(json_str)?;
     Ok(value)
 }
 
+/// The two bytes every gzip stream starts with.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Whether `bytes` start like a gzip stream.
+pub fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC)
+}
+
+/// Decode raw document bytes as UTF-8 text, decompressing them first when
+/// they are gzipped. Corrupt gzip data and invalid UTF-8 are I/O errors.
+///
+/// Decompression is unbounded; use [`parse_maybe_gzip_with_limits`] for
+/// untrusted input.
+pub fn decode_maybe_gzip(bytes: Vec<u8>) -> io::Result<String> {
+    if is_gzip(&bytes) {
+        gunzip(&bytes)
+    } else {
+        String::from_utf8(bytes).map_err(invalid_utf8)
+    }
+}
+
+/// Parse a document that may be gzipped, detected by its magic bytes rather
+/// than a file name, so it works on stdin too.
+pub fn parse_maybe_gzip(bytes: &[u8]) -> Result<TreeDocument, ParseError> {
+    if is_gzip(bytes) {
+        parse(&gunzip(bytes)?)
+    } else {
+        parse(utf8(bytes)?)
+    }
+}
+
+/// Parse like [`parse_maybe_gzip`], but reject input that crosses any of
+/// `limits`. For gzipped input `max_bytes` bounds the decompressed size, and
+/// decompression stops as soon as it is crossed.
+pub fn parse_maybe_gzip_with_limits(
+    bytes: &[u8],
+    limits: ParseLimits,
+) -> Result<TreeDocument, ParseError> {
+    if !is_gzip(bytes) {
+        return parse_with_limits(utf8(bytes)?, limits);
+    }
+    let max = u64::try_from(limits.max_bytes).unwrap_or(u64::MAX);
+    let mut decoded = Vec::new();
+    GzDecoder::new(bytes)
+        .take(max.saturating_add(1))
+        .read_to_end(&mut decoded)?;
+    check_limit("bytes", decoded.len(), limits.max_bytes)?;
+    let text = String::from_utf8(decoded).map_err(invalid_utf8)?;
+    parse_with_limits(&text, limits)
+}
+
+fn gunzip(bytes: &[u8]) -> io::Result<String> {
+    let mut text = String::new();
+    GzDecoder::new(bytes).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+fn utf8(bytes: &[u8]) -> io::Result<&str> {
+    std::str::from_utf8(bytes).map_err(invalid_utf8)
+}
+
+fn invalid_utf8(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,6 +172,55 @@ mod tests {
         assert_eq!(doc.edges.len(), 2);
     }
 
+    #[test]
+    fn parse_gzipped_document() {
+        use std::io::Write;
+
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        assert!(is_gzip(&gzipped));
+        let expected = parse(json).unwrap().to_canonical_json();
+        let doc = parse_maybe_gzip(&gzipped).unwrap();
+        assert_eq!(doc.to_canonical_json(), expected);
+        let plain = parse_maybe_gzip(json.as_bytes()).unwrap();
+        assert_eq!(plain.to_canonical_json(), expected);
+
+        let truncated = &gzipped[..gzipped.len() / 2];
+        assert!(matches!(parse_maybe_gzip(truncated), Err(ParseError::Io(_))));
+    }
+
+    #[test]
+    fn gzipped_input_is_bounded_by_limits() {
+        use std::io::Write;
+
+        // Whitespace compresses to almost nothing, so a small stream can
+        // expand far past the limit
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let padded = format!("{}{json}", " ".repeat(1 << 20));
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(padded.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        assert!(gzipped.len() < 4096);
+
+        let limits = ParseLimits {
+            max_bytes: 4096,
+            ..ParseLimits::default()
+        };
+        let err = parse_maybe_gzip_with_limits(&gzipped, limits).unwrap_err();
+        assert!(
+            matches!(err, ParseError::LimitExceeded { limit: "bytes", max: 4096, .. }),
+            "{err}"
+        );
+        let err = parse_maybe_gzip_with_limits(padded.as_bytes(), limits).unwrap_err();
+        assert!(matches!(err, ParseError::LimitExceeded { limit: "bytes", .. }), "{err}");
+
+        let doc = parse_maybe_gzip_with_limits(&gzipped, ParseLimits::default()).unwrap();
+        assert_eq!(doc.nodes.len(), 3);
+    }
+
     #[test]
     fn parse_tier1_document() {
         let json = include_str!("../../../examples/story.tree.json");
@@ -50,12 +240,117 @@ mod tests {
         assert_eq!(doc.edges.len(), doc2.edges.len());
     }
 
+    #[test]
+    fn serialize_omits_absent_fields() {
+        fn assert_no_nulls(value: &serde_json::Value) {
+            match value {
+                serde_json::Value::Null => panic!("serialized document contains null"),
+                serde_json::Value::Array(items) => items.iter().for_each(assert_no_nulls),
+                serde_json::Value::Object(map) => map.values().for_each(assert_no_nulls),
+                _ => {}
+            }
+        }
+
+        for json in [
+            include_str!("../../../examples/minimal.tree.json"),
+            include_str!("../../../examples/multi-tree.tree.json"),
+        ] {
+            let doc = parse(json).unwrap();
+            let value = serde_json::to_value(&doc).unwrap();
+            assert_no_nulls(&value);
+        }
+
+        let minimal = parse(include_str!("../../../examples/minimal.tree.json")).unwrap();
+        let value = serde_json::to_value(&minimal).unwrap();
+        assert!(value.get("trees").is_none());
+        assert!(value.get("embeddingRef").is_none());
+    }
+
     #[test]
     fn parse_invalid_json() {
         let result = parse("not json at all");
         assert!(result.is_err());
     }
 
+    #[test]
+    fn parse_error_reports_position() {
+        let err = parse("{\n  \"formatVersion\": }").unwrap_err();
+        let ParseError::InvalidJson { line, column, .. } = &err else {
+            panic!("expected InvalidJson, got {err:?}");
+        };
+        assert_eq!(*line, 2);
+        assert_eq!(*column, 20);
+        let message = err.to_string();
+        assert!(message.contains("at line 2 column 20"), "got: {message}");
+        assert_eq!(message.matches("line 2").count(), 1, "got: {message}");
+    }
+
+    fn minimal_limits() -> ParseLimits {
+        // minimal.tree.json has 3 nodes and 2 edges
+        let json = include_str!("../../../examples/minimal.tree.json");
+        ParseLimits {
+            max_bytes: json.len(),
+            max_nodes: 3,
+            max_edges: 2,
+        }
+    }
+
+    #[test]
+    fn limits_at_boundary_pass() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        assert!(parse_with_limits(json, minimal_limits()).is_ok());
+    }
+
+    #[test]
+    fn byte_limit_exceeded() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let limits = ParseLimits {
+            max_bytes: json.len() - 1,
+            ..minimal_limits()
+        };
+        let err = parse_with_limits(json, limits).unwrap_err();
+        assert!(matches!(err, ParseError::LimitExceeded { limit: "bytes", .. }));
+    }
+
+    #[test]
+    fn byte_limit_checked_before_parsing() {
+        let limits = ParseLimits {
+            max_bytes: 4,
+            ..ParseLimits::default()
+        };
+        let err = parse_with_limits("not json at all", limits).unwrap_err();
+        assert!(matches!(err, ParseError::LimitExceeded { limit: "bytes", .. }));
+    }
+
+    #[test]
+    fn node_limit_exceeded() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let limits = ParseLimits {
+            max_nodes: 2,
+            ..minimal_limits()
+        };
+        let err = parse_with_limits(json, limits).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::LimitExceeded { limit: "nodes", actual: 3, max: 2 }
+        ));
+    }
+
+    #[test]
+    fn edge_limit_exceeded() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let limits = ParseLimits {
+            max_edges: 1,
+            ..minimal_limits()
+        };
+        let err = parse_with_limits(json, limits).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::LimitExceeded { limit: "edges", actual: 2, max: 1 }
+        ));
+        assert_eq!(err.to_string(), "document exceeds the edges limit (2 > 1)");
+    }
+
     #[test]
     fn parse_value_returns_value() {
         let json = r#"{"formatVersion": "1.0"}"#;
@@ -0,0 +1,647 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use petgraph::algo::{kosaraju_scc, toposort};
+use petgraph::graph::{DiGraph, NodeIndex};
+
+use crate::types::{Node, TreeDocument};
+
+/// Find the shortest directed path from `from` to `to`, following both
+/// trunk and branch edges.
+///
+/// Returns the node ids along the path, including both endpoints, or `None`
+/// if either endpoint does not exist or `to` is unreachable from `from`.
+/// Ties are broken by edge order in the document.
+pub fn shortest_path(doc: &TreeDocument, from: &str, to: &str) -> Option<Vec<String>> {
+    doc.node(from)?;
+    doc.node(to)?;
+
+    let outgoing = doc.edges_by_source();
+    let mut visited = HashSet::from([from]);
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            let mut path = vec![current.to_string()];
+            let mut step = current;
+            while let Some(&prev) = parent.get(step) {
+                path.push(prev.to_string());
+                step = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for edge in outgoing.get(current).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if visited.insert(next) {
+                parent.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Collect the ids of every node reachable from `start` along directed edges,
+/// including `start` itself.
+///
+/// Both trunk and branch edges are followed; edges to unknown nodes are
+/// ignored. Fails if `start` is not a node.
+pub fn reachable_from(doc: &TreeDocument, start: &str) -> Result<HashSet<String>, String> {
+    if doc.node(start).is_none() {
+        return Err(format!("Node '{start}' not found in nodes array"));
+    }
+
+    let outgoing = doc.edges_by_source();
+    let mut reachable = HashSet::from([start.to_string()]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        for edge in outgoing.get(current).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if reachable.insert(next.to_string()) {
+                queue.push_back(next);
+            }
+        }
+    }
+    Ok(reachable)
+}
+
+/// Enumerate complete paths from the root to an ending, stopping after
+/// `max_paths` of them.
+///
+/// Paths follow both trunk and branch edges in document order, and end at a
+/// node without outgoing edges, as in [`endings`]. A path never revisits a
+/// node, so cycles are cut short rather than followed forever; a path that can
+/// only continue into itself is dropped. Edges to unknown nodes are ignored.
+/// Returns nothing when the document has no root.
+///
+/// Nodes that cannot reach an ending are never entered. Densely cyclic
+/// documents can still have more simple paths than could ever be listed, so
+/// the search gives up after `ALL_PATHS_SEARCH_LIMIT` steps and returns the
+/// paths found so far.
+pub fn all_paths(doc: &TreeDocument, max_paths: usize) -> Vec<Vec<String>> {
+    let mut paths = Vec::new();
+    let node_ids: HashSet<&str> = doc.nodes.iter().map(|n| n.id.as_str()).collect();
+    let Some(root) = doc.root_node_id.as_deref().filter(|r| node_ids.contains(r)) else {
+        return paths;
+    };
+    let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut predecessors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &doc.edges {
+        successors
+            .entry(edge.source.as_str())
+            .or_default()
+            .push(edge.target.as_str());
+        predecessors
+            .entry(edge.target.as_str())
+            .or_default()
+            .push(edge.source.as_str());
+    }
+
+    // Walk backwards from the endings to find every node that can finish.
+    let mut finishes: HashSet<&str> = node_ids
+        .iter()
+        .copied()
+        .filter(|id| !successors.contains_key(id))
+        .collect();
+    let mut queue: VecDeque<&str> = finishes.iter().copied().collect();
+    while let Some(current) = queue.pop_front() {
+        for &source in predecessors.get(current).into_iter().flatten() {
+            if node_ids.contains(source) && finishes.insert(source) {
+                queue.push_back(source);
+            }
+        }
+    }
+    if !finishes.contains(root) {
+        return paths;
+    }
+
+    // `cursors[i]` is the index of the next successor to try from `path[i]`.
+    let mut path = vec![root];
+    let mut cursors = vec![0];
+    let mut on_path = HashSet::from([root]);
+    let mut steps = 0;
+    while paths.len() < max_paths && steps < ALL_PATHS_SEARCH_LIMIT {
+        let (Some(&current), Some(next)) = (path.last(), cursors.last_mut()) else {
+            break;
+        };
+        let targets = successors.get(current).map_or(&[][..], Vec::as_slice);
+        if targets.is_empty() {
+            paths.push(path.iter().map(|id| id.to_string()).collect());
+        }
+
+        let step = targets[*next..]
+            .iter()
+            .position(|t| finishes.contains(t) && !on_path.contains(t));
+        match step {
+            Some(offset) => {
+                let target = targets[*next + offset];
+                *next += offset + 1;
+                on_path.insert(target);
+                path.push(target);
+                cursors.push(0);
+                steps += 1;
+            }
+            None => {
+                on_path.remove(current);
+                path.pop();
+                cursors.pop();
+            }
+        }
+    }
+    paths
+}
+
+/// Most path extensions [`all_paths`] tries before returning the paths found
+/// so far.
+const ALL_PATHS_SEARCH_LIMIT: usize = 1_000_000;
+
+/// Most path extensions [`longest_path`] tries on a cyclic document before
+/// settling for the longest path found so far.
+const LONGEST_PATH_SEARCH_LIMIT: usize = 100_000;
+
+/// The longest simple path from the root, as node ids, following both trunk
+/// and branch edges.
+///
+/// When no cycle is reachable from the root the answer is exact, found by
+/// relaxing edges in topological order. Otherwise finding it is NP-hard, so a
+/// depth-first search over simple paths stops after a fixed number of steps
+/// and returns the longest path seen. Ties go to the path found first in edge
+/// order. Returns nothing when the document has no valid root.
+pub fn longest_path(doc: &TreeDocument) -> Vec<String> {
+    let Some(root) = doc.root_node_id.as_deref().filter(|r| doc.node(r).is_some()) else {
+        return Vec::new();
+    };
+    let path = match longest_dag_path(doc, root) {
+        Some(path) => path,
+        None => longest_path_search(doc, root),
+    };
+    path.into_iter().map(str::to_string).collect()
+}
+
+/// Successors of every node reachable from `root`, in edge order, skipping
+/// edges to unknown nodes. Keys are the reachable nodes.
+fn reachable_successors<'a>(
+    doc: &'a TreeDocument,
+    root: &'a str,
+) -> HashMap<&'a str, Vec<&'a str>> {
+    let outgoing = doc.edges_by_source();
+    let mut successors: HashMap<&str, Vec<&str>> = HashMap::from([(root, Vec::new())]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(current) = queue.pop_front() {
+        let targets: Vec<&str> = outgoing
+            .get(current)
+            .into_iter()
+            .flatten()
+            .map(|e| e.target.as_str())
+            .collect();
+        for &target in &targets {
+            if !successors.contains_key(target) {
+                successors.insert(target, Vec::new());
+                queue.push_back(target);
+            }
+        }
+        successors.insert(current, targets);
+    }
+    successors
+}
+
+/// Longest path from `root` by relaxing edges in topological order, or `None`
+/// if a cycle is reachable from `root`.
+pub(crate) fn longest_dag_path<'a>(doc: &'a TreeDocument, root: &'a str) -> Option<Vec<&'a str>> {
+    let successors = reachable_successors(doc, root);
+    let mut in_degree: HashMap<&str, usize> = successors.keys().map(|&id| (id, 0)).collect();
+    for &target in successors.values().flatten() {
+        *in_degree.entry(target).or_default() += 1;
+    }
+
+    // Everything reachable descends from the root, so only the root starts
+    // with no inbound edges unless a cycle leads back into it.
+    let mut distance: HashMap<&str, usize> = HashMap::from([(root, 0)]);
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut ready: VecDeque<&str> = VecDeque::new();
+    if in_degree[root] == 0 {
+        ready.push_back(root);
+    }
+    let mut visited = 0;
+
+    while let Some(current) = ready.pop_front() {
+        visited += 1;
+        let next_distance = distance[current] + 1;
+        for &target in &successors[current] {
+            if distance.get(target).is_none_or(|&d| d < next_distance) {
+                distance.insert(target, next_distance);
+                parent.insert(target, current);
+            }
+            let remaining = in_degree.get_mut(target).expect("every target is reachable");
+            *remaining -= 1;
+            if *remaining == 0 {
+                ready.push_back(target);
+            }
+        }
+    }
+    if visited < successors.len() {
+        return None;
+    }
+
+    // The farthest node, ties broken by document order
+    let mut end = root;
+    for node in &doc.nodes {
+        if distance.get(node.id.as_str()).is_some_and(|&d| d > distance[end]) {
+            end = node.id.as_str();
+        }
+    }
+    let mut path = vec![end];
+    while let Some(&prev) = parent.get(path[path.len() - 1]) {
+        path.push(prev);
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Depth-first search over simple paths from `root`, bounded by
+/// `LONGEST_PATH_SEARCH_LIMIT` steps, keeping the longest path seen.
+fn longest_path_search<'a>(doc: &'a TreeDocument, root: &'a str) -> Vec<&'a str> {
+    let successors = reachable_successors(doc, root);
+
+    // `cursors[i]` is the index of the next successor to try from `path[i]`.
+    let mut path = vec![root];
+    let mut cursors = vec![0];
+    let mut on_path = HashSet::from([root]);
+    let mut longest = path.clone();
+    let mut steps = 0;
+    while steps < LONGEST_PATH_SEARCH_LIMIT {
+        let (Some(&current), Some(next)) = (path.last(), cursors.last_mut()) else {
+            break;
+        };
+        let targets = &successors[current];
+        match targets[*next..].iter().position(|t| !on_path.contains(t)) {
+            Some(offset) => {
+                let target = targets[*next + offset];
+                *next += offset + 1;
+                on_path.insert(target);
+                path.push(target);
+                cursors.push(0);
+                steps += 1;
+                if path.len() > longest.len() {
+                    longest = path.clone();
+                }
+            }
+            None => {
+                on_path.remove(current);
+                path.pop();
+                cursors.pop();
+            }
+        }
+    }
+    longest
+}
+
+/// Count each node's `(in_degree, out_degree)`, keyed by node id.
+///
+/// Every node appears, with `(0, 0)` if no edges touch it. Duplicate edges
+/// are counted each time; edge endpoints that are not nodes are ignored.
+pub fn degrees(doc: &TreeDocument) -> HashMap<String, (usize, usize)> {
+    let mut degrees: HashMap<String, (usize, usize)> =
+        doc.nodes.iter().map(|n| (n.id.clone(), (0, 0))).collect();
+    for edge in &doc.edges {
+        if let Some((_, out_degree)) = degrees.get_mut(&edge.source) {
+            *out_degree += 1;
+        }
+        if let Some((in_degree, _)) = degrees.get_mut(&edge.target) {
+            *in_degree += 1;
+        }
+    }
+    degrees
+}
+
+/// List every node without outgoing edges, in document order.
+///
+/// Unlike the trunk view, which stops at a single terminus, this covers the
+/// ends of every branch. A document with a single node and no edges has that
+/// node as its only ending.
+pub fn endings(doc: &TreeDocument) -> Vec<&Node> {
+    let sources: HashSet<&str> = doc.edges.iter().map(|e| e.source.as_str()).collect();
+    doc.nodes
+        .iter()
+        .filter(|n| !sources.contains(n.id.as_str()))
+        .collect()
+}
+
+/// Nodes whose content contains `needle`, in document order.
+///
+/// With `case_insensitive`, both sides are lowercased before comparing. An
+/// empty `needle` matches every node.
+pub fn find_nodes<'a>(
+    doc: &'a TreeDocument,
+    needle: &str,
+    case_insensitive: bool,
+) -> Vec<&'a Node> {
+    let folded = needle.to_lowercase();
+    doc.nodes
+        .iter()
+        .filter(|n| {
+            if case_insensitive {
+                n.content.to_lowercase().contains(&folded)
+            } else {
+                n.content.contains(needle)
+            }
+        })
+        .collect()
+}
+
+/// Order node ids so every edge points from an earlier node to a later one.
+///
+/// On success returns every node id. If the document has a cycle, returns
+/// the ids of one strongly connected component that blocks the ordering, in
+/// document order. Edges that reference unknown nodes are ignored.
+pub fn topo_order(doc: &TreeDocument) -> Result<Vec<String>, Vec<String>> {
+    let mut graph = DiGraph::<&str, ()>::new();
+    let mut indices: HashMap<&str, NodeIndex> = HashMap::new();
+    for node in &doc.nodes {
+        indices
+            .entry(node.id.as_str())
+            .or_insert_with(|| graph.add_node(node.id.as_str()));
+    }
+    for edge in &doc.edges {
+        if let (Some(&a), Some(&b)) = (
+            indices.get(edge.source.as_str()),
+            indices.get(edge.target.as_str()),
+        ) {
+            graph.add_edge(a, b, ());
+        }
+    }
+
+    match toposort(&graph, None) {
+        Ok(order) => Ok(order.into_iter().map(|i| graph[i].to_string()).collect()),
+        Err(cycle) => {
+            let blocking = kosaraju_scc(&graph)
+                .into_iter()
+                .find(|scc| scc.contains(&cycle.node_id()))
+                .unwrap_or_default();
+            let mut ids: Vec<_> = blocking.into_iter().collect();
+            ids.sort_unstable();
+            Err(ids.into_iter().map(|i| graph[i].to_string()).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse;
+
+    fn story() -> TreeDocument {
+        parse(include_str!("../../../examples/story.tree.json")).unwrap()
+    }
+
+    #[test]
+    fn direct_trunk_path() {
+        let doc = story();
+        let path = shortest_path(&doc, "start", "wish").unwrap();
+        assert_eq!(path, vec!["start", "enter", "fountain", "wish"]);
+    }
+
+    #[test]
+    fn path_through_branch_edge() {
+        let doc = story();
+        let path = shortest_path(&doc, "start", "explore").unwrap();
+        assert_eq!(path, vec!["start", "enter", "explore"]);
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let doc = story();
+        assert_eq!(shortest_path(&doc, "ending", "start"), None);
+    }
+
+    #[test]
+    fn missing_endpoint() {
+        let doc = story();
+        assert_eq!(shortest_path(&doc, "start", "nowhere"), None);
+        assert_eq!(shortest_path(&doc, "nowhere", "start"), None);
+    }
+
+    fn sorted(set: HashSet<String>) -> Vec<String> {
+        let mut ids: Vec<_> = set.into_iter().collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn reachable_from_root_and_mid_trunk() {
+        let doc = story();
+
+        let from_root = reachable_from(&doc, "start").unwrap();
+        assert_eq!(from_root.len(), doc.nodes.len());
+
+        let from_fountain = reachable_from(&doc, "fountain").unwrap();
+        assert_eq!(sorted(from_fountain.clone()), ["ending", "fountain", "wish"]);
+        assert!(from_fountain.is_subset(&from_root));
+        assert!(!from_fountain.contains("explore"));
+    }
+
+    #[test]
+    fn reachable_from_missing_node() {
+        let err = reachable_from(&story(), "nowhere").unwrap_err();
+        assert_eq!(err, "Node 'nowhere' not found in nodes array");
+    }
+
+    #[test]
+    fn all_story_paths() {
+        let paths = all_paths(&story(), 100);
+        assert_eq!(
+            paths,
+            [
+                vec!["start", "enter", "fountain", "wish", "ending"],
+                vec!["start", "enter", "explore", "ending"],
+                vec!["start", "climb"],
+            ]
+        );
+        assert_eq!(all_paths(&story(), 2).len(), 2);
+        assert!(all_paths(&story(), 0).is_empty());
+    }
+
+    #[test]
+    fn all_paths_cuts_cycles() {
+        let doc = parse(include_str!("../../../examples/invalid/general-cycle.tree.json")).unwrap();
+        assert_eq!(all_paths(&doc, 100), [vec!["n1", "n2", "n5"]]);
+    }
+
+    #[test]
+    fn all_paths_skips_cycles_that_never_end() {
+        // Every node in n1..n11 links to every other, and only the root
+        // reaches an ending; the clique must not be searched.
+        let ids: Vec<String> = (1..=11).map(|i| format!("n{i}")).collect();
+        let mut nodes: Vec<String> = ids
+            .iter()
+            .map(|id| format!(r#"{{"id": "{id}", "content": "{id}"}}"#))
+            .collect();
+        nodes.push(r#"{"id": "end", "content": "End"}"#.to_string());
+        let mut edges = vec![r#"{"source": "n1", "target": "end", "isTrunk": true}"#.to_string()];
+        for source in &ids {
+            for target in ids.iter().filter(|t| *t != source) {
+                edges.push(format!(r#"{{"source": "{source}", "target": "{target}"}}"#));
+            }
+        }
+        let json = format!(
+            r#"{{"formatVersion": "1.0", "rootNodeId": "n1", "nodes": [{}], "edges": [{}]}}"#,
+            nodes.join(","),
+            edges.join(",")
+        );
+        let doc = parse(&json).unwrap();
+        assert_eq!(all_paths(&doc, 100), [vec!["n1", "end"]]);
+    }
+
+    #[test]
+    fn longest_path_in_dag() {
+        // The trunk is two steps; the detour through b, c, and d is longer
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"},
+                {"id": "c", "content": "C"},
+                {"id": "d", "content": "D"},
+                {"id": "e", "content": "E"},
+                {"id": "end", "content": "End"}
+            ],
+            "edges": [
+                {"source": "a", "target": "e", "isTrunk": true},
+                {"source": "e", "target": "end", "isTrunk": true},
+                {"source": "a", "target": "b"},
+                {"source": "b", "target": "c"},
+                {"source": "b", "target": "d"},
+                {"source": "c", "target": "d"},
+                {"source": "d", "target": "end"}
+            ]
+        }"#;
+        let doc = parse(json).unwrap();
+        assert_eq!(longest_path(&doc), ["a", "b", "c", "d", "end"]);
+        assert_eq!(
+            longest_path(&story()),
+            ["start", "enter", "fountain", "wish", "ending"]
+        );
+    }
+
+    #[test]
+    fn longest_path_with_cycle() {
+        let doc = parse(include_str!("../../../examples/invalid/general-cycle.tree.json")).unwrap();
+        assert_eq!(longest_path(&doc), ["n1", "n2", "n3", "n4"]);
+    }
+
+    #[test]
+    fn degrees_of_hub_and_isolated_nodes() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "hub",
+            "nodes": [
+                {"id": "hub", "content": "Hub"},
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"},
+                {"id": "c", "content": "C"},
+                {"id": "alone", "content": "Alone"}
+            ],
+            "edges": [
+                {"source": "hub", "target": "a", "isTrunk": true},
+                {"source": "hub", "target": "b"},
+                {"source": "hub", "target": "c"},
+                {"source": "hub", "target": "c"},
+                {"source": "a", "target": "hub"},
+                {"source": "c", "target": "missing"}
+            ]
+        }"#;
+        let degrees = degrees(&parse(json).unwrap());
+        assert_eq!(degrees.len(), 5);
+        assert_eq!(degrees["hub"], (1, 4));
+        assert_eq!(degrees["a"], (1, 1));
+        assert_eq!(degrees["b"], (1, 0));
+        assert_eq!(degrees["c"], (2, 1));
+        assert_eq!(degrees["alone"], (0, 0));
+    }
+
+    #[test]
+    fn story_endings() {
+        let doc = story();
+        let ids: Vec<_> = endings(&doc).iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["climb", "ending"]);
+    }
+
+    #[test]
+    fn single_node_is_its_own_ending() {
+        let doc = parse(include_str!("../../../examples/empty-document.tree.json")).unwrap();
+        let ids: Vec<_> = endings(&doc).iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["n1"]);
+    }
+
+    fn found_ids<'a>(doc: &'a TreeDocument, needle: &str, case_insensitive: bool) -> Vec<&'a str> {
+        find_nodes(doc, needle, case_insensitive)
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn find_nodes_case_sensitive() {
+        let doc = story();
+        assert_eq!(found_ids(&doc, "fountain", false), ["enter", "fountain", "wish"]);
+        assert!(found_ids(&doc, "FOUNTAIN", false).is_empty());
+        assert_eq!(found_ids(&doc, "", false).len(), doc.nodes.len());
+    }
+
+    #[test]
+    fn find_nodes_case_insensitive() {
+        let doc = story();
+        assert_eq!(found_ids(&doc, "FOUNTAIN", true), ["enter", "fountain", "wish"]);
+        assert_eq!(found_ids(&doc, "you", true).len(), doc.nodes.len());
+        assert_eq!(found_ids(&doc, "you", false), ["climb", "explore", "ending"]);
+    }
+
+    #[test]
+    fn topo_order_of_linear_document() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "c", "content": "C"},
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "b", "target": "c", "isTrunk": true}
+            ]
+        }"#;
+        let doc = parse(json).unwrap();
+        assert_eq!(topo_order(&doc).unwrap(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn topo_order_respects_every_edge() {
+        let doc = story();
+        let order = topo_order(&doc).unwrap();
+        assert_eq!(order.len(), doc.nodes.len());
+        let position = |id: &str| order.iter().position(|o| o == id).unwrap();
+        for edge in &doc.edges {
+            assert!(position(&edge.source) < position(&edge.target));
+        }
+    }
+
+    #[test]
+    fn topo_order_reports_cycle() {
+        let doc = parse(include_str!("../../../examples/invalid/general-cycle.tree.json")).unwrap();
+        assert_eq!(topo_order(&doc).unwrap_err(), ["n2", "n3", "n4"]);
+    }
+
+    #[test]
+    fn same_node() {
+        let doc = story();
+        assert_eq!(
+            shortest_path(&doc, "enter", "enter").unwrap(),
+            vec!["enter"]
+        );
+    }
+}
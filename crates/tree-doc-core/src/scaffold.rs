@@ -0,0 +1,71 @@
+use serde_json::json;
+
+use crate::types::TreeDocument;
+
+/// A minimal document for `tier` (0, 1, or 2) to start writing from: a start
+/// node joined to an ending by one labeled trunk edge.
+///
+/// Tier 1 adds `minReaderVersion`, `features`, and a `metadata` title; tier 2
+/// also puts everything in a single tree named `main`. Every scaffold
+/// validates with no diagnostics. Returns `None` for any other tier.
+pub fn scaffold(tier: u8) -> Option<TreeDocument> {
+    if tier > 2 {
+        return None;
+    }
+
+    let mut value = json!({
+        "formatVersion": "1.0",
+        "rootNodeId": "start",
+        "nodes": [
+            {"id": "start", "content": "The story begins here."},
+            {"id": "end", "content": "The story ends here.", "status": "ending"}
+        ],
+        "edges": [
+            {"source": "start", "target": "end", "isTrunk": true, "label": "Continue"}
+        ]
+    });
+
+    if tier >= 1 {
+        value["minReaderVersion"] = json!("1.0");
+        value["features"] = json!(["labels", "status"]);
+        value["metadata"] = json!({"title": "Untitled Story"});
+    }
+    if tier >= 2 {
+        value["features"] = json!(["labels", "status", "trees"]);
+        value["trees"] = json!({"main": {"rootNodeId": "start", "label": "Main"}});
+        for node in value["nodes"].as_array_mut().into_iter().flatten() {
+            node["treeIds"] = json!(["main"]);
+        }
+        for edge in value["edges"].as_array_mut().into_iter().flatten() {
+            edge["treeId"] = json!("main");
+        }
+    }
+    Some(serde_json::from_value(value).expect("scaffold matches TreeDocument"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::detect_tier;
+    use crate::validate::validate_document;
+
+    #[test]
+    fn each_tier_scaffold_validates_cleanly() {
+        for tier in 0..=2 {
+            let json = scaffold(tier).unwrap().to_canonical_json();
+            let result = validate_document(&json).unwrap();
+            assert!(result.is_valid, "tier {tier}: {:?}", result.errors);
+            assert!(result.warnings.is_empty(), "tier {tier}: {:?}", result.warnings);
+            assert!(result.advisories.is_empty(), "tier {tier}: {:?}", result.advisories);
+            assert_eq!(result.stats.tier, tier);
+
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(detect_tier(&value), tier);
+        }
+    }
+
+    #[test]
+    fn unknown_tier_has_no_scaffold() {
+        assert!(scaffold(3).is_none());
+    }
+}
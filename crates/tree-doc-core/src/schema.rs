@@ -2,6 +2,7 @@ use crate::error::{Diagnostic, Location, Rule, Severity};
 
 static TIER0_SCHEMA_STR: &str = include_str!("../../../schemas/tier0.schema.json");
 static TIER1_SCHEMA_STR: &str = include_str!("../../../schemas/tier1.schema.json");
+static TIER2_SCHEMA_STR: &str = include_str!("../../../schemas/tier2.schema.json");
 
 use std::sync::OnceLock;
 
@@ -23,20 +24,66 @@ fn tier1_schema() -> &'static jsonschema::Validator {
     })
 }
 
+fn tier2_schema() -> &'static jsonschema::Validator {
+    static VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+    VALIDATOR.get_or_init(|| {
+        let schema: serde_json::Value = serde_json::from_str(TIER2_SCHEMA_STR)
+            .expect("embedded tier2 schema is valid JSON");
+        jsonschema::validator_for(&schema).expect("embedded tier2 schema is valid")
+    })
+}
+
+/// Validate against the schema for the document's detected tier.
 pub fn validate_schema(value: &serde_json::Value) -> Vec<Diagnostic> {
-    let validator = tier0_schema();
+    let validator = match detect_tier(value) {
+        2 => tier2_schema(),
+        1 => tier1_schema(),
+        _ => tier0_schema(),
+    };
     let mut diagnostics = Vec::new();
+    push_schema_errors(validator, value, &mut diagnostics);
+    diagnostics
+}
 
+/// Validate against the tier schema and then against `extra`, such as an
+/// organization's own schema. Diagnostics from both are returned in that
+/// order.
+pub fn validate_schema_with(
+    value: &serde_json::Value,
+    extra: &jsonschema::Validator,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = validate_schema(value);
+    push_schema_errors(extra, value, &mut diagnostics);
+    diagnostics
+}
+
+/// Parse and compile a JSON Schema for use with [`validate_schema_with`].
+pub fn compile_schema(schema_json: &str) -> Result<jsonschema::Validator, String> {
+    let schema: serde_json::Value =
+        serde_json::from_str(schema_json).map_err(|e| format!("invalid JSON: {e}"))?;
+    jsonschema::validator_for(&schema).map_err(|e| format!("invalid schema: {e}"))
+}
+
+fn push_schema_errors(
+    validator: &jsonschema::Validator,
+    value: &serde_json::Value,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
     for error in validator.iter_errors(value) {
+        let pointer = error.instance_path.to_string();
+        let location = if pointer.is_empty() {
+            Location::Root
+        } else {
+            Location::Pointer(pointer)
+        };
         diagnostics.push(Diagnostic {
             rule: Rule::SchemaValidation,
             message: format!("{error}"),
-            location: Location::Root,
+            location,
             severity: Severity::Error,
+            span: None,
         });
     }
-
-    diagnostics
 }
 
 pub fn detect_tier(value: &serde_json::Value) -> u8 {
@@ -47,7 +94,6 @@ pub fn detect_tier(value: &serde_json::Value) -> u8 {
         || value.get("features").is_some()
         || value.get("metadata").is_some();
     if has_tier1_fields {
-        let _ = tier1_schema();
         return 1;
     }
     0
@@ -82,6 +128,58 @@ mod tests {
         assert!(diags.iter().all(|d| d.severity == Severity::Error));
     }
 
+    #[test]
+    fn schema_errors_point_at_the_bad_field() {
+        let value = serde_json::json!({
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [{"id": 5, "content": "Numeric id"}],
+            "edges": []
+        });
+        let diags = validate_schema(&value);
+        assert_eq!(diags.len(), 1, "{diags:?}");
+        assert_eq!(diags[0].location, Location::Pointer("/nodes/0/id".to_string()));
+        assert!(diags[0].to_string().ends_with("(at /nodes/0/id)"));
+    }
+
+    #[test]
+    fn root_level_schema_errors_stay_at_root() {
+        let value = serde_json::json!({"formatVersion": "1.0", "nodes": []});
+        let diags = validate_schema(&value);
+        assert!(!diags.is_empty());
+        assert!(diags.iter().all(|d| d.location == Location::Root));
+    }
+
+    const REQUIRE_AUTHOR: &str = r#"{
+        "type": "object",
+        "required": ["metadata"],
+        "properties": {
+            "metadata": {"type": "object", "required": ["author"]}
+        }
+    }"#;
+
+    #[test]
+    fn custom_schema_adds_its_errors() {
+        let extra = compile_schema(REQUIRE_AUTHOR).unwrap();
+
+        let json = include_str!("../../../examples/story.tree.json");
+        let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert!(validate_schema_with(&value, &extra).is_empty());
+
+        value["metadata"].as_object_mut().unwrap().remove("author");
+        let diags = validate_schema_with(&value, &extra);
+        assert_eq!(diags.len(), 1, "{diags:?}");
+        assert!(diags[0].message.contains("\"author\" is a required property"));
+        assert_eq!(diags[0].location, Location::Pointer("/metadata".to_string()));
+    }
+
+    #[test]
+    fn compile_schema_rejects_bad_schemas() {
+        assert!(compile_schema("{not json").unwrap_err().starts_with("invalid JSON"));
+        let err = compile_schema(r#"{"type": "no-such-type"}"#).unwrap_err();
+        assert!(err.starts_with("invalid schema"), "{err}");
+    }
+
     #[test]
     fn detect_tier0() {
         let json = include_str!("../../../examples/minimal.tree.json");
@@ -96,6 +194,62 @@ mod tests {
         assert_eq!(detect_tier(&value), 1);
     }
 
+    #[test]
+    fn detect_tier2() {
+        let json = include_str!("../../../examples/multi-tree.tree.json");
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(detect_tier(&value), 2);
+    }
+
+    #[test]
+    fn valid_multi_tree_passes_schema() {
+        let json = include_str!("../../../examples/multi-tree.tree.json");
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let diags = validate_schema(&value);
+        assert!(diags.is_empty(), "expected no errors, got: {diags:?}");
+    }
+
+    #[test]
+    fn tier2_tree_without_root_fails_schema() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "nodes": [{"id": "n1", "content": "hello"}],
+            "edges": [],
+            "trees": {"main": {"label": "No root"}}
+        }"#;
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let diags = validate_schema(&value);
+        assert!(!diags.is_empty());
+    }
+
+    #[test]
+    fn tier2_embedding_ref_requires_format() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "nodes": [{"id": "n1", "content": "hello"}],
+            "edges": [],
+            "trees": {"main": {"rootNodeId": "n1"}},
+            "embeddingRef": {"path": "embeddings.npy"}
+        }"#;
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let diags = validate_schema(&value);
+        assert!(!diags.is_empty());
+    }
+
+    #[test]
+    fn tier1_metadata_types_enforced() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "metadata": {"title": 42},
+            "nodes": [{"id": "n1", "content": "hello"}],
+            "edges": []
+        }"#;
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let diags = validate_schema(&value);
+        assert!(!diags.is_empty());
+    }
+
     #[test]
     fn extra_fields_pass_schema() {
         let json = r#"{
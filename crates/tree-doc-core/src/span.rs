@@ -0,0 +1,252 @@
+use std::collections::HashMap;
+
+use crate::error::{Diagnostic, Location, Rule, ValidationResult};
+
+/// Byte ranges of the elements of the top-level `nodes` and `edges` arrays,
+/// in array order.
+#[derive(Debug, Default)]
+struct ElementSpans {
+    nodes: Vec<(usize, usize)>,
+    edges: Vec<(usize, usize)>,
+}
+
+/// Fill in `span` on each diagnostic in `result` that points at a node or an
+/// edge, using the JSON text `source` the result was produced from.
+///
+/// A span is the byte range `start..end` of the whole node or edge object in
+/// `source`. `Node` locations map to the first node with that id, except that
+/// repeated `duplicate-node-id` diagnostics map to the second, third, ...
+/// occurrence in turn. `Edge` locations map by index, and JSON Pointers by the
+/// node or edge they point into. Root and path locations get no span. This is
+/// best effort: if `source` is not the text that was validated, spans may be
+/// wrong or missing.
+pub fn attach_spans(result: &mut ValidationResult, source: &str) {
+    let spans = element_spans(source);
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(source) else {
+        return;
+    };
+
+    // Node id -> positions of every node with that id, in array order
+    let mut node_positions: HashMap<&str, Vec<usize>> = HashMap::new();
+    if let Some(nodes) = value.get("nodes").and_then(|n| n.as_array()) {
+        for (i, node) in nodes.iter().enumerate() {
+            if let Some(id) = node.get("id").and_then(|id| id.as_str()) {
+                node_positions.entry(id).or_default().push(i);
+            }
+        }
+    }
+
+    let mut duplicates_seen: HashMap<String, usize> = HashMap::new();
+    let diagnostics = result
+        .errors
+        .iter_mut()
+        .chain(&mut result.warnings)
+        .chain(&mut result.advisories);
+    for diag in diagnostics {
+        let occurrence = match (&diag.rule, &diag.location) {
+            (Rule::DuplicateNodeId, Location::Node(id)) => {
+                let seen = duplicates_seen.entry(id.clone()).or_default();
+                *seen += 1;
+                *seen
+            }
+            _ => 0,
+        };
+        diag.span = span_of(diag, occurrence, &node_positions, &spans);
+    }
+}
+
+fn span_of(
+    diag: &Diagnostic,
+    occurrence: usize,
+    node_positions: &HashMap<&str, Vec<usize>>,
+    spans: &ElementSpans,
+) -> Option<(usize, usize)> {
+    match &diag.location {
+        Location::Node(id) => {
+            let index = node_positions.get(id.as_str())?.get(occurrence)?;
+            spans.nodes.get(*index).copied()
+        }
+        Location::Edge { index, .. } => spans.edges.get(*index).copied(),
+        Location::Pointer(pointer) => {
+            let mut parts = pointer.split('/').skip(1);
+            let array = match parts.next()? {
+                "nodes" => &spans.nodes,
+                "edges" => &spans.edges,
+                _ => return None,
+            };
+            let index: usize = parts.next()?.parse().ok()?;
+            array.get(index).copied()
+        }
+        Location::Root | Location::Path(_) => None,
+    }
+}
+
+/// The 1-based line and column of byte `offset` in `source`, counting columns
+/// in characters. Offsets past the end map to the end of the text.
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// Find each element of the top-level `nodes` and `edges` arrays with a
+/// single pass that tracks nesting depth and skips over strings.
+fn element_spans(source: &str) -> ElementSpans {
+    let mut spans = ElementSpans::default();
+    let bytes = source.as_bytes();
+
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string_start = 0;
+    // The last string seen directly inside the top-level object, and the key
+    // it became once followed by `:`
+    let mut last_string = (0, 0);
+    let mut key: &str = "";
+    // Which array is being scanned, the element start, and where the last
+    // non-whitespace byte ended
+    let mut target: Option<&mut Vec<(usize, usize)>> = None;
+    let mut element_start: Option<usize> = None;
+    let mut last_end = 0;
+
+    for (i, &b) in bytes.iter().enumerate() {
+        if in_string {
+            last_end = i + 1;
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+                if depth == 1 {
+                    last_string = (string_start + 1, i);
+                }
+            }
+            continue;
+        }
+        if b.is_ascii_whitespace() {
+            continue;
+        }
+
+        let in_array = target.is_some() && depth == 2;
+        if in_array && element_start.is_none() && b != b',' && b != b']' {
+            element_start = Some(i);
+        }
+
+        match b {
+            b'"' => {
+                in_string = true;
+                string_start = i;
+            }
+            b':' if depth == 1 => key = &source[last_string.0..last_string.1],
+            b'[' | b'{' => {
+                if b == b'[' && depth == 1 {
+                    target = match key {
+                        "nodes" => Some(&mut spans.nodes),
+                        "edges" => Some(&mut spans.edges),
+                        _ => None,
+                    };
+                }
+                depth += 1;
+            }
+            b',' | b']' | b'}' => {
+                if in_array && b != b'}' {
+                    if let (Some(start), Some(array)) = (element_start.take(), target.as_mut()) {
+                        array.push((start, last_end));
+                    }
+                    if b == b']' {
+                        target = None;
+                    }
+                }
+                if b != b',' {
+                    depth -= 1;
+                }
+            }
+            _ => {}
+        }
+        last_end = i + 1;
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::validate_document_with_spans;
+
+    const JSON: &str = r#"{
+  "formatVersion": "1.0",
+  "rootNodeId": "n1",
+  "nodes": [
+    { "id": "n1", "content": "Start, with \"quotes\" and [brackets]" },
+    { "id": "n2", "content": "End" },
+    { "id": "n2", "content": "Again" }
+  ],
+  "edges": [
+    { "source": "n1", "target": "n2", "isTrunk": true },
+    { "source": "n1", "target": "n9" }
+  ]
+}"#;
+
+    fn spanned(rule: Rule, result: &ValidationResult) -> Vec<&str> {
+        result
+            .errors
+            .iter()
+            .chain(&result.warnings)
+            .chain(&result.advisories)
+            .filter(|d| d.rule == rule)
+            .map(|d| {
+                let (start, end) = d.span.expect("diagnostic has a span");
+                &JSON[start..end]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_array_elements() {
+        let spans = element_spans(JSON);
+        assert_eq!(spans.nodes.len(), 3);
+        assert_eq!(spans.edges.len(), 2);
+        let (start, end) = spans.nodes[1];
+        assert_eq!(&JSON[start..end], r#"{ "id": "n2", "content": "End" }"#);
+    }
+
+    #[test]
+    fn node_span_contains_its_id() {
+        let result = validate_document_with_spans(JSON).unwrap();
+        assert_eq!(
+            spanned(Rule::DuplicateNodeId, &result),
+            [r#"{ "id": "n2", "content": "Again" }"#]
+        );
+        let dead_ends = spanned(Rule::DeadEndNode, &result);
+        assert!(dead_ends.iter().all(|s| s.contains(r#""id": "n2""#)), "{dead_ends:?}");
+    }
+
+    #[test]
+    fn edge_span_is_the_edge_object() {
+        let result = validate_document_with_spans(JSON).unwrap();
+        assert_eq!(
+            spanned(Rule::DanglingEdge, &result),
+            [r#"{ "source": "n1", "target": "n9" }"#]
+        );
+    }
+
+    #[test]
+    fn spans_are_opt_in() {
+        let result = crate::validate::validate_document(JSON).unwrap();
+        assert!(result.errors.iter().all(|d| d.span.is_none()));
+    }
+
+    #[test]
+    fn line_and_column() {
+        let (start, _) = element_spans(JSON).edges[1];
+        assert_eq!(line_column(JSON, start), (11, 5));
+        assert_eq!(line_column(JSON, 0), (1, 1));
+        assert_eq!(line_column("é\nx", 3), (2, 1));
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::Serialize;
+
+use crate::query::{endings, longest_dag_path};
+use crate::types::TreeDocument;
+use crate::validate::compute_component_count;
+use crate::viewer::trunk_path;
+
+/// Shape metrics beyond the counts in `DocumentStats`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendedStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Edges per node; the average branching factor.
+    pub average_out_degree: f64,
+    /// Greatest number of edges from the root to any node on its shortest
+    /// path, or `None` without a valid root.
+    pub max_depth: Option<usize>,
+    /// Edge count of the longest path from the root, or `None` without a
+    /// valid root or when a cycle is reachable from it.
+    pub longest_path: Option<usize>,
+    pub ending_count: usize,
+    pub component_count: usize,
+    /// Fraction of nodes on the trunk, from 0.0 to 1.0.
+    pub trunk_coverage: f64,
+}
+
+/// Fraction of the document's nodes visited by the trunk walk from the root,
+/// or 0.0 for a document without nodes.
+pub fn trunk_coverage(doc: &TreeDocument) -> f64 {
+    if doc.nodes.is_empty() {
+        return 0.0;
+    }
+    let node_ids: HashSet<&str> = doc.nodes.iter().map(|n| n.id.as_str()).collect();
+    let trunk_nodes = trunk_path(doc)
+        .into_iter()
+        .filter(|id| node_ids.contains(id))
+        .count();
+    trunk_nodes as f64 / doc.nodes.len() as f64
+}
+
+/// Compute `ExtendedStats` for a parsed document. Edges that reference
+/// unknown nodes are ignored when walking from the root.
+pub fn compute_stats(doc: &TreeDocument) -> ExtendedStats {
+    let node_count = doc.nodes.len();
+    let edge_count = doc.edges.len();
+    let average_out_degree = if node_count == 0 {
+        0.0
+    } else {
+        edge_count as f64 / node_count as f64
+    };
+
+    let root = doc
+        .root_node_id
+        .as_deref()
+        .filter(|id| doc.node(id).is_some());
+
+    ExtendedStats {
+        node_count,
+        edge_count,
+        average_out_degree,
+        max_depth: root.map(|root| max_depth(doc, root)),
+        longest_path: root.and_then(|root| longest_dag_path(doc, root)).map(|p| p.len() - 1),
+        ending_count: endings(doc).len(),
+        component_count: compute_component_count(doc),
+        trunk_coverage: trunk_coverage(doc),
+    }
+}
+
+fn max_depth(doc: &TreeDocument, root: &str) -> usize {
+    let outgoing = doc.edges_by_source();
+    let mut depth = HashMap::from([(root, 0)]);
+    let mut queue = VecDeque::from([root]);
+    let mut deepest = 0;
+
+    while let Some(current) = queue.pop_front() {
+        let next_depth = depth[current] + 1;
+        for edge in outgoing.get(current).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if !depth.contains_key(next) {
+                depth.insert(next, next_depth);
+                deepest = next_depth;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    deepest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse;
+
+    #[test]
+    fn story_stats() {
+        let doc = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        let stats = compute_stats(&doc);
+
+        assert_eq!(stats.node_count, 7);
+        assert_eq!(stats.edge_count, 7);
+        assert_eq!(stats.average_out_degree, 1.0);
+        // `ending` is three steps away through `explore`...
+        assert_eq!(stats.max_depth, Some(3));
+        // ...but four along the trunk.
+        assert_eq!(stats.longest_path, Some(4));
+        assert_eq!(stats.ending_count, 2);
+        assert_eq!(stats.component_count, 1);
+        // start, enter, fountain, wish, and ending out of seven nodes
+        assert_eq!(stats.trunk_coverage, 5.0 / 7.0);
+    }
+
+    #[test]
+    fn single_node_document() {
+        let doc = parse(include_str!("../../../examples/empty-document.tree.json")).unwrap();
+        let stats = compute_stats(&doc);
+
+        assert_eq!(stats.max_depth, Some(0));
+        assert_eq!(stats.longest_path, Some(0));
+        assert_eq!(stats.ending_count, 1);
+        assert_eq!(stats.trunk_coverage, 1.0);
+    }
+
+    #[test]
+    fn no_nodes_has_zero_coverage() {
+        let doc = parse(r#"{"formatVersion": "1.0", "nodes": [], "edges": []}"#).unwrap();
+        assert_eq!(trunk_coverage(&doc), 0.0);
+    }
+
+    #[test]
+    fn cycle_has_no_longest_path() {
+        let doc = parse(include_str!(
+            "../../../examples/invalid/general-cycle.tree.json"
+        ))
+        .unwrap();
+        let stats = compute_stats(&doc);
+
+        assert!(stats.max_depth.is_some());
+        assert_eq!(stats.longest_path, None);
+    }
+}
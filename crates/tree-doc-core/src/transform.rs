@@ -0,0 +1,523 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use thiserror::Error;
+
+use crate::types::{Edge, EdgeType, TreeDocument};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MergeError {
+    #[error("node id '{0}' exists in both documents")]
+    IdConflict(String),
+    #[error("tree id '{0}' exists in both documents")]
+    TreeConflict(String),
+}
+
+/// A copy of `doc` keeping trunk edges and the other edges whose `type` or
+/// `linkType` is `edge_type`; untyped branch edges are dropped.
+///
+/// Nodes are kept even if no remaining edge reaches them, so exporters still
+/// show every node.
+pub fn filter_edge_type(doc: &TreeDocument, edge_type: &EdgeType) -> TreeDocument {
+    let mut filtered = doc.clone();
+    filtered
+        .edges
+        .retain(|e| e.is_trunk == Some(true) || e.has_type(edge_type));
+    filtered
+}
+
+/// Extract everything reachable from `root` into a new document rooted there.
+///
+/// Nodes keep their document order, and only edges between retained nodes
+/// are kept. Tier 1 fields and `embeddingRef` are carried over unchanged.
+/// Trees whose root was not retained are dropped, along with any `treeIds`
+/// and `treeId` references to them, so the result does not point at
+/// undefined trees.
+pub fn extract_subtree(doc: &TreeDocument, root: &str) -> Result<TreeDocument, String> {
+    if doc.node(root).is_none() {
+        return Err(format!("Node '{root}' not found in nodes array"));
+    }
+
+    let outgoing = doc.edges_by_source();
+    let mut reachable = HashSet::from([root]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(current) = queue.pop_front() {
+        for edge in outgoing.get(current).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if reachable.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let trees = doc.trees.as_ref().map(|trees| {
+        trees
+            .iter()
+            .filter(|(_, tree)| reachable.contains(tree.root_node_id.as_str()))
+            .map(|(id, tree)| (id.clone(), tree.clone()))
+            .collect::<BTreeMap<_, _>>()
+    });
+    let tree_kept = |id: &String| trees.as_ref().is_some_and(|t| t.contains_key(id));
+
+    let nodes = doc
+        .nodes
+        .iter()
+        .filter(|n| reachable.contains(n.id.as_str()))
+        .cloned()
+        .map(|mut node| {
+            if let Some(ids) = &mut node.tree_ids {
+                ids.retain(|id| tree_kept(id));
+                if ids.is_empty() {
+                    node.tree_ids = None;
+                }
+            }
+            node
+        })
+        .collect();
+
+    let edges = doc
+        .edges
+        .iter()
+        .filter(|e| reachable.contains(e.source.as_str()) && reachable.contains(e.target.as_str()))
+        .cloned()
+        .map(|mut edge| {
+            if edge.tree_id.as_ref().is_some_and(|id| !tree_kept(id)) {
+                edge.tree_id = None;
+            }
+            edge
+        })
+        .collect();
+
+    Ok(TreeDocument {
+        format_version: doc.format_version.clone(),
+        root_node_id: Some(root.to_string()),
+        nodes,
+        edges,
+        min_reader_version: doc.min_reader_version.clone(),
+        features: doc.features.clone(),
+        metadata: doc.metadata.clone(),
+        trees,
+        embedding_ref: doc.embedding_ref.clone(),
+        extra: doc.extra.clone(),
+    })
+}
+
+/// A copy of `doc` without the nodes the `orphan-node` rule reports: those
+/// unreachable from the root. Edges touching a removed node go too, as do
+/// dangling edges and trees rooted at a removed node.
+///
+/// Unlike `autofix`, this leaves the input alone. A document without a valid
+/// root is returned unchanged.
+pub fn prune_orphans(doc: &TreeDocument) -> TreeDocument {
+    match doc.root_node_id.as_deref() {
+        Some(root) if doc.node(root).is_some() => {
+            extract_subtree(doc, root).expect("the root is a node")
+        }
+        _ => doc.clone(),
+    }
+}
+
+/// A copy of `doc` with nodes sorted by id and edges by `(source, target)`,
+/// so reordering alone never shows up in a diff. The sort is stable: edges
+/// with the same endpoints keep their relative order. Nothing else changes.
+///
+/// Viewers list branches in edge order, so the order branches are shown in
+/// may change.
+pub fn canonicalize(doc: &TreeDocument) -> TreeDocument {
+    let mut sorted = doc.clone();
+    sorted.nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    sorted
+        .edges
+        .sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+    sorted
+}
+
+/// Combine two documents into one, appending `b`'s nodes and edges after
+/// `a`'s.
+///
+/// With `prefix_b`, every node and tree id from `b` (including edge endpoints
+/// and tree references) is prefixed before merging. Ids that still collide
+/// with `a` are rejected rather than silently merged. The result takes
+/// `rootNodeId` and all other document-level fields from `a`, except that
+/// `features` and `trees` are the union of both documents. Edges from `b`
+/// whose [`Edge::dedup_key`] is already present are dropped.
+pub fn merge(
+    a: &TreeDocument,
+    b: &TreeDocument,
+    prefix_b: Option<&str>,
+) -> Result<TreeDocument, MergeError> {
+    let rename = |id: &str| format!("{}{id}", prefix_b.unwrap_or(""));
+
+    let a_ids: HashSet<&str> = a.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut merged = a.clone();
+
+    for node in &b.nodes {
+        let mut node = node.clone();
+        node.id = rename(&node.id);
+        if a_ids.contains(node.id.as_str()) {
+            return Err(MergeError::IdConflict(node.id));
+        }
+        if let Some(ids) = &mut node.tree_ids {
+            for id in ids.iter_mut() {
+                *id = rename(id);
+            }
+        }
+        merged.nodes.push(node);
+    }
+
+    let mut edge_keys: HashSet<_> = merged.edges.iter().map(Edge::dedup_key).collect();
+    for edge in &b.edges {
+        let mut edge = edge.clone();
+        edge.source = rename(&edge.source);
+        edge.target = rename(&edge.target);
+        edge.tree_id = edge.tree_id.as_deref().map(rename);
+        if edge_keys.insert(edge.dedup_key()) {
+            merged.edges.push(edge);
+        }
+    }
+
+    if let Some(b_trees) = &b.trees {
+        let trees = merged.trees.get_or_insert_with(BTreeMap::new);
+        for (id, tree) in b_trees {
+            let id = rename(id);
+            if trees.contains_key(&id) {
+                return Err(MergeError::TreeConflict(id));
+            }
+            let mut tree = tree.clone();
+            tree.root_node_id = rename(&tree.root_node_id);
+            trees.insert(id, tree);
+        }
+    }
+
+    if let Some(b_features) = &b.features {
+        let features = merged.features.get_or_insert_with(Vec::new);
+        for feature in b_features {
+            if !features.contains(feature) {
+                features.push(feature.clone());
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Rename every node to `prefix` followed by its position in a breadth-first
+/// walk from the root, so `n0` is the root when `prefix` is `n`.
+///
+/// The walk follows outgoing edges in document order. Nodes it does not
+/// reach are numbered after it, in document order. Edge endpoints,
+/// `rootNodeId`, tree roots, and `metadata.beginEndMapping` are rewritten to
+/// match, while references to ids that are not nodes are left as they are.
+/// Nodes keep their position in `nodes`. Returns the new document and the
+/// old-to-new id mapping, which has an entry for every node id.
+pub fn normalize_ids(doc: &TreeDocument, prefix: &str) -> (TreeDocument, HashMap<String, String>) {
+    let outgoing = doc.edges_by_source();
+    let mut order: Vec<&str> = Vec::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut queue: VecDeque<&str> = doc
+        .root_node_id
+        .as_deref()
+        .filter(|id| doc.node(id).is_some())
+        .into_iter()
+        .collect();
+    seen.extend(queue.iter().copied());
+    while let Some(current) = queue.pop_front() {
+        order.push(current);
+        for edge in outgoing.get(current).into_iter().flatten() {
+            let next = edge.target.as_str();
+            if seen.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    for node in &doc.nodes {
+        if seen.insert(node.id.as_str()) {
+            order.push(node.id.as_str());
+        }
+    }
+
+    let mapping: HashMap<String, String> = order
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.to_string(), format!("{prefix}{i}")))
+        .collect();
+    let rename = |id: &mut String| {
+        if let Some(new) = mapping.get(id.as_str()) {
+            *id = new.clone();
+        }
+    };
+
+    let mut normalized = doc.clone();
+    for node in &mut normalized.nodes {
+        rename(&mut node.id);
+    }
+    for edge in &mut normalized.edges {
+        rename(&mut edge.source);
+        rename(&mut edge.target);
+    }
+    if let Some(root) = &mut normalized.root_node_id {
+        rename(root);
+    }
+    for tree in normalized.trees.iter_mut().flat_map(|t| t.values_mut()) {
+        rename(&mut tree.root_node_id);
+    }
+    let mapping_ids = normalized
+        .metadata
+        .as_mut()
+        .and_then(|m| m.get_mut("beginEndMapping"))
+        .and_then(|m| m.as_object_mut());
+    for (key, value) in mapping_ids.into_iter().flatten() {
+        if key == "beginNodeId" || key == "endNodeId" {
+            if let Some(new) = value.as_str().and_then(|id| mapping.get(id)) {
+                *value = serde_json::Value::String(new.clone());
+            }
+        }
+    }
+
+    (normalized, mapping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse;
+    use crate::validate::validate_document;
+
+    fn ids(doc: &TreeDocument) -> Vec<&str> {
+        doc.nodes.iter().map(|n| n.id.as_str()).collect()
+    }
+
+    #[test]
+    fn extracts_reachable_nodes() {
+        let doc = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        let sub = extract_subtree(&doc, "enter").unwrap();
+
+        assert_eq!(sub.root_node_id.as_deref(), Some("enter"));
+        assert_eq!(
+            ids(&sub),
+            ["enter", "fountain", "explore", "wish", "ending"]
+        );
+        assert!(sub.edges.iter().all(|e| e.source != "start"));
+        assert_eq!(sub.metadata, doc.metadata);
+        assert_eq!(sub.features, doc.features);
+
+        let result = validate_document(&sub.to_canonical_json()).unwrap();
+        assert!(result.is_valid, "{:?}", result.errors);
+    }
+
+    #[test]
+    fn drops_trees_rooted_outside_the_subtree() {
+        let doc = parse(include_str!("../../../examples/multi-tree.tree.json")).unwrap();
+        let sub = extract_subtree(&doc, "stairs").unwrap();
+
+        assert_eq!(
+            ids(&sub),
+            ["fountain", "ending", "stairs", "barrels", "tunnel"]
+        );
+        let trees = sub.trees.as_ref().unwrap();
+        assert!(trees.contains_key("cellar"));
+        assert!(!trees.contains_key("garden"));
+        assert_eq!(sub.node("fountain").unwrap().tree_ids, None);
+
+        let result = validate_document(&sub.to_canonical_json()).unwrap();
+        assert!(result.is_valid, "{:?}", result.errors);
+    }
+
+    #[test]
+    fn prunes_orphans_and_their_edges() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Root"},
+                {"id": "orphan1", "content": "Orphan"},
+                {"id": "n2", "content": "Reachable"},
+                {"id": "orphan2", "content": "Another orphan"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "orphan1", "target": "orphan2"},
+                {"source": "orphan2", "target": "n2"}
+            ]
+        }"#;
+        let doc = parse(json).unwrap();
+        let pruned = prune_orphans(&doc);
+
+        assert_eq!(ids(&pruned), ["n1", "n2"]);
+        assert_eq!(pruned.edges.len(), 1);
+        assert_eq!(pruned.root_node_id.as_deref(), Some("n1"));
+        assert_eq!(doc.nodes.len(), 4);
+
+        let result = validate_document(&pruned.to_canonical_json()).unwrap();
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert!(result
+            .advisories
+            .iter()
+            .all(|d| d.rule != crate::error::Rule::OrphanNode));
+    }
+
+    #[test]
+    fn prune_without_root_is_unchanged() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "missing",
+            "nodes": [{"id": "a", "content": "A"}],
+            "edges": []
+        }"#;
+        let doc = parse(json).unwrap();
+        assert_eq!(ids(&prune_orphans(&doc)), ["a"]);
+    }
+
+    #[test]
+    fn canonicalize_ignores_original_order() {
+        let doc = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        let mut shuffled = doc.clone();
+        shuffled.nodes.reverse();
+        shuffled.edges.rotate_left(3);
+
+        let canonical = canonicalize(&doc);
+        assert_eq!(
+            canonical.to_canonical_json(),
+            canonicalize(&shuffled).to_canonical_json()
+        );
+        assert_eq!(
+            ids(&canonical),
+            ["climb", "ending", "enter", "explore", "fountain", "start", "wish"]
+        );
+        let sources: Vec<_> = canonical.edges.iter().map(|e| e.source.as_str()).collect();
+        assert_eq!(
+            sources,
+            ["enter", "enter", "explore", "fountain", "start", "start", "wish"]
+        );
+    }
+
+    #[test]
+    fn filters_branches_by_edge_type() {
+        let doc = parse(include_str!("../../../examples/multi-tree.tree.json")).unwrap();
+        let cross = filter_edge_type(&doc, &EdgeType::Cross);
+        assert_eq!(cross.edges.len(), 6);
+
+        let other = filter_edge_type(&doc, &EdgeType::from("narrative"));
+        assert_eq!(other.nodes.len(), doc.nodes.len());
+        assert!(other.edges.iter().all(|e| e.is_trunk == Some(true)));
+        assert_eq!(other.edges.len(), 4);
+    }
+
+    fn minimal() -> TreeDocument {
+        parse(include_str!("../../../examples/minimal.tree.json")).unwrap()
+    }
+
+    #[test]
+    fn merges_disjoint_documents() {
+        let a = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        let b = minimal();
+        let merged = merge(&a, &b, None).unwrap();
+
+        assert_eq!(merged.nodes.len(), a.nodes.len() + b.nodes.len());
+        assert_eq!(merged.edges.len(), a.edges.len() + b.edges.len());
+        assert_eq!(merged.root_node_id, a.root_node_id);
+        assert_eq!(merged.metadata, a.metadata);
+        assert_eq!(merged.edges.last(), b.edges.last());
+    }
+
+    #[test]
+    fn prefixes_ids_from_second_document() {
+        let merged = merge(&minimal(), &minimal(), Some("b_")).unwrap();
+
+        assert_eq!(ids(&merged), ["n1", "n2", "n3", "b_n1", "b_n2", "b_n3"]);
+        assert_eq!(merged.edges[2].source, "b_n1");
+        assert_eq!(merged.edges[2].target, "b_n2");
+        assert_eq!(merged.root_node_id.as_deref(), Some("n1"));
+    }
+
+    #[test]
+    fn merge_drops_duplicate_edges() {
+        let a = minimal();
+        let mut b = TreeDocument {
+            nodes: Vec::new(),
+            ..a.clone()
+        };
+        b.edges[0].label = Some("Relabeled".to_string());
+        let merged = merge(&a, &b, None).unwrap();
+
+        assert_eq!(merged.edges, a.edges);
+    }
+
+    #[test]
+    fn prefixes_tree_references() {
+        let doc = parse(include_str!("../../../examples/multi-tree.tree.json")).unwrap();
+        let merged = merge(&doc, &doc, Some("copy_")).unwrap();
+
+        let trees = merged.trees.as_ref().unwrap();
+        assert_eq!(trees["copy_cellar"].root_node_id, "copy_stairs");
+        let copied = merged.node("copy_gate").unwrap();
+        assert_eq!(
+            copied.tree_ids.as_deref(),
+            Some(&["copy_garden".to_string()][..])
+        );
+        assert_eq!(merged.features.as_deref(), doc.features.as_deref());
+    }
+
+    #[test]
+    fn conflicting_ids_are_rejected() {
+        let err = merge(&minimal(), &minimal(), None).unwrap_err();
+        assert_eq!(err, MergeError::IdConflict("n1".to_string()));
+        assert_eq!(err.to_string(), "node id 'n1' exists in both documents");
+    }
+
+    #[test]
+    fn normalizes_ids_in_bfs_order() {
+        let doc = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        let (normalized, mapping) = normalize_ids(&doc, "n");
+
+        assert_eq!(normalized.root_node_id.as_deref(), Some("n0"));
+        assert_eq!(mapping["start"], "n0");
+        assert_eq!(mapping["enter"], "n1");
+        assert_eq!(mapping["climb"], "n2");
+        assert_eq!(mapping.len(), doc.nodes.len());
+
+        for (old, new) in doc.edges.iter().zip(&normalized.edges) {
+            assert_eq!(new.source, mapping[&old.source]);
+            assert_eq!(new.target, mapping[&old.target]);
+            assert_eq!(new.label, old.label);
+        }
+        for (old, new) in doc.nodes.iter().zip(&normalized.nodes) {
+            assert_eq!(new.id, mapping[&old.id]);
+            assert_eq!(new.content, old.content);
+        }
+
+        let result = validate_document(&normalized.to_canonical_json()).unwrap();
+        assert!(result.is_valid, "{:?}", result.errors);
+    }
+
+    #[test]
+    fn orphans_are_numbered_last() {
+        let doc = parse(include_str!("../../../examples/invalid/orphan-node.tree.json")).unwrap();
+        let (normalized, mapping) = normalize_ids(&doc, "step-");
+
+        assert_eq!(mapping["n1"], "step-0");
+        assert_eq!(mapping["n2"], "step-1");
+        assert_eq!(mapping["orphan1"], "step-2");
+        assert_eq!(mapping["orphan2"], "step-3");
+        assert_eq!(ids(&normalized), ["step-0", "step-1", "step-2", "step-3"]);
+        assert_eq!(normalized.edges[0].source, "step-0");
+        assert_eq!(normalized.edges[0].target, "step-1");
+    }
+
+    #[test]
+    fn normalize_rewrites_tree_roots() {
+        let doc = parse(include_str!("../../../examples/multi-tree.tree.json")).unwrap();
+        let (normalized, mapping) = normalize_ids(&doc, "n");
+
+        let trees = normalized.trees.as_ref().unwrap();
+        assert_eq!(trees["cellar"].root_node_id, mapping["stairs"]);
+        let result = validate_document(&normalized.to_canonical_json()).unwrap();
+        assert!(result.is_valid, "{:?}", result.errors);
+    }
+
+    #[test]
+    fn missing_root_is_an_error() {
+        let doc = parse(include_str!("../../../examples/story.tree.json")).unwrap();
+        let err = extract_subtree(&doc, "nowhere").unwrap_err();
+        assert_eq!(err, "Node 'nowhere' not found in nodes array");
+    }
+}
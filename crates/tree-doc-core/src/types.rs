@@ -1,20 +1,95 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TreeDocument {
     pub format_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub root_node_id: Option<String>,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
     // Tier 1
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_reader_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub features: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
     // Tier 2
-    pub trees: Option<HashMap<String, TreeDescriptor>>,
+    /// Keyed by tree id; kept sorted so serialized output is deterministic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trees: Option<BTreeMap<String, TreeDescriptor>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub embedding_ref: Option<EmbeddingRef>,
+    /// Keys the format does not define, kept so a rewritten document does not
+    /// lose them.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TreeDocument {
+    /// The first node with the given id.
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    /// Edges whose source is `id`, in document order.
+    pub fn outgoing<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Edge> + 'a {
+        self.edges.iter().filter(move |e| e.source == id)
+    }
+
+    /// Edges whose target is `id`, in document order.
+    pub fn incoming<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a Edge> + 'a {
+        self.edges.iter().filter(move |e| e.target == id)
+    }
+
+    /// Edges grouped by source id, each group in document order, leaving out
+    /// edges whose target is not a node.
+    ///
+    /// [`outgoing`](Self::outgoing) and [`node`](Self::node) scan the whole
+    /// document, so traversals build this once instead of calling them at
+    /// every step.
+    pub fn edges_by_source(&self) -> HashMap<&str, Vec<&Edge>> {
+        let node_ids: HashSet<&str> = self.nodes.iter().map(|n| n.id.as_str()).collect();
+        let mut edges: HashMap<&str, Vec<&Edge>> = HashMap::new();
+        for edge in &self.edges {
+            if node_ids.contains(edge.target.as_str()) {
+                edges.entry(edge.source.as_str()).or_default().push(edge);
+            }
+        }
+        edges
+    }
+
+    /// The `metadata` keys the format defines, read leniently: a key that is
+    /// missing or has the wrong type is `None`, and unknown keys are ignored.
+    /// Returns `None` when there is no metadata object at all.
+    pub fn metadata_typed(&self) -> Option<DocumentMetadata> {
+        let metadata = self.metadata.as_ref()?.as_object()?;
+        let string = |key: &str| metadata.get(key)?.as_str().map(str::to_string);
+        Some(DocumentMetadata {
+            title: string("title"),
+            author: string("author"),
+            created: string("created"),
+            tags: metadata
+                .get("tags")
+                .and_then(|tags| serde_json::from_value(tags.clone()).ok()),
+        })
+    }
+
+    /// Serialize in canonical form: fields in declaration order (tier 0 first,
+    /// then tier 1 and 2), absent optional fields omitted, 2-space indentation,
+    /// and a trailing newline.
+    ///
+    /// Keys the format does not define are kept, sorted, after the defined
+    /// fields of the object they appear in.
+    pub fn to_canonical_json(&self) -> String {
+        let mut json =
+            serde_json::to_string_pretty(self).expect("TreeDocument always serializes");
+        json.push('\n');
+        json
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,31 +97,223 @@ pub struct TreeDocument {
 pub struct Node {
     pub id: String,
     pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
-    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<NodeStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tree_ids: Option<Vec<String>>,
+    /// Keys the format does not define.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Edge {
     pub source: String,
     pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub is_trunk: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
-    #[serde(rename = "type")]
-    pub edge_type: Option<String>,
-    pub status: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub edge_type: Option<EdgeType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<NodeStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tree_id: Option<String>,
-    pub link_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_type: Option<EdgeType>,
+    /// Keys the format does not define.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Edge {
+    /// Whether the edge's `type` or `linkType` is `edge_type`.
+    pub fn has_type(&self, edge_type: &EdgeType) -> bool {
+        self.edge_type.as_ref() == Some(edge_type) || self.link_type.as_ref() == Some(edge_type)
+    }
+
+    /// The key two edges must share to count as duplicates: source, target,
+    /// and whether the edge is on the trunk. Labels, types, and other
+    /// annotations are ignored.
+    pub fn dedup_key(&self) -> (String, String, bool) {
+        (
+            self.source.clone(),
+            self.target.clone(),
+            self.is_trunk == Some(true),
+        )
+    }
+}
+
+/// A `status` value on a node or edge.
+///
+/// Values renderers understand get their own variant; anything else is kept
+/// verbatim in `Other`, so deserialization never fails on an unfamiliar
+/// status. Serializes back to the original string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum NodeStatus {
+    Active,
+    Draft,
+    Deprecated,
+    DeadEnd,
+    Ending,
+    Terminal,
+    Other(String),
+}
+
+impl NodeStatus {
+    /// Every status with its own variant, in declaration order.
+    pub const KNOWN: &'static [NodeStatus] = &[
+        NodeStatus::Active,
+        NodeStatus::Draft,
+        NodeStatus::Deprecated,
+        NodeStatus::DeadEnd,
+        NodeStatus::Ending,
+        NodeStatus::Terminal,
+    ];
+
+    /// The status as written in the document.
+    pub fn as_str(&self) -> &str {
+        match self {
+            NodeStatus::Active => "active",
+            NodeStatus::Draft => "draft",
+            NodeStatus::Deprecated => "deprecated",
+            NodeStatus::DeadEnd => "dead_end",
+            NodeStatus::Ending => "ending",
+            NodeStatus::Terminal => "terminal",
+            NodeStatus::Other(s) => s,
+        }
+    }
+
+    /// Whether this status marks a node without outgoing edges as a
+    /// deliberate stop.
+    pub fn is_ending(&self) -> bool {
+        matches!(
+            self,
+            NodeStatus::Ending | NodeStatus::Terminal | NodeStatus::DeadEnd
+        )
+    }
+}
+
+impl From<&str> for NodeStatus {
+    fn from(s: &str) -> Self {
+        NodeStatus::KNOWN
+            .iter()
+            .find(|known| known.as_str() == s)
+            .cloned()
+            .unwrap_or_else(|| NodeStatus::Other(s.to_string()))
+    }
+}
+
+impl From<String> for NodeStatus {
+    fn from(s: String) -> Self {
+        match NodeStatus::from(s.as_str()) {
+            NodeStatus::Other(_) => NodeStatus::Other(s),
+            known => known,
+        }
+    }
+}
+
+impl From<NodeStatus> for String {
+    fn from(status: NodeStatus) -> Self {
+        match status {
+            NodeStatus::Other(s) => s,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for NodeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An edge `type` or `linkType` classification.
+///
+/// The format defines `cross`, for an edge linking two trees of a multi-tree
+/// document, and `intra`, for a link that stays within one tree; any other
+/// value is kept verbatim in `Other`. Serializes back to the original string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum EdgeType {
+    Cross,
+    Intra,
+    Other(String),
+}
+
+impl EdgeType {
+    /// Every type with its own variant, in declaration order.
+    pub const KNOWN: &'static [EdgeType] = &[EdgeType::Cross, EdgeType::Intra];
+
+    /// The type as written in the document.
+    pub fn as_str(&self) -> &str {
+        match self {
+            EdgeType::Cross => "cross",
+            EdgeType::Intra => "intra",
+            EdgeType::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for EdgeType {
+    fn from(s: &str) -> Self {
+        EdgeType::KNOWN
+            .iter()
+            .find(|known| known.as_str() == s)
+            .cloned()
+            .unwrap_or_else(|| EdgeType::Other(s.to_string()))
+    }
+}
+
+impl From<String> for EdgeType {
+    fn from(s: String) -> Self {
+        match EdgeType::from(s.as_str()) {
+            EdgeType::Other(_) => EdgeType::Other(s),
+            known => known,
+        }
+    }
+}
+
+impl From<EdgeType> for String {
+    fn from(edge_type: EdgeType) -> Self {
+        match edge_type {
+            EdgeType::Other(s) => s,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for EdgeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Typed view of the document-level `metadata` keys, from
+/// [`TreeDocument::metadata_typed`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocumentMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// An RFC 3339 timestamp, kept as written.
+    pub created: Option<String>,
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TreeDescriptor {
     pub root_node_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 }
 
@@ -54,5 +321,185 @@ pub struct TreeDescriptor {
 #[serde(rename_all = "camelCase")]
 pub struct EmbeddingRef {
     pub format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DocumentMetadata, Edge, EdgeType, NodeStatus};
+    use crate::parse;
+
+    #[test]
+    fn node_lookup() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        assert_eq!(doc.node("climb").unwrap().id, "climb");
+        assert!(doc.node("missing").is_none());
+    }
+
+    #[test]
+    fn outgoing_and_incoming_edges() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+
+        let outgoing: Vec<_> = doc.outgoing("start").map(|e| e.target.as_str()).collect();
+        assert_eq!(outgoing, vec!["enter", "climb"]);
+
+        let incoming: Vec<_> = doc.incoming("ending").map(|e| e.source.as_str()).collect();
+        assert_eq!(incoming, vec!["wish", "explore"]);
+
+        assert_eq!(doc.outgoing("ending").count(), 0);
+        assert_eq!(doc.outgoing("missing").count(), 0);
+        assert_eq!(doc.incoming("missing").count(), 0);
+    }
+
+    #[test]
+    fn edges_by_source_skips_dangling_targets() {
+        let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let edges = doc.edges_by_source();
+
+        for (source, group) in &edges {
+            let expected: Vec<_> = doc
+                .outgoing(source)
+                .filter(|e| doc.node(&e.target).is_some())
+                .collect();
+            assert_eq!(*group, expected);
+        }
+        let kept: usize = edges.values().map(Vec::len).sum();
+        assert!(kept < doc.edges.len());
+    }
+
+    #[test]
+    fn dedup_key_ignores_annotations() {
+        let edge = |json: &str| serde_json::from_str::<Edge>(json).unwrap();
+        let plain = edge(r#"{"source": "a", "target": "b", "isTrunk": true}"#);
+        let labeled = edge(r#"{"source": "a", "target": "b", "isTrunk": true, "label": "Go"}"#);
+        assert_eq!(plain.dedup_key(), labeled.dedup_key());
+
+        let reversed = edge(r#"{"source": "b", "target": "a", "isTrunk": true}"#);
+        let branch = edge(r#"{"source": "a", "target": "b", "isTrunk": false}"#);
+        let untyped = edge(r#"{"source": "a", "target": "b"}"#);
+        assert_ne!(plain.dedup_key(), reversed.dedup_key());
+        assert_ne!(plain.dedup_key(), branch.dedup_key());
+        assert_eq!(branch.dedup_key(), untyped.dedup_key());
+    }
+
+    #[test]
+    fn canonical_json_is_idempotent() {
+        for json in [
+            include_str!("../../../examples/story.tree.json"),
+            include_str!("../../../examples/multi-tree.tree.json"),
+        ] {
+            let once = parse::parse(json).unwrap().to_canonical_json();
+            let twice = parse::parse(&once).unwrap().to_canonical_json();
+            assert_eq!(once, twice);
+        }
+    }
+
+    #[test]
+    fn canonical_json_field_order() {
+        let json = r#"{"edges": [], "nodes": [{"content": "Hi", "id": "n1"}],
+            "rootNodeId": "n1", "formatVersion": "1.0"}"#;
+        let formatted = parse::parse(json).unwrap().to_canonical_json();
+        let positions: Vec<_> = ["formatVersion", "rootNodeId", "nodes", "edges"]
+            .iter()
+            .map(|key| formatted.find(&format!("\"{key}\"")).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{formatted}");
+        assert!(formatted.contains("\n  \"nodes\""));
+    }
+
+    #[test]
+    fn canonical_json_keeps_unknown_fields() {
+        let json = r#"{"formatVersion": "1.0", "rootNodeId": "n1", "x-editor": {"zoom": 2},
+            "nodes": [{"id": "n1", "content": "Hi", "notes": "check tone"}],
+            "edges": [{"source": "n1", "target": "n1", "weight": 3}]}"#;
+        let formatted = parse::parse(json).unwrap().to_canonical_json();
+        let value: serde_json::Value = serde_json::from_str(&formatted).unwrap();
+        assert_eq!(value["x-editor"]["zoom"], 2);
+        assert_eq!(value["nodes"][0]["notes"], "check tone");
+        assert_eq!(value["edges"][0]["weight"], 3);
+        // Unknown keys follow the defined ones
+        assert!(formatted.find("\"edges\"") < formatted.find("\"x-editor\""));
+        let again = parse::parse(&formatted).unwrap().to_canonical_json();
+        assert_eq!(again, formatted);
+    }
+
+    #[test]
+    fn known_statuses_map_to_variants() {
+        let cases = [
+            ("active", NodeStatus::Active),
+            ("draft", NodeStatus::Draft),
+            ("deprecated", NodeStatus::Deprecated),
+            ("dead_end", NodeStatus::DeadEnd),
+            ("ending", NodeStatus::Ending),
+            ("terminal", NodeStatus::Terminal),
+        ];
+        for (text, status) in &cases {
+            assert_eq!(NodeStatus::from(*text), *status);
+            assert_eq!(status.as_str(), *text);
+        }
+        let listed: Vec<_> = cases.into_iter().map(|(_, status)| status).collect();
+        assert_eq!(NodeStatus::KNOWN, listed);
+        assert!(NodeStatus::DeadEnd.is_ending());
+        assert!(!NodeStatus::Draft.is_ending());
+    }
+
+    #[test]
+    fn known_edge_types_map_to_variants() {
+        let cases = [("cross", EdgeType::Cross), ("intra", EdgeType::Intra)];
+        for (text, edge_type) in &cases {
+            assert_eq!(EdgeType::from(*text), *edge_type);
+            assert_eq!(edge_type.as_str(), *text);
+        }
+        let listed: Vec<_> = cases.into_iter().map(|(_, edge_type)| edge_type).collect();
+        assert_eq!(EdgeType::KNOWN, listed);
+    }
+
+    #[test]
+    fn unknown_values_land_in_fallback() {
+        let json = r#"{"formatVersion": "1.0", "rootNodeId": "a",
+            "nodes": [{"id": "a", "content": "A", "status": "review"}],
+            "edges": [{"source": "a", "target": "a", "type": "jump", "linkType": "cross"}]}"#;
+        let doc = parse::parse(json).unwrap();
+        assert_eq!(doc.nodes[0].status, Some(NodeStatus::Other("review".to_string())));
+        assert_eq!(doc.edges[0].edge_type, Some(EdgeType::Other("jump".to_string())));
+        assert_eq!(doc.edges[0].link_type, Some(EdgeType::Cross));
+
+        let formatted = doc.to_canonical_json();
+        assert!(formatted.contains(r#""status": "review""#), "{formatted}");
+        assert!(formatted.contains(r#""type": "jump""#), "{formatted}");
+        assert!(formatted.contains(r#""linkType": "cross""#), "{formatted}");
+    }
+
+    #[test]
+    fn typed_metadata() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let metadata = parse::parse(json).unwrap().metadata_typed().unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("The Enchanted Garden"));
+        assert_eq!(metadata.author.as_deref(), Some("Tree Doc Examples"));
+        assert_eq!(metadata.tags, None);
+
+        let json = include_str!("../../../examples/minimal.tree.json");
+        assert_eq!(parse::parse(json).unwrap().metadata_typed(), None);
+    }
+
+    #[test]
+    fn typed_metadata_skips_wrong_types() {
+        let json = r#"{"formatVersion": "1.0", "nodes": [], "edges": [],
+            "metadata": {"title": 42, "author": "Ann", "tags": ["a", 1],
+                "created": "2024-05-01"}}"#;
+        let metadata = parse::parse(json).unwrap().metadata_typed().unwrap();
+        assert_eq!(
+            metadata,
+            DocumentMetadata {
+                title: None,
+                author: Some("Ann".to_string()),
+                created: Some("2024-05-01".to_string()),
+                tags: None,
+            }
+        );
+    }
+}
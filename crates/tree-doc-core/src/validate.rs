@@ -1,32 +1,231 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use petgraph::graph::{DiGraph, NodeIndex};
 
-use crate::error::{Diagnostic, DocumentStats, Location, Rule, Severity, ValidationResult};
+use crate::error::{
+    Diagnostic, DocumentOrder, DocumentStats, Location, Rule, Severity, ValidationResult,
+};
 use crate::parse::{self, ParseError};
 use crate::schema;
-use crate::types::TreeDocument;
+use crate::types::{Edge, EdgeType, NodeStatus, TreeDocument};
+use crate::viewer;
 
-/// Run the full validation pipeline: parse → schema → semantic → stats.
+/// `formatVersion` values this validator fully understands.
+const SUPPORTED_VERSIONS: &[&str] = &["1.0"];
+
+/// The reader version this crate implements, compared against `minReaderVersion`.
+pub const READER_VERSION: (u32, u32) = (1, 0);
+
+/// Feature flags this validator understands.
+const KNOWN_FEATURES: &[&str] = &["labels", "status", "metadata", "trees", "embeddings"];
+
+/// Lookups shared by the graph rules and stats, built once per
+/// `validate_document` call instead of once per rule.
+///
+/// Construction is a single pass over `doc.nodes` and two passes over
+/// `doc.edges`; every map borrows ids from `doc`.
+struct GraphIndex<'a> {
+    node_ids: HashSet<&'a str>,
+    /// source -> targets, for every edge in document order (dangling edges included).
+    outgoing: HashMap<&'a str, Vec<&'a str>>,
+    /// target -> sources, the reverse of `outgoing`.
+    incoming: HashMap<&'a str, Vec<&'a str>>,
+    /// source -> targets, for every trunk edge in document order.
+    trunk_outgoing: HashMap<&'a str, Vec<&'a str>>,
+    /// source -> first trunk target, from `viewer::first_trunk_target`.
+    trunk_next: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> GraphIndex<'a> {
+    fn new(doc: &'a TreeDocument) -> Self {
+        let node_ids = doc.nodes.iter().map(|n| n.id.as_str()).collect();
+
+        let mut outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut incoming: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut trunk_outgoing: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &doc.edges {
+            let (source, target) = (edge.source.as_str(), edge.target.as_str());
+            outgoing.entry(source).or_default().push(target);
+            incoming.entry(target).or_default().push(source);
+            if edge.is_trunk == Some(true) {
+                trunk_outgoing.entry(source).or_default().push(target);
+            }
+        }
+
+        GraphIndex {
+            node_ids,
+            outgoing,
+            incoming,
+            trunk_outgoing,
+            trunk_next: viewer::first_trunk_target(doc),
+        }
+    }
+
+    /// The root node id, if it is set and references an existing node.
+    fn root<'d>(&self, doc: &'d TreeDocument) -> Option<&'d str> {
+        doc.root_node_id
+            .as_deref()
+            .filter(|id| self.node_ids.contains(id))
+    }
+
+    /// Nodes the trunk visits from the root, following every trunk edge (not
+    /// just the first per node). Empty without a valid root.
+    fn trunk_from_root(&self, doc: &TreeDocument) -> HashSet<&'a str> {
+        match self.root(doc).and_then(|id| self.node_ids.get(id)) {
+            Some(&root_id) => reachable_from(root_id, &self.trunk_outgoing),
+            None => HashSet::new(),
+        }
+    }
+}
+
+/// Settings that adjust what `validate_document_with_options` reports.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationOptions {
+    /// Rules whose diagnostics are dropped before `is_valid` is decided.
+    pub allowed_rules: HashSet<Rule>,
+    /// Status values accepted in addition to the built-in ones (Rule 11).
+    pub extra_statuses: HashSet<String>,
+    /// Feature flags accepted in addition to the built-in ones (Rule 17).
+    pub extra_features: HashSet<String>,
+    /// Longest node content, in characters, before Rule 19 reports it.
+    /// `None` disables the length check.
+    pub max_content_len: Option<usize>,
+    /// A schema checked in addition to the tier schema, e.g. from
+    /// [`schema::compile_schema`].
+    pub extra_schema: Option<Arc<jsonschema::Validator>>,
+    /// Report document, node, and edge fields the format does not define
+    /// (Rule 20). Off by default, so extra fields are accepted.
+    pub strict: bool,
+    /// Most trunk edges allowed before Rule 21 reports the trunk as too
+    /// long. `None` means no cap.
+    pub max_trunk_length: Option<usize>,
+    /// Report each missing node once (Rule 22) instead of every edge that
+    /// references it (Rule 2).
+    pub group_dangling_edges: bool,
+    /// Report general cycles (Rule 4) as errors rather than warnings, for
+    /// pipelines that require a strict DAG.
+    pub cycles_are_errors: bool,
+}
+
+/// Run the full validation pipeline with default options.
 pub fn validate_document(json_str: &str) -> Result<ValidationResult, ParseError> {
+    validate_document_with_options(json_str, &ValidationOptions::default())
+}
+
+/// Run the full validation pipeline with default options, then fill in each
+/// node and edge diagnostic's `span` from `json_str` (see
+/// [`attach_spans`](crate::span::attach_spans)).
+pub fn validate_document_with_spans(json_str: &str) -> Result<ValidationResult, ParseError> {
+    let mut result = validate_document(json_str)?;
+    crate::span::attach_spans(&mut result, json_str);
+    Ok(result)
+}
+
+/// Run the full validation pipeline with default options and also return the
+/// parsed document, so callers that go on to use it need not parse again.
+///
+/// The document is `None` when schema errors left it impossible to parse into
+/// typed structs; the result then holds those errors and is invalid.
+pub fn validate_and_parse(
+    json_str: &str,
+) -> Result<(ValidationResult, Option<TreeDocument>), ParseError> {
+    validate_and_parse_with_options(json_str, &ValidationOptions::default())
+}
+
+/// Run the full validation pipeline with default options on JSON read from
+/// `reader`, such as a file or stdin.
+///
+/// The input is read once into a buffer that schema and semantic validation
+/// share. Read failures, including input that is not UTF-8, are returned as
+/// [`ParseError::Io`].
+pub fn validate_reader<R: Read>(mut reader: R) -> Result<ValidationResult, ParseError> {
+    let mut json_str = String::new();
+    reader.read_to_string(&mut json_str)?;
+    validate_document(&json_str)
+}
+
+/// Validate each file in `paths` with default options, in order.
+///
+/// Every path gets an entry: a file that cannot be opened or read yields
+/// [`ParseError::Io`], and one that is not a document yields the usual parse
+/// error, without stopping the rest of the batch. Gzipped files are
+/// decompressed as in [`validate_bytes_with_options`].
+pub fn validate_files(paths: &[PathBuf]) -> Vec<(PathBuf, Result<ValidationResult, ParseError>)> {
+    let options = ValidationOptions::default();
+    paths
+        .iter()
+        .map(|path| {
+            let result = fs::read(path)
+                .map_err(ParseError::from)
+                .and_then(|bytes| validate_bytes_with_options(bytes, &options));
+            (path.clone(), result)
+        })
+        .collect()
+}
+
+/// Validate the raw bytes of one document file, decompressing them first
+/// when they are gzipped.
+///
+/// Corrupt gzip data and input that is not UTF-8 are returned as
+/// [`ParseError::Io`]; everything else behaves like
+/// [`validate_document_with_options`].
+pub fn validate_bytes_with_options(
+    bytes: Vec<u8>,
+    options: &ValidationOptions,
+) -> Result<ValidationResult, ParseError> {
+    validate_document_with_options(&parse::decode_maybe_gzip(bytes)?, options)
+}
+
+/// Run the full validation pipeline: parse → schema → semantic → stats.
+///
+/// Diagnostics for rules in `options.allowed_rules` are removed before they
+/// are partitioned by severity, so an allowed error does not make the
+/// document invalid.
+pub fn validate_document_with_options(
+    json_str: &str,
+    options: &ValidationOptions,
+) -> Result<ValidationResult, ParseError> {
+    validate_and_parse_with_options(json_str, options).map(|(result, _)| result)
+}
+
+/// The pipeline behind [`validate_document_with_options`] and
+/// [`validate_and_parse`], handing back the typed document it parsed.
+fn validate_and_parse_with_options(
+    json_str: &str,
+    options: &ValidationOptions,
+) -> Result<(ValidationResult, Option<TreeDocument>), ParseError> {
+    let is_reported = |d: &Diagnostic| !options.allowed_rules.contains(&d.rule);
     let mut all_diagnostics: Vec<Diagnostic> = Vec::new();
 
     // Step 1: Parse as generic JSON value
     let value = parse::parse_value(json_str)?;
 
     // Step 2: Schema validation
-    let schema_diags = schema::validate_schema(&value);
+    let schema_diags = match &options.extra_schema {
+        Some(extra) => schema::validate_schema_with(&value, extra),
+        None => schema::validate_schema(&value),
+    };
     let has_schema_errors = !schema_diags.is_empty();
     all_diagnostics.extend(schema_diags);
 
+    // Rule 20: Unknown fields, checked on the raw value since serde drops them
+    if options.strict {
+        check_unknown_fields(&value, &mut all_diagnostics);
+    }
+
     // If schema validation fails, we may not be able to parse into typed structs.
     // Try anyway — serde is more lenient than the schema in some ways.
     let doc = match parse::parse(json_str) {
         Ok(doc) => doc,
         Err(_) if has_schema_errors => {
             // Can't parse — return schema errors only
-            return Ok(ValidationResult {
-                is_valid: false,
+            all_diagnostics.retain(is_reported);
+            let result = ValidationResult {
+                is_valid: all_diagnostics.is_empty(),
                 errors: all_diagnostics,
                 warnings: Vec::new(),
                 advisories: Vec::new(),
@@ -35,35 +234,70 @@ pub fn validate_document(json_str: &str) -> Result<ValidationResult, ParseError>
                     edge_count: 0,
                     trunk_length: 0,
                     branch_count: 0,
+                    component_count: 0,
+                    longest_path_len: 0,
+                    max_out_degree: 0,
                     tier: 0,
                 },
-            });
+                order: DocumentOrder::default(),
+            };
+            return Ok((result, None));
         }
         Err(e) => return Err(e),
     };
 
     // Step 3: Semantic validation
-    let semantic_diags = validate_semantics(&doc);
+    let index = GraphIndex::new(&doc);
+    let semantic_diags = validate_semantics(&doc, &index, options);
     all_diagnostics.extend(semantic_diags);
 
     // Step 4: Compute stats
-    let tier = schema::detect_tier(&value);
-    let trunk_length = compute_trunk_length(&doc);
+    let stats = document_stats(&doc, &index, schema::detect_tier(&value));
+
+    // Rule 21: Trunk longer than the configured cap
+    check_trunk_length(stats.trunk_length, options.max_trunk_length, &mut all_diagnostics);
+
+    all_diagnostics.retain(is_reported);
+    let result = partition(all_diagnostics, stats, document_order(&doc));
+    Ok((result, Some(doc)))
+}
+
+fn document_stats(doc: &TreeDocument, index: &GraphIndex, tier: u8) -> DocumentStats {
     let branch_count = doc
         .edges
         .iter()
         .filter(|e| e.is_trunk != Some(true))
         .count();
 
-    let stats = DocumentStats {
+    DocumentStats {
         node_count: doc.nodes.len(),
         edge_count: doc.edges.len(),
-        trunk_length,
+        trunk_length: compute_trunk_length(doc, index),
         branch_count,
+        component_count: compute_component_count(doc),
+        longest_path_len: compute_longest_path_len(doc, index),
+        max_out_degree: index.outgoing.values().map(Vec::len).max().unwrap_or(0),
         tier,
-    };
+    }
+}
 
-    // Partition diagnostics by severity
+fn document_order(doc: &TreeDocument) -> DocumentOrder {
+    let mut nodes = HashMap::new();
+    for (i, node) in doc.nodes.iter().enumerate() {
+        nodes.entry(node.id.clone()).or_insert(i);
+    }
+    DocumentOrder {
+        nodes,
+        edge_sources: doc.edges.iter().map(|e| e.source.clone()).collect(),
+    }
+}
+
+/// Split diagnostics by severity into a result; valid means no errors.
+fn partition(
+    all_diagnostics: Vec<Diagnostic>,
+    stats: DocumentStats,
+    order: DocumentOrder,
+) -> ValidationResult {
     let mut errors = Vec::new();
     let mut warnings = Vec::new();
     let mut advisories = Vec::new();
@@ -76,39 +310,492 @@ pub fn validate_document(json_str: &str) -> Result<ValidationResult, ParseError>
         }
     }
 
-    Ok(ValidationResult {
+    ValidationResult {
         is_valid: errors.is_empty(),
         errors,
         warnings,
         advisories,
         stats,
-    })
+        order,
+    }
 }
 
-/// Run all semantic validation rules on a parsed document.
-fn validate_semantics(doc: &TreeDocument) -> Vec<Diagnostic> {
-    let mut diagnostics = Vec::new();
+/// Flatten a result's diagnostics into one list ordered by where they point in
+/// the document, for problems panels that group by node rather than by
+/// severity.
+///
+/// Document-level diagnostics come first, then each node in document order,
+/// followed by its outgoing edges in document order. Paths sort by their
+/// first node and JSON Pointers by the node or edge they point into.
+/// Diagnostics on ids missing from the document come last. Ties break by
+/// severity, errors first, then by rule code; the sort is stable beyond that.
+pub fn sorted_diagnostics(result: &ValidationResult) -> Vec<&Diagnostic> {
+    let order = &result.order;
+
+    // (group, node position, 0 for the node itself or 1 + edge position)
+    let node_key = |id: &str| order.nodes.get(id).map_or((2, 0, 0), |&i| (1, i, 0));
+    let edge_key = |i: usize| match order.edge_sources.get(i) {
+        Some(source) => {
+            let (group, node, _) = node_key(source);
+            (group, node, i + 1)
+        }
+        None => (2, 0, i + 1),
+    };
+    let location_key = |location: &Location| match location {
+        Location::Root => (0, 0, 0),
+        Location::Node(id) => node_key(id),
+        Location::Edge { index, .. } => edge_key(*index),
+        Location::Path(ids) => ids.first().map_or((0, 0, 0), |id| node_key(id)),
+        Location::Pointer(pointer) => {
+            let mut parts = pointer.split('/').skip(1);
+            let index = |part: Option<&str>| part.and_then(|p| p.parse::<usize>().ok());
+            match (parts.next(), index(parts.next())) {
+                (Some("nodes"), Some(i)) => (1, i, 0),
+                (Some("edges"), Some(i)) => edge_key(i),
+                _ => (0, 0, 0),
+            }
+        }
+    };
+    let severity_rank = |severity: &Severity| match severity {
+        Severity::Error => 0,
+        Severity::Warning => 1,
+        Severity::Advisory => 2,
+    };
+
+    let mut sorted: Vec<&Diagnostic> = result
+        .errors
+        .iter()
+        .chain(&result.warnings)
+        .chain(&result.advisories)
+        .collect();
+    sorted.sort_by_key(|d| {
+        (
+            location_key(&d.location),
+            severity_rank(&d.severity),
+            Rule::ALL.iter().position(|r| *r == d.rule),
+        )
+    });
+    sorted
+}
+
+/// Every diagnostic in `result` whose location mentions `node_id` (see
+/// [`Location::mentions_node`]), errors first, then warnings, then advisories.
+pub fn diagnostics_for_node<'a>(
+    result: &'a ValidationResult,
+    node_id: &str,
+) -> Vec<&'a Diagnostic> {
+    result
+        .errors
+        .iter()
+        .chain(&result.warnings)
+        .chain(&result.advisories)
+        .filter(|d| d.location.mentions_node(node_id))
+        .collect()
+}
+
+/// Rules whose diagnostics belong to a single node: the node itself for a
+/// `Location::Node`, or the edge's source for a `Location::Edge`. They only
+/// change when that node or its outgoing edges change.
+const LOCAL_RULES: &[Rule] = &[
+    Rule::DuplicateNodeId,
+    Rule::DanglingEdge,
+    Rule::SelfLoop,
+    Rule::AmbiguousTrunk,
+    Rule::UnknownStatus,
+    Rule::UnlabeledBranch,
+    Rule::DuplicateEdge,
+    Rule::DeadEndNode,
+    Rule::EmptyContent,
+    Rule::ContentTooLong,
+];
+
+/// Rules that depend only on which nodes exist and how edges connect them.
+const STRUCTURAL_RULES: &[Rule] = &[
+    Rule::TrunkCycle,
+    Rule::GeneralCycle,
+    Rule::OrphanNode,
+    Rule::MissingRoot,
+    Rule::OffTrunk,
+    Rule::UndefinedNode,
+    Rule::TrunkNotLinear,
+    Rule::DisconnectedTrunk,
+];
+
+/// Results of a full semantic validation, kept so [`revalidate_node`] can
+/// re-check one edited node without re-running every rule.
+///
+/// The cache describes the document as it was when built. Build it before
+/// editing a node, call `revalidate_node` after each change to that node,
+/// and build a new cache before editing a different one.
+#[derive(Debug, Clone)]
+pub struct ValidationCache {
+    options: ValidationOptions,
+    /// How many nodes use each id.
+    node_counts: HashMap<String, usize>,
+    /// source -> (target, isTrunk) for every edge, in document order.
+    outgoing: HashMap<String, Vec<(String, bool)>>,
+    /// target -> source for every edge, in document order.
+    incoming: HashMap<String, Vec<String>>,
+    /// The source of every edge, in document order.
+    edge_sources: Vec<String>,
+    /// Semantic diagnostics before `allowed_rules` is applied.
+    diagnostics: Vec<Diagnostic>,
+    stats: DocumentStats,
+}
+
+impl ValidationCache {
+    /// Validate `doc` with default options and remember the results.
+    pub fn new(doc: &TreeDocument) -> Self {
+        Self::with_options(doc, &ValidationOptions::default())
+    }
+
+    /// Validate `doc` with `options` and remember the results.
+    pub fn with_options(doc: &TreeDocument, options: &ValidationOptions) -> Self {
+        let mut node_counts = HashMap::new();
+        for node in &doc.nodes {
+            *node_counts.entry(node.id.clone()).or_default() += 1;
+        }
+        let mut outgoing: HashMap<String, Vec<(String, bool)>> = HashMap::new();
+        let mut incoming: HashMap<String, Vec<String>> = HashMap::new();
+        for edge in &doc.edges {
+            outgoing
+                .entry(edge.source.clone())
+                .or_default()
+                .push((edge.target.clone(), edge.is_trunk == Some(true)));
+            incoming
+                .entry(edge.target.clone())
+                .or_default()
+                .push(edge.source.clone());
+        }
+
+        let index = GraphIndex::new(doc);
+        let tier = serde_json::to_value(doc).map_or(0, |v| schema::detect_tier(&v));
+        ValidationCache {
+            options: options.clone(),
+            node_counts,
+            outgoing,
+            incoming,
+            edge_sources: doc.edges.iter().map(|e| e.source.clone()).collect(),
+            diagnostics: validate_semantics(doc, &index, options),
+            stats: document_stats(doc, &index, tier),
+        }
+    }
+}
+
+/// The node a `LOCAL_RULES` diagnostic belongs to.
+fn local_owner(d: &Diagnostic) -> Option<&str> {
+    match &d.location {
+        Location::Node(id) => Some(id),
+        Location::Edge { source, .. } => Some(source),
+        _ => None,
+    }
+}
+
+/// Indices of the edges, given by their sources, whose source is not in `set`.
+fn positions_outside<'a>(
+    sources: impl Iterator<Item = &'a str>,
+    set: &HashSet<&str>,
+) -> Vec<usize> {
+    sources
+        .enumerate()
+        .filter(|(_, source)| !set.contains(source))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Re-run semantic validation after a change to the node `node_id`.
+///
+/// `doc` must match the document `cache` was built from except for nodes
+/// with id `node_id` and edges into or out of them, so a node may be added,
+/// edited, or removed along with its edges. Node-local rules such as
+/// duplicate ids, dangling edges, and content checks are only re-run for that
+/// node and the nodes with edges into it. Graph rules such as cycles and
+/// orphans are re-run over the whole document when those edges or the node's
+/// presence changed, and document-level rules always are.
+///
+/// Diagnostics match those of a full [`validate_document_with_options`] on
+/// the serialized document, except that schema validation is not repeated and
+/// they are ordered by rule code rather than in the order the rules run.
+pub fn revalidate_node(
+    doc: &TreeDocument,
+    cache: &ValidationCache,
+    node_id: &str,
+) -> ValidationResult {
+    let options = &cache.options;
+
+    // Local diagnostics are owned by a node or by an edge's source, so the
+    // owners that may change are the node and every source with an edge into
+    // it, before or after the edit.
+    let mut affected: HashSet<&str> = HashSet::from([node_id]);
+    affected.extend(cache.incoming.get(node_id).into_iter().flatten().map(String::as_str));
+    affected.extend(
+        doc.edges
+            .iter()
+            .filter(|e| e.target == node_id)
+            .map(|e| e.source.as_str()),
+    );
+
+    // The affected nodes with all of their outgoing edges, which is all the
+    // local rules need to see
+    let (local_edge_index, local_edges): (Vec<usize>, Vec<Edge>) = doc
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| affected.contains(e.source.as_str()))
+        .map(|(i, e)| (i, e.clone()))
+        .unzip();
+    let local = TreeDocument {
+        format_version: doc.format_version.clone(),
+        root_node_id: doc.root_node_id.clone(),
+        nodes: doc
+            .nodes
+            .iter()
+            .filter(|n| affected.contains(n.id.as_str()))
+            .cloned()
+            .collect(),
+        edges: local_edges,
+        min_reader_version: None,
+        features: None,
+        metadata: None,
+        trees: None,
+        embedding_ref: None,
+        extra: Default::default(),
+    };
+
+    let node_count = local.nodes.iter().filter(|n| n.id == node_id).count();
+    let structure_changed = node_count != cache.node_counts.get(node_id).copied().unwrap_or(0)
+        || affected.iter().any(|&source| {
+            let old = cache.outgoing.get(source).map_or(&[][..], Vec::as_slice);
+            let new = local.edges.iter().filter(|e| e.source == source);
+            !old.iter()
+                .map(|(target, is_trunk)| (target.as_str(), *is_trunk))
+                .eq(new.map(|e| (e.target.as_str(), e.is_trunk == Some(true))))
+        });
 
-    // Rule 1: Duplicate node IDs
-    check_duplicate_ids(doc, &mut diagnostics);
+    let mut diagnostics: Vec<Diagnostic> = cache
+        .diagnostics
+        .iter()
+        .filter(|d| {
+            if LOCAL_RULES.contains(&d.rule) {
+                local_owner(d).is_some_and(|id| !affected.contains(id))
+            } else {
+                STRUCTURAL_RULES.contains(&d.rule) && !structure_changed
+            }
+        })
+        .cloned()
+        .collect();
+
+    // Edges from unaffected sources are unchanged and keep their relative
+    // order, but edits elsewhere may have shifted their indices
+    let old_positions = positions_outside(cache.edge_sources.iter().map(String::as_str), &affected);
+    let new_positions = positions_outside(doc.edges.iter().map(|e| e.source.as_str()), &affected);
+    let moved: HashMap<usize, usize> = old_positions.into_iter().zip(new_positions).collect();
+    for d in &mut diagnostics {
+        if let Location::Edge { index, .. } = &mut d.location {
+            *index = moved.get(index).copied().unwrap_or(*index);
+        }
+    }
 
-    // Build node ID set for subsequent checks
     let node_ids: HashSet<&str> = doc.nodes.iter().map(|n| n.id.as_str()).collect();
+    let local_index = GraphIndex::new(&local);
+    let first_local = diagnostics.len();
+    check_duplicate_ids(&local, &mut diagnostics);
+    if !options.group_dangling_edges {
+        check_dangling_edges(&local, &node_ids, &mut diagnostics);
+    }
+    check_self_loops(&local, &mut diagnostics);
+    check_ambiguous_trunk(&local, &mut diagnostics);
+    check_unknown_status(&local, &options.extra_statuses, &mut diagnostics);
+    check_unlabeled_branches(&local, &mut diagnostics);
+    check_duplicate_edges(&local, &mut diagnostics);
+    check_dead_ends(&local, &local_index, &mut diagnostics);
+    check_content_length(&local, options.max_content_len, &mut diagnostics);
+    for d in &mut diagnostics[first_local..] {
+        if let Location::Edge { index, .. } = &mut d.location {
+            *index = local_edge_index[*index];
+        }
+    }
+
+    check_begin_end_mapping(doc, &node_ids, &mut diagnostics);
+    check_tree_references(doc, &node_ids, &mut diagnostics);
+    check_tree_links(doc, &mut diagnostics);
+    check_format_version(doc, &mut diagnostics);
+    check_min_reader_version(doc, &mut diagnostics);
+    check_features(doc, &options.extra_features, &mut diagnostics);
+    check_metadata_types(doc, &mut diagnostics);
+    let index = GraphIndex::new(doc);
+    check_incomplete_branches(doc, &index, &mut diagnostics);
+
+    let stats = if structure_changed {
+        check_trunk_cycle(doc, &index, &mut diagnostics);
+        check_general_cycles(doc, &index, cycle_severity(options), &mut diagnostics);
+        check_orphan_nodes(doc, &index, &mut diagnostics);
+        check_missing_root(doc, &index.node_ids, &mut diagnostics);
+        check_off_trunk(doc, &index, &mut diagnostics);
+        check_converging_trunk(doc, &mut diagnostics);
+        check_disconnected_trunk(doc, &index, &mut diagnostics);
+        if options.group_dangling_edges {
+            check_undefined_nodes(doc, &index.node_ids, &mut diagnostics);
+        }
+        document_stats(doc, &index, cache.stats.tier)
+    } else {
+        cache.stats.clone()
+    };
+    check_trunk_length(stats.trunk_length, options.max_trunk_length, &mut diagnostics);
+
+    diagnostics.retain(|d| !options.allowed_rules.contains(&d.rule));
+    diagnostics.sort_by_key(|d| Rule::ALL.iter().position(|r| *r == d.rule));
+    partition(diagnostics, stats, document_order(doc))
+}
+
+fn cycle_severity(options: &ValidationOptions) -> Severity {
+    if options.cycles_are_errors {
+        Severity::Error
+    } else {
+        Severity::Warning
+    }
+}
+
+/// Run all semantic validation rules on a parsed document.
+fn validate_semantics(
+    doc: &TreeDocument,
+    index: &GraphIndex,
+    options: &ValidationOptions,
+) -> Vec<Diagnostic> {
+    run_semantic_checks(doc, index, options, cfg!(feature = "parallel"))
+}
+
+/// Run `a` and `b`, on the rayon pool when `parallel` is set.
+fn join<A, B, RA, RB>(parallel: bool, a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    if parallel {
+        return rayon::join(a, b);
+    }
+    let _ = parallel;
+    (a(), b())
+}
 
-    // Rule 2: Dangling edges
-    check_dangling_edges(doc, &node_ids, &mut diagnostics);
+/// Collect the diagnostics a single check function pushes.
+fn collect(check: impl FnOnce(&mut Vec<Diagnostic>)) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check(&mut diagnostics);
+    diagnostics
+}
 
-    // Rule 3: Trunk cycle detection
-    check_trunk_cycle(doc, &node_ids, &mut diagnostics);
+fn run_semantic_checks(
+    doc: &TreeDocument,
+    index: &GraphIndex,
+    options: &ValidationOptions,
+    parallel: bool,
+) -> Vec<Diagnostic> {
+    let node_ids = &index.node_ids;
 
-    // Rule 4: General cycle detection (Tarjan's SCC)
-    check_general_cycles(doc, &node_ids, &mut diagnostics);
+    // Rules 1-5 only read `doc`, so they may run concurrently. Each check
+    // collects into its own vector and the results are concatenated in rule
+    // order, so output is identical to running them one after another.
+    let (first, (trunk, (general, orphans))) = join(
+        parallel,
+        || {
+            collect(|d| {
+                // Rule 1: Duplicate node IDs
+                check_duplicate_ids(doc, d);
+                if options.group_dangling_edges {
+                    // Rule 22: Missing nodes, once per id
+                    check_undefined_nodes(doc, node_ids, d);
+                } else {
+                    // Rule 2: Dangling edges
+                    check_dangling_edges(doc, node_ids, d);
+                }
+            })
+        },
+        || {
+            join(
+                parallel,
+                // Rule 3: Trunk cycle detection
+                || collect(|d| check_trunk_cycle(doc, index, d)),
+                || {
+                    join(
+                        parallel,
+                        // Rule 4: General cycle detection (Kosaraju's SCC)
+                        || {
+                            let severity = cycle_severity(options);
+                            collect(|d| check_general_cycles(doc, index, severity, d))
+                        },
+                        // Rule 5: Orphan nodes
+                        || collect(|d| check_orphan_nodes(doc, index, d)),
+                    )
+                },
+            )
+        },
+    );
 
-    // Rule 5: Orphan nodes
-    check_orphan_nodes(doc, &node_ids, &mut diagnostics);
+    let mut diagnostics = first;
+    diagnostics.extend(trunk);
+    diagnostics.extend(general);
+    diagnostics.extend(orphans);
 
     // Rule 6: Begin-to-end mapping references
-    check_begin_end_mapping(doc, &node_ids, &mut diagnostics);
+    check_begin_end_mapping(doc, node_ids, &mut diagnostics);
+
+    // Rule 7: Self-loops
+    check_self_loops(doc, &mut diagnostics);
+
+    // Rule 8: Root node must exist
+    check_missing_root(doc, node_ids, &mut diagnostics);
+
+    // Rule 9: Multiple trunk edges leaving one node
+    check_ambiguous_trunk(doc, &mut diagnostics);
+
+    // Rule 24: Multiple trunk edges entering one node
+    check_converging_trunk(doc, &mut diagnostics);
+
+    // Rule 25: Trunk edges the trunk from the root never reaches
+    check_disconnected_trunk(doc, index, &mut diagnostics);
+
+    // Rule 10: Tier 2 tree references
+    check_tree_references(doc, node_ids, &mut diagnostics);
+
+    // Rule 26: Tier 2 links between trees
+    check_tree_links(doc, &mut diagnostics);
+
+    // Rule 11: Unknown node/edge status values
+    check_unknown_status(doc, &options.extra_statuses, &mut diagnostics);
+
+    // Rule 12: Branch edges without a label
+    check_unlabeled_branches(doc, &mut diagnostics);
+
+    // Rule 13: Identical edges
+    check_duplicate_edges(doc, &mut diagnostics);
+
+    // Rule 14: Unmarked dead ends
+    check_dead_ends(doc, index, &mut diagnostics);
+
+    // Rule 15: Recognized formatVersion
+    check_format_version(doc, &mut diagnostics);
+
+    // Rule 16: minReaderVersion within what this reader supports
+    check_min_reader_version(doc, &mut diagnostics);
+
+    // Rule 17: Declared features are recognized and cover what the document uses
+    check_features(doc, &options.extra_features, &mut diagnostics);
+
+    // Rule 18: Reachable nodes that a trunk-only walk never visits
+    check_off_trunk(doc, index, &mut diagnostics);
+
+    // Rule 19: Empty or overlong node content
+    check_content_length(doc, options.max_content_len, &mut diagnostics);
+
+    // Rule 23: Metadata keys with the wrong type
+    check_metadata_types(doc, &mut diagnostics);
+
+    // Rule 27: Branches that never reach an ending
+    check_incomplete_branches(doc, index, &mut diagnostics);
 
     diagnostics
 }
@@ -123,6 +810,7 @@ fn check_duplicate_ids(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
                 message: format!("Duplicate node ID '{}'", node.id),
                 location: Location::Node(node.id.clone()),
                 severity: Severity::Error,
+                span: None,
             });
         }
     }
@@ -134,7 +822,7 @@ fn check_dangling_edges(
     node_ids: &HashSet<&str>,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
-    for edge in &doc.edges {
+    for (index, edge) in doc.edges.iter().enumerate() {
         if !node_ids.contains(edge.source.as_str()) {
             diagnostics.push(Diagnostic {
                 rule: Rule::DanglingEdge,
@@ -145,8 +833,10 @@ fn check_dangling_edges(
                 location: Location::Edge {
                     source: edge.source.clone(),
                     target: edge.target.clone(),
+                    index,
                 },
                 severity: Severity::Error,
+                span: None,
             });
         }
         if !node_ids.contains(edge.target.as_str()) {
@@ -159,32 +849,68 @@ fn check_dangling_edges(
                 location: Location::Edge {
                     source: edge.source.clone(),
                     target: edge.target.clone(),
+                    index,
                 },
                 severity: Severity::Error,
+                span: None,
             });
         }
     }
 }
 
-/// Rule 3: Detect cycles in the trunk path via iterative walk.
-fn check_trunk_cycle(
+/// Rule 22: Report each id that edges reference but no node defines, once,
+/// with the number of edges that use it; a self-loop counts once. Ids are
+/// reported in the order they first appear in `edges`.
+fn check_undefined_nodes(
     doc: &TreeDocument,
     node_ids: &HashSet<&str>,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
-    let root_id = match &doc.root_node_id {
-        Some(id) if node_ids.contains(id.as_str()) => id.as_str(),
-        _ => return,
-    };
-
-    // Build trunk adjacency: source -> target for isTrunk edges
-    let mut trunk_next: HashMap<&str, &str> = HashMap::new();
+    let mut missing: Vec<&str> = Vec::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
     for edge in &doc.edges {
-        if edge.is_trunk == Some(true) {
-            trunk_next.insert(edge.source.as_str(), edge.target.as_str());
+        let (source, target) = (edge.source.as_str(), edge.target.as_str());
+        let endpoints = if source == target {
+            &[source][..]
+        } else {
+            &[source, target]
+        };
+        for &id in endpoints {
+            if node_ids.contains(id) {
+                continue;
+            }
+            let count = counts.entry(id).or_insert(0);
+            if *count == 0 {
+                missing.push(id);
+            }
+            *count += 1;
         }
     }
 
+    for id in missing {
+        let count = counts[id];
+        diagnostics.push(Diagnostic {
+            rule: Rule::UndefinedNode,
+            message: format!(
+                "Node '{}' is referenced by {} edge{} but not defined in nodes",
+                id,
+                count,
+                if count == 1 { "" } else { "s" }
+            ),
+            location: Location::Node(id.to_string()),
+            severity: Severity::Error,
+            span: None,
+        });
+    }
+}
+
+/// Rule 3: Detect cycles in the trunk path via iterative walk.
+fn check_trunk_cycle(doc: &TreeDocument, index: &GraphIndex, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(root_id) = index.root(doc) else {
+        return;
+    };
+    let trunk_next = &index.trunk_next;
+
     // Walk trunk from root, tracking visited nodes
     let mut visited = HashSet::new();
     let mut path = Vec::new();
@@ -204,6 +930,7 @@ fn check_trunk_cycle(
                 ),
                 location: Location::Path(cycle_path),
                 severity: Severity::Error,
+                span: None,
             });
             return;
         }
@@ -215,10 +942,19 @@ fn check_trunk_cycle(
     }
 }
 
-/// Rule 4: Detect general cycles using Tarjan's SCC via petgraph.
+/// Most node ids a general-cycle message lists before summarizing the rest;
+/// the diagnostic's `Location::Path` always holds every id.
+const MAX_CYCLE_IDS_IN_MESSAGE: usize = 10;
+
+/// Rule 4: Detect general cycles using Kosaraju's SCC via petgraph, reported
+/// at `severity`.
+///
+/// petgraph's `tarjan_scc` is recursive and overflows the stack on long
+/// chains (tens of thousands of nodes); `kosaraju_scc` is iterative.
 fn check_general_cycles(
     doc: &TreeDocument,
-    _node_ids: &HashSet<&str>,
+    index: &GraphIndex,
+    severity: Severity,
     diagnostics: &mut Vec<Diagnostic>,
 ) {
     // Build petgraph DiGraph
@@ -235,72 +971,44 @@ fn check_general_cycles(
         }
     }
 
-    for edge in &doc.edges {
-        if let (Some(&src), Some(&tgt)) = (
-            id_to_index.get(edge.source.as_str()),
-            id_to_index.get(edge.target.as_str()),
-        ) {
-            graph.add_edge(src, tgt, ());
+    // Walk sources in node order (not map order) so SCC output is deterministic
+    for src in graph.node_indices().collect::<Vec<_>>() {
+        for target in index.outgoing.get(graph[src]).into_iter().flatten() {
+            if let Some(&tgt) = id_to_index.get(target) {
+                graph.add_edge(src, tgt, ());
+            }
         }
     }
 
-    // Tarjan's SCC
-    let sccs = petgraph::algo::tarjan_scc(&graph);
+    let sccs = petgraph::algo::kosaraju_scc(&graph);
     for scc in &sccs {
         if scc.len() > 1 {
             let cycle_ids: Vec<String> = scc
                 .iter()
                 .filter_map(|idx| index_to_id.get(idx).map(|s| s.to_string()))
                 .collect();
+            let shown = cycle_ids.len().min(MAX_CYCLE_IDS_IN_MESSAGE);
+            let mut listed = cycle_ids[..shown].join(", ");
+            if cycle_ids.len() > shown {
+                listed.push_str(&format!(" … and {} more", cycle_ids.len() - shown));
+            }
             diagnostics.push(Diagnostic {
                 rule: Rule::GeneralCycle,
-                message: format!(
-                    "Cycle detected among {} nodes: {}",
-                    cycle_ids.len(),
-                    cycle_ids.join(", ")
-                ),
+                message: format!("Cycle detected among {} nodes: {}", cycle_ids.len(), listed),
                 location: Location::Path(cycle_ids),
-                severity: Severity::Warning,
+                severity: severity.clone(),
+                span: None,
             });
         }
     }
 }
 
 /// Rule 5: Detect orphan nodes unreachable from root via BFS.
-fn check_orphan_nodes(
-    doc: &TreeDocument,
-    node_ids: &HashSet<&str>,
-    diagnostics: &mut Vec<Diagnostic>,
-) {
-    let root_id = match &doc.root_node_id {
-        Some(id) if node_ids.contains(id.as_str()) => id.as_str(),
-        _ => return,
+fn check_orphan_nodes(doc: &TreeDocument, index: &GraphIndex, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(root_id) = index.root(doc) else {
+        return;
     };
-
-    // Build adjacency list (edges are directional: source -> target)
-    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
-    for edge in &doc.edges {
-        adjacency
-            .entry(edge.source.as_str())
-            .or_default()
-            .push(edge.target.as_str());
-    }
-
-    // BFS from root
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back(root_id);
-    visited.insert(root_id);
-
-    while let Some(current) = queue.pop_front() {
-        if let Some(neighbors) = adjacency.get(current) {
-            for &neighbor in neighbors {
-                if visited.insert(neighbor) {
-                    queue.push_back(neighbor);
-                }
-            }
-        }
-    }
+    let visited = reachable_from(root_id, &index.outgoing);
 
     // Any node not visited is an orphan
     for node in &doc.nodes {
@@ -313,11 +1021,33 @@ fn check_orphan_nodes(
                 ),
                 location: Location::Node(node.id.clone()),
                 severity: Severity::Advisory,
+                span: None,
             });
         }
     }
 }
 
+/// Ids reachable from `start` by BFS over `adjacency`, including `start`.
+fn reachable_from<'a>(
+    start: &'a str,
+    adjacency: &HashMap<&'a str, Vec<&'a str>>,
+) -> HashSet<&'a str> {
+    let mut visited = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(current) = queue.pop_front() {
+        if let Some(neighbors) = adjacency.get(current) {
+            for &neighbor in neighbors {
+                if visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    visited
+}
+
 /// Rule 6: If metadata.beginEndMapping is present, validate that beginNodeId
 /// and endNodeId reference existing nodes.
 fn check_begin_end_mapping(
@@ -343,6 +1073,7 @@ fn check_begin_end_mapping(
                 ),
                 location: Location::Root,
                 severity: Severity::Error,
+                span: None,
             });
         }
     }
@@ -357,289 +1088,2258 @@ fn check_begin_end_mapping(
                 ),
                 location: Location::Root,
                 severity: Severity::Error,
+                span: None,
             });
         }
     }
 }
 
-/// Count trunk edges to determine trunk length.
-fn compute_trunk_length(doc: &TreeDocument) -> usize {
-    let root_id = match &doc.root_node_id {
-        Some(id) => id.as_str(),
-        None => return 0,
-    };
+/// Rule 7: Warn on edges whose source and target are the same node.
+///
+/// The general cycle check only reports components of size > 1, so
+/// self-loops are never caught there.
+fn check_self_loops(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if edge.source == edge.target {
+            diagnostics.push(Diagnostic {
+                rule: Rule::SelfLoop,
+                message: format!("Node '{}' has an edge to itself", edge.source),
+                location: Location::Edge {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                    index,
+                },
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Rule 8: Reject a rootNodeId that does not reference an existing node.
+///
+/// A document without a rootNodeId is left alone (it simply has an empty trunk).
+fn check_missing_root(
+    doc: &TreeDocument,
+    node_ids: &HashSet<&str>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if let Some(root_id) = &doc.root_node_id {
+        if !node_ids.contains(root_id.as_str()) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::MissingRoot,
+                message: format!("rootNodeId references nonexistent node '{}'", root_id),
+                location: Location::Node(root_id.clone()),
+                severity: Severity::Error,
+                span: None,
+            });
+        }
+    }
+}
 
-    let mut trunk_next: HashMap<&str, &str> = HashMap::new();
+/// Rule 9: Warn when a node has more than one outgoing trunk edge.
+///
+/// The trunk walk follows the first such edge in document order.
+fn check_ambiguous_trunk(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    let mut trunk_targets: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut sources: Vec<&str> = Vec::new();
     for edge in &doc.edges {
         if edge.is_trunk == Some(true) {
-            trunk_next.insert(edge.source.as_str(), edge.target.as_str());
+            let targets = trunk_targets.entry(edge.source.as_str()).or_default();
+            if targets.is_empty() {
+                sources.push(edge.source.as_str());
+            }
+            targets.push(edge.target.as_str());
         }
     }
 
-    let mut visited = HashSet::new();
-    let mut current = root_id;
-    let mut length = 0;
-
-    while let Some(&next) = trunk_next.get(current) {
-        if !visited.insert(current) {
-            break; // Avoid infinite loop on trunk cycles
+    for source in sources {
+        let targets = &trunk_targets[source];
+        if targets.len() > 1 {
+            diagnostics.push(Diagnostic {
+                rule: Rule::AmbiguousTrunk,
+                message: format!(
+                    "Node '{}' has {} outgoing trunk edges (targets: {}); the trunk follows '{}'",
+                    source,
+                    targets.len(),
+                    targets.join(", "),
+                    targets[0]
+                ),
+                location: Location::Node(source.to_string()),
+                severity: Severity::Warning,
+                span: None,
+            });
         }
-        length += 1;
-        current = next;
     }
-
-    length
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Rule 24: Warn when trunk edges from more than one node enter the same
+/// node, so the trunk converges instead of forming a single chain. The
+/// counterpart of Rule 9 for diverging trunks; duplicate edges from the same
+/// source are left to Rule 13.
+fn check_converging_trunk(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    let mut trunk_sources: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut targets: Vec<&str> = Vec::new();
+    for edge in &doc.edges {
+        if edge.is_trunk == Some(true) {
+            let sources = trunk_sources.entry(edge.target.as_str()).or_default();
+            if sources.is_empty() {
+                targets.push(edge.target.as_str());
+            }
+            if !sources.contains(&edge.source.as_str()) {
+                sources.push(edge.source.as_str());
+            }
+        }
+    }
 
-    #[test]
-    fn valid_minimal_passes() {
+    for target in targets {
+        let sources = &trunk_sources[target];
+        if sources.len() > 1 {
+            diagnostics.push(Diagnostic {
+                rule: Rule::TrunkNotLinear,
+                message: format!(
+                    "Node '{}' is entered by trunk edges from {} nodes (sources: {}); \
+                     the trunk should be a single path",
+                    target,
+                    sources.len(),
+                    sources.join(", ")
+                ),
+                location: Location::Node(target.to_string()),
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Rule 25: Warn about trunk edges whose source no trunk walk reaches, i.e.
+/// stray `isTrunk` markers floating apart from the trunk. Trunk walks start at
+/// the root and, in tier 2 documents, at each tree's root, and follow every
+/// trunk edge. Skipped without a valid root, which Rule 8 reports.
+fn check_disconnected_trunk(
+    doc: &TreeDocument,
+    index: &GraphIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(root_id) = index.root(doc) else {
+        return;
+    };
+
+    let tree_roots = doc.trees.iter().flatten().map(|(_, tree)| tree.root_node_id.as_str());
+    let mut on_trunk = index.trunk_from_root(doc);
+    for start in tree_roots {
+        if !on_trunk.contains(start) {
+            on_trunk.extend(reachable_from(start, &index.trunk_outgoing));
+        }
+    }
+
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if edge.is_trunk == Some(true) && !on_trunk.contains(edge.source.as_str()) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::DisconnectedTrunk,
+                message: format!(
+                    "Trunk edge '{}' -> '{}' is not connected to the trunk from root '{}'",
+                    edge.source, edge.target, root_id
+                ),
+                location: Location::Edge {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                    index,
+                },
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Rule 27: Flag branch edges that lead somewhere no ending can be reached
+/// from: nothing downstream, following every edge, is a node without outgoing
+/// edges whose status marks it as an ending. Such branches usually stop
+/// mid-draft. Only the edge where the story leaves the trunk or a finishing
+/// path is flagged, not every edge further along the unfinished part. The
+/// trunk is the walk from the root that Rule 18 uses. Documents that mark no
+/// endings at all are skipped, since completeness can't be judged there.
+fn check_incomplete_branches(
+    doc: &TreeDocument,
+    index: &GraphIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // Walk edges backwards from every ending at once, marking each node that
+    // can reach one
+    let mut queue: VecDeque<&str> = doc
+        .nodes
+        .iter()
+        .filter(|n| !index.outgoing.contains_key(n.id.as_str()))
+        .filter(|n| n.status.as_ref().is_some_and(NodeStatus::is_ending))
+        .map(|n| n.id.as_str())
+        .collect();
+    if queue.is_empty() {
+        return;
+    }
+    let mut finishes: HashSet<&str> = queue.iter().copied().collect();
+    while let Some(current) = queue.pop_front() {
+        for &source in index.incoming.get(current).into_iter().flatten() {
+            if finishes.insert(source) {
+                queue.push_back(source);
+            }
+        }
+    }
+    let on_trunk = index.trunk_from_root(doc);
+    let node_ids = &index.node_ids;
+
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if edge.is_trunk == Some(true)
+            || !node_ids.contains(edge.source.as_str())
+            || !node_ids.contains(edge.target.as_str())
+            || finishes.contains(edge.target.as_str())
+        {
+            continue;
+        }
+        let source = edge.source.as_str();
+        if !finishes.contains(source) && !on_trunk.contains(source) {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            rule: Rule::IncompleteBranch,
+            message: format!(
+                "Branch '{}' -> '{}' never reaches a node marked as an ending",
+                edge.source, edge.target
+            ),
+            location: Location::Edge {
+                source: edge.source.clone(),
+                target: edge.target.clone(),
+                index,
+            },
+            severity: Severity::Advisory,
+            span: None,
+        });
+    }
+}
+
+/// Rule 10: For tier 2 documents, every tree's rootNodeId must reference an
+/// existing node, and every node's treeIds and edge's treeId must reference
+/// defined trees. Without `trees`, non-empty node treeIds are flagged as
+/// ignored.
+fn check_tree_references(
+    doc: &TreeDocument,
+    node_ids: &HashSet<&str>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let trees = match &doc.trees {
+        Some(trees) => trees,
+        None => {
+            let claimed = doc
+                .nodes
+                .iter()
+                .filter(|n| n.tree_ids.as_ref().is_some_and(|ids| !ids.is_empty()));
+            for node in claimed {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::IgnoredTreeIds,
+                    message: format!(
+                        "Node '{}' has treeIds, but the document defines no trees; \
+                         its tree membership is ignored",
+                        node.id
+                    ),
+                    location: Location::Node(node.id.clone()),
+                    severity: Severity::Warning,
+                    span: None,
+                });
+            }
+            return;
+        }
+    };
+
+    // `trees` is a BTreeMap, so diagnostics come out in tree-id order
+    for (tree_id, tree) in trees {
+        if !node_ids.contains(tree.root_node_id.as_str()) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::DanglingTreeRoot,
+                message: format!(
+                    "Tree '{}' has rootNodeId '{}' which references a nonexistent node",
+                    tree_id, tree.root_node_id
+                ),
+                location: Location::Node(tree.root_node_id.clone()),
+                severity: Severity::Error,
+                span: None,
+            });
+        }
+    }
+
+    for node in &doc.nodes {
+        for tree_id in node.tree_ids.iter().flatten() {
+            if !trees.contains_key(tree_id) {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::UndefinedTree,
+                    message: format!(
+                        "Node '{}' belongs to undefined tree '{}'",
+                        node.id, tree_id
+                    ),
+                    location: Location::Node(node.id.clone()),
+                    severity: Severity::Error,
+                    span: None,
+                });
+            }
+        }
+    }
+
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if let Some(tree_id) = &edge.tree_id {
+            if !trees.contains_key(tree_id) {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::UndefinedTree,
+                    message: format!(
+                        "Edge '{}' -> '{}' belongs to undefined tree '{}'",
+                        edge.source, edge.target, tree_id
+                    ),
+                    location: Location::Edge {
+                        source: edge.source.clone(),
+                        target: edge.target.clone(),
+                        index,
+                    },
+                    severity: Severity::Error,
+                    span: None,
+                });
+            }
+        }
+    }
+}
+
+/// Rule 26: For tier 2 documents, flag `linkType` values outside
+/// `EdgeType::KNOWN`, and edges between nodes that share no tree but carry
+/// no `linkType` (or `type: "cross"`) saying so. Nodes without treeIds are
+/// not considered part of any tree.
+fn check_tree_links(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    if doc.trees.is_none() {
+        return;
+    }
+    let tree_ids: HashMap<&str, &[String]> = doc
+        .nodes
+        .iter()
+        .filter_map(|n| Some((n.id.as_str(), n.tree_ids.as_deref()?)))
+        .collect();
+    let expected = EdgeType::KNOWN
+        .iter()
+        .map(EdgeType::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    for (index, edge) in doc.edges.iter().enumerate() {
+        let location = || Location::Edge {
+            source: edge.source.clone(),
+            target: edge.target.clone(),
+            index,
+        };
+
+        if let Some(link_type) = &edge.link_type {
+            if let EdgeType::Other(_) = link_type {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::UnknownLinkType,
+                    message: format!(
+                        "Edge '{}' -> '{}' has unknown linkType '{}' (expected one of: {})",
+                        edge.source, edge.target, link_type, expected
+                    ),
+                    location: location(),
+                    severity: Severity::Advisory,
+                    span: None,
+                });
+            }
+            continue;
+        }
+        if edge.has_type(&EdgeType::Cross) {
+            continue;
+        }
+
+        let (Some(source_trees), Some(target_trees)) = (
+            tree_ids.get(edge.source.as_str()),
+            tree_ids.get(edge.target.as_str()),
+        ) else {
+            continue;
+        };
+        if !source_trees.iter().any(|id| target_trees.contains(id)) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::UnmarkedCrossTreeEdge,
+                message: format!(
+                    "Edge '{}' -> '{}' links nodes in different trees but has no linkType",
+                    edge.source, edge.target
+                ),
+                location: location(),
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Rule 11: Flag node and edge `status` values outside `NodeStatus::KNOWN` and
+/// the caller's `extra` statuses.
+fn check_unknown_status(
+    doc: &TreeDocument,
+    extra: &HashSet<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let is_known =
+        |s: &NodeStatus| !matches!(s, NodeStatus::Other(_)) || extra.contains(s.as_str());
+    let mut extra: Vec<&str> = extra.iter().map(String::as_str).collect();
+    extra.sort_unstable();
+    let expected = NodeStatus::KNOWN
+        .iter()
+        .map(NodeStatus::as_str)
+        .chain(extra)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    for node in &doc.nodes {
+        if let Some(status) = &node.status {
+            if !is_known(status) {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::UnknownStatus,
+                    message: format!(
+                        "Node '{}' has unknown status '{}' (expected one of: {})",
+                        node.id, status, expected
+                    ),
+                    location: Location::Node(node.id.clone()),
+                    severity: Severity::Advisory,
+                    span: None,
+                });
+            }
+        }
+    }
+
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if let Some(status) = &edge.status {
+            if !is_known(status) {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::UnknownStatus,
+                    message: format!(
+                        "Edge '{}' -> '{}' has unknown status '{}' (expected one of: {})",
+                        edge.source, edge.target, status, expected
+                    ),
+                    location: Location::Edge {
+                        source: edge.source.clone(),
+                        target: edge.target.clone(),
+                        index,
+                    },
+                    severity: Severity::Advisory,
+                    span: None,
+                });
+            }
+        }
+    }
+}
+
+/// Rule 12: Branch (non-trunk) edges should carry a `label` as choice text.
+fn check_unlabeled_branches(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if edge.is_trunk != Some(true) && edge.label.is_none() {
+            diagnostics.push(Diagnostic {
+                rule: Rule::UnlabeledBranch,
+                message: format!(
+                    "Branch edge '{}' -> '{}' has no label",
+                    edge.source, edge.target
+                ),
+                location: Location::Edge {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                    index,
+                },
+                severity: Severity::Advisory,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Rule 13: Flag edges that share an earlier edge's endpoints and trunk flag.
+fn check_duplicate_edges(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    let mut seen = HashSet::new();
+    for (index, edge) in doc.edges.iter().enumerate() {
+        if !seen.insert(edge.dedup_key()) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::DuplicateEdge,
+                message: format!(
+                    "Edge '{}' -> '{}' duplicates an earlier edge",
+                    edge.source, edge.target
+                ),
+                location: Location::Edge {
+                    source: edge.source.clone(),
+                    target: edge.target.clone(),
+                    index,
+                },
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Rule 14: Flag non-root nodes with no outgoing edges unless their status
+/// marks them as an intended ending.
+fn check_dead_ends(doc: &TreeDocument, index: &GraphIndex, diagnostics: &mut Vec<Diagnostic>) {
+    for node in &doc.nodes {
+        if doc.root_node_id.as_deref() == Some(node.id.as_str())
+            || index.outgoing.contains_key(node.id.as_str())
+        {
+            continue;
+        }
+        if node.status.as_ref().is_some_and(NodeStatus::is_ending) {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            rule: Rule::DeadEndNode,
+            message: format!(
+                "Node '{}' has no outgoing edges; set its status to \"ending\" if this is intended",
+                node.id
+            ),
+            location: Location::Node(node.id.clone()),
+            severity: Severity::Advisory,
+            span: None,
+        });
+    }
+}
+
+/// Parse a `major.minor` version string.
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+/// Rule 15: `formatVersion` must be a supported version. A newer minor
+/// version of a supported major is only a warning, since minor revisions are
+/// meant to stay readable by older validators.
+fn check_format_version(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    let version = doc.format_version.as_str();
+    if SUPPORTED_VERSIONS.contains(&version) {
+        return;
+    }
+
+    let supported = SUPPORTED_VERSIONS.join(", ");
+    let (message, severity) = match parse_version(version) {
+        None => (
+            format!("formatVersion '{version}' is not a valid major.minor version"),
+            Severity::Error,
+        ),
+        Some((major, minor))
+            if SUPPORTED_VERSIONS
+                .iter()
+                .filter_map(|v| parse_version(v))
+                .any(|(m, n)| m == major && minor > n) =>
+        {
+            (
+                format!(
+                    "formatVersion '{version}' is newer than this validator supports \
+                     ({supported}); newer features may not be checked"
+                ),
+                Severity::Warning,
+            )
+        }
+        Some(_) => (
+            format!("formatVersion '{version}' is not supported (supported: {supported})"),
+            Severity::Error,
+        ),
+    };
+
+    diagnostics.push(Diagnostic {
+        rule: Rule::UnsupportedVersion,
+        message,
+        location: Location::Root,
+        severity,
+        span: None,
+    });
+}
+
+/// Rule 16: Reject documents whose `minReaderVersion` is newer than
+/// `READER_VERSION`, and warn when the value cannot be parsed.
+fn check_min_reader_version(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(required) = doc.min_reader_version.as_deref() else {
+        return;
+    };
+    let (major, minor) = READER_VERSION;
+
+    match parse_version(required) {
+        None => diagnostics.push(Diagnostic {
+            rule: Rule::MalformedReaderVersion,
+            message: format!(
+                "minReaderVersion '{required}' is not a valid major.minor version; \
+                 reader compatibility was not checked"
+            ),
+            location: Location::Root,
+            severity: Severity::Warning,
+            span: None,
+        }),
+        Some(version) if version > READER_VERSION => diagnostics.push(Diagnostic {
+            rule: Rule::ReaderTooOld,
+            message: format!(
+                "Document requires reader version {required}, but this reader supports \
+                 {major}.{minor}"
+            ),
+            location: Location::Root,
+            severity: Severity::Error,
+            span: None,
+        }),
+        Some(_) => {}
+    }
+}
+
+/// Rule 17: Every entry in `features` must be a known feature flag, built in
+/// or listed in `extra`. When a document declares features, labeled edges
+/// also require the `labels` flag; documents without a `features` list make
+/// no claims and are not checked.
+fn check_features(
+    doc: &TreeDocument,
+    extra: &HashSet<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(features) = &doc.features else {
+        return;
+    };
+
+    for feature in features {
+        if !KNOWN_FEATURES.contains(&feature.as_str()) && !extra.contains(feature) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::UnknownFeature,
+                message: format!(
+                    "Unknown feature '{}'; this validator may not understand what it implies",
+                    feature
+                ),
+                location: Location::Root,
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    }
+
+    let uses_labels = doc.edges.iter().any(|e| e.label.is_some());
+    if uses_labels && !features.iter().any(|f| f == "labels") {
+        diagnostics.push(Diagnostic {
+            rule: Rule::UndeclaredFeature,
+            message: "Edges have labels, but the 'labels' feature is not declared".to_string(),
+            location: Location::Root,
+            severity: Severity::Warning,
+            span: None,
+        });
+    }
+}
+
+/// Rule 18: Flag nodes reachable from the root only by taking at least one
+/// branch edge. Following every trunk edge (not just the first per node)
+/// from the root defines what counts as on-trunk. Unreachable nodes are left
+/// to Rule 5.
+fn check_off_trunk(doc: &TreeDocument, index: &GraphIndex, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(root_id) = index.root(doc) else {
+        return;
+    };
+
+    let reachable = reachable_from(root_id, &index.outgoing);
+    let on_trunk = index.trunk_from_root(doc);
+
+    for node in &doc.nodes {
+        let id = node.id.as_str();
+        if reachable.contains(id) && !on_trunk.contains(id) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::OffTrunk,
+                message: format!("Node '{}' is only reachable through branch edges", node.id),
+                location: Location::Node(node.id.clone()),
+                severity: Severity::Advisory,
+                span: None,
+            });
+        }
+    }
+}
+
+const DOCUMENT_FIELDS: &[&str] = &[
+    "formatVersion",
+    "rootNodeId",
+    "nodes",
+    "edges",
+    "minReaderVersion",
+    "features",
+    "metadata",
+    "trees",
+    "embeddingRef",
+];
+const NODE_FIELDS: &[&str] = &["id", "content", "metadata", "status", "treeIds"];
+const EDGE_FIELDS: &[&str] = &[
+    "source",
+    "target",
+    "isTrunk",
+    "label",
+    "type",
+    "status",
+    "description",
+    "treeId",
+    "linkType",
+];
+
+/// Rule 20: In strict mode, flag keys on the document, its nodes, and its
+/// edges that the format does not define, such as a misspelled `rootNodId`.
+/// Free-form `metadata` objects are not inspected.
+fn check_unknown_fields(value: &serde_json::Value, diagnostics: &mut Vec<Diagnostic>) {
+    let mut check = |object: &serde_json::Value, known: &[&str], pointer: &str| {
+        let Some(object) = object.as_object() else {
+            return;
+        };
+        for key in object.keys().filter(|k| !known.contains(&k.as_str())) {
+            let escaped = key.replace('~', "~0").replace('/', "~1");
+            diagnostics.push(Diagnostic {
+                rule: Rule::UnknownField,
+                message: format!("Unknown field '{key}'"),
+                location: Location::Pointer(format!("{pointer}/{escaped}")),
+                severity: Severity::Warning,
+                span: None,
+            });
+        }
+    };
+
+    check(value, DOCUMENT_FIELDS, "");
+    for (list, known) in [("nodes", NODE_FIELDS), ("edges", EDGE_FIELDS)] {
+        let items = value.get(list).and_then(|v| v.as_array());
+        for (i, item) in items.into_iter().flatten().enumerate() {
+            check(item, known, &format!("/{list}/{i}"));
+        }
+    }
+}
+
+/// Rule 21: Flag a trunk with more than `max_len` edges, for style guides
+/// that cap the length of the main path.
+fn check_trunk_length(
+    trunk_length: usize,
+    max_len: Option<usize>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(max_len) = max_len else {
+        return;
+    };
+    if trunk_length > max_len {
+        diagnostics.push(Diagnostic {
+            rule: Rule::TrunkTooLong,
+            message: format!(
+                "Trunk has {} edges, more than the limit of {}",
+                trunk_length, max_len
+            ),
+            location: Location::Root,
+            severity: Severity::Warning,
+            span: None,
+        });
+    }
+}
+
+/// Rule 19: Flag nodes whose content is empty or whitespace-only, and, when
+/// `max_len` is set, nodes whose content is longer than `max_len` characters.
+fn check_content_length(
+    doc: &TreeDocument,
+    max_len: Option<usize>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for node in &doc.nodes {
+        if node.content.trim().is_empty() {
+            diagnostics.push(Diagnostic {
+                rule: Rule::EmptyContent,
+                message: format!("Node '{}' has no content", node.id),
+                location: Location::Node(node.id.clone()),
+                severity: Severity::Advisory,
+                span: None,
+            });
+            continue;
+        }
+
+        let Some(max_len) = max_len else {
+            continue;
+        };
+        let len = node.content.chars().count();
+        if len > max_len {
+            diagnostics.push(Diagnostic {
+                rule: Rule::ContentTooLong,
+                message: format!(
+                    "Node '{}' content is {} characters long (limit {})",
+                    node.id, len, max_len
+                ),
+                location: Location::Node(node.id.clone()),
+                severity: Severity::Advisory,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Whether a metadata value has the type its key requires.
+type MetadataCheck = fn(&serde_json::Value) -> bool;
+
+/// Metadata keys with a defined meaning, the type they should have, and a
+/// test for it.
+const METADATA_TYPES: &[(&str, &str, MetadataCheck)] = &[
+    ("title", "a string", serde_json::Value::is_string),
+    ("author", "a string", serde_json::Value::is_string),
+    ("created", "a string", serde_json::Value::is_string),
+    ("tags", "an array of strings", |v| {
+        v.as_array().is_some_and(|tags| tags.iter().all(serde_json::Value::is_string))
+    }),
+];
+
+/// Rule 23: Flag `metadata` keys the format defines whose value has the wrong
+/// type. `TreeDocument::metadata_typed` treats such keys as absent.
+fn check_metadata_types(doc: &TreeDocument, diagnostics: &mut Vec<Diagnostic>) {
+    let Some(metadata) = doc.metadata.as_ref().and_then(|m| m.as_object()) else {
+        return;
+    };
+    for (key, expected, is_well_formed) in METADATA_TYPES {
+        let Some(value) = metadata.get(*key) else {
+            continue;
+        };
+        if !is_well_formed(value) {
+            diagnostics.push(Diagnostic {
+                rule: Rule::MalformedMetadata,
+                message: format!("metadata.{key} should be {expected}, found {value}"),
+                location: Location::Pointer(format!("/metadata/{key}")),
+                severity: Severity::Advisory,
+                span: None,
+            });
+        }
+    }
+}
+
+/// Count trunk edges to determine trunk length.
+fn compute_trunk_length(doc: &TreeDocument, index: &GraphIndex) -> usize {
+    let root_id = match &doc.root_node_id {
+        Some(id) => id.as_str(),
+        None => return 0,
+    };
+
+    let trunk_next = &index.trunk_next;
+
+    let mut visited = HashSet::new();
+    let mut current = root_id;
+    let mut length = 0;
+
+    while let Some(&next) = trunk_next.get(current) {
+        if !visited.insert(current) {
+            break; // Avoid infinite loop on trunk cycles
+        }
+        length += 1;
+        current = next;
+    }
+
+    length
+}
+
+/// Length of the longest directed path from the root, in edges.
+///
+/// An iterative DFS from the root gives a post-order; in reverse post-order
+/// every edge points forward except the back edges that close cycles, so
+/// relaxing only forward edges yields the longest path of the acyclic rest.
+fn compute_longest_path_len(doc: &TreeDocument, index: &GraphIndex) -> usize {
+    let Some(root) = index.root(doc) else {
+        return 0;
+    };
+    let targets = |id: &str| {
+        index
+            .outgoing
+            .get(id)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .copied()
+            .filter(|t| index.node_ids.contains(t))
+    };
+
+    let mut post_order = Vec::new();
+    let mut visited = HashSet::from([root]);
+    let mut stack = vec![(root, targets(root))];
+    while let Some((current, children)) = stack.last_mut() {
+        match children.next() {
+            Some(next) => {
+                if visited.insert(next) {
+                    stack.push((next, targets(next)));
+                }
+            }
+            None => {
+                post_order.push(*current);
+                stack.pop();
+            }
+        }
+    }
+
+    post_order.reverse();
+    let position: HashMap<&str, usize> =
+        post_order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let mut depth = vec![0; post_order.len()];
+    for (i, &id) in post_order.iter().enumerate() {
+        for next in targets(id) {
+            let j = position[next];
+            if j > i {
+                depth[j] = depth[j].max(depth[i] + 1);
+            }
+        }
+    }
+
+    depth.into_iter().max().unwrap_or(0)
+}
+
+/// Count weakly-connected components, treating every edge as undirected.
+/// Edges that reference unknown nodes are ignored.
+pub(crate) fn compute_component_count(doc: &TreeDocument) -> usize {
+    let mut adjacency: HashMap<&str, Vec<&str>> = doc
+        .nodes
+        .iter()
+        .map(|n| (n.id.as_str(), Vec::new()))
+        .collect();
+
+    for edge in &doc.edges {
+        let (source, target) = (edge.source.as_str(), edge.target.as_str());
+        if adjacency.contains_key(source) && adjacency.contains_key(target) {
+            adjacency.get_mut(source).unwrap().push(target);
+            adjacency.get_mut(target).unwrap().push(source);
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut components = 0;
+
+    for node in &doc.nodes {
+        if !visited.insert(node.id.as_str()) {
+            continue;
+        }
+        components += 1;
+
+        let mut queue = VecDeque::from([node.id.as_str()]);
+        while let Some(current) = queue.pop_front() {
+            for &neighbor in &adjacency[current] {
+                if visited.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::document_with;
+
+    #[test]
+    fn valid_minimal_passes() {
         let json = include_str!("../../../examples/minimal.tree.json");
         let result = validate_document(json).unwrap();
         assert!(result.is_valid, "errors: {:?}", result.errors);
-        assert_eq!(result.stats.node_count, 3);
-        assert_eq!(result.stats.edge_count, 2);
+        assert_eq!(result.stats.node_count, 3);
+        assert_eq!(result.stats.edge_count, 2);
+        assert_eq!(result.stats.trunk_length, 1);
+        assert_eq!(result.stats.branch_count, 1);
+    }
+
+    #[test]
+    fn valid_story_passes() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "errors: {:?}", result.errors);
+        assert_eq!(result.stats.node_count, 7);
+        assert_eq!(result.stats.tier, 1);
+    }
+
+    #[test]
+    fn duplicate_ids_rejected() {
+        let json = include_str!("../../../examples/invalid/duplicate-ids.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .errors
+            .iter()
+            .any(|d| d.rule == Rule::DuplicateNodeId));
+    }
+
+    #[test]
+    fn dangling_edge_rejected() {
+        let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        let dangling: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|d| d.rule == Rule::DanglingEdge)
+            .collect();
+        assert!(
+            dangling.len() >= 2,
+            "expected at least 2 dangling edge errors, got: {dangling:?}"
+        );
+    }
+
+    #[test]
+    fn undefined_node_reported_once() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "ghost", "label": "One"},
+                {"source": "b", "target": "ghost", "label": "Two"},
+                {"source": "ghost", "target": "b", "label": "Three"},
+                {"source": "ghost", "target": "ghost", "label": "Four"}
+            ]
+        }"#;
+        let options = ValidationOptions {
+            group_dangling_edges: true,
+            ..Default::default()
+        };
+        let result = validate_document_with_options(json, &options).unwrap();
+
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().all(|d| d.rule != Rule::DanglingEdge));
+        let undefined: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|d| d.rule == Rule::UndefinedNode)
+            .collect();
+        assert_eq!(undefined.len(), 1, "{undefined:?}");
+        assert_eq!(undefined[0].location, Location::Node("ghost".to_string()));
+        // The self-loop is one edge, not two references
+        assert!(undefined[0].message.contains("referenced by 4 edges"));
+
+        let per_edge = validate_document(json).unwrap();
+        let dangling = per_edge.errors.iter().filter(|d| d.rule == Rule::DanglingEdge);
+        // Per-edge reporting still flags each missing endpoint
+        assert_eq!(dangling.count(), 5);
+    }
+
+    #[test]
+    fn edge_locations_carry_their_index() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B", "status": "ending"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "ghost", "label": "Boo"},
+                {"source": "a", "target": "b", "isTrunk": true}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+
+        let location = |rule: Rule| {
+            let all = result.errors.iter().chain(&result.warnings);
+            let found: Vec<_> = all.filter(|d| d.rule == rule).collect();
+            assert_eq!(found.len(), 1, "{found:?}");
+            found[0].location.clone()
+        };
+        // Only the second of the two identical a -> b edges is the duplicate
+        let duplicate = location(Rule::DuplicateEdge);
+        assert!(matches!(duplicate, Location::Edge { index: 2, .. }), "{duplicate:?}");
+        assert_eq!(duplicate.to_string(), "edge 'a' -> 'b' (edges[2])");
+        let dangling = location(Rule::DanglingEdge);
+        assert!(matches!(dangling, Location::Edge { index: 1, .. }), "{dangling:?}");
+    }
+
+    #[test]
+    fn trunk_cycle_rejected() {
+        let json = include_str!("../../../examples/invalid/trunk-cycle.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.errors.iter().any(|d| d.rule == Rule::TrunkCycle));
+    }
+
+    #[test]
+    fn missing_fields_rejected() {
+        let json = include_str!("../../../examples/invalid/missing-fields.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .errors
+            .iter()
+            .any(|d| d.rule == Rule::SchemaValidation));
+    }
+
+    #[test]
+    fn general_cycle_warns() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "A"},
+                {"id": "n3", "content": "B"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n2", "target": "n3"},
+                {"source": "n3", "target": "n2"}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "general cycles are warnings, not errors");
+        assert!(result
+            .warnings
+            .iter()
+            .any(|d| d.rule == Rule::GeneralCycle));
+
+        let options = ValidationOptions {
+            cycles_are_errors: true,
+            ..Default::default()
+        };
+        let result = validate_document_with_options(json, &options).unwrap();
+        assert!(!result.is_valid, "cycles_are_errors makes cycles fatal");
+        assert!(result.errors.iter().any(|d| d.rule == Rule::GeneralCycle));
+        assert!(result.warnings.iter().all(|d| d.rule != Rule::GeneralCycle));
+    }
+
+    #[test]
+    fn orphan_node_advisory() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Connected"},
+                {"id": "orphan", "content": "I am unreachable"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "orphans are advisories, not errors");
+        assert!(result
+            .advisories
+            .iter()
+            .any(|d| d.rule == Rule::OrphanNode));
+    }
+
+    #[test]
+    fn single_node_no_edges() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [{"id": "n1", "content": "Only node"}],
+            "edges": []
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.stats.node_count, 1);
+        assert_eq!(result.stats.edge_count, 0);
+        assert_eq!(result.stats.trunk_length, 0);
+    }
+
+    #[test]
+    fn begin_end_mapping_valid() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "start",
+            "metadata": {
+                "beginEndMapping": {
+                    "beginNodeId": "start",
+                    "endNodeId": "end",
+                    "includeDeadEnds": true
+                }
+            },
+            "nodes": [
+                {"id": "start", "content": "Beginning"},
+                {"id": "middle", "content": "Middle"},
+                {"id": "end", "content": "End"},
+                {"id": "dead_end", "content": "Dead end", "status": "dead_end"}
+            ],
+            "edges": [
+                {"source": "start", "target": "middle", "isTrunk": true},
+                {"source": "middle", "target": "end", "isTrunk": true},
+                {"source": "start", "target": "dead_end"}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "errors: {:?}", result.errors);
+    }
+
+    #[test]
+    fn begin_end_mapping_dangling_begin() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "metadata": {
+                "beginEndMapping": {
+                    "beginNodeId": "nonexistent",
+                    "endNodeId": "n1"
+                }
+            },
+            "nodes": [
+                {"id": "n1", "content": "Only node"}
+            ],
+            "edges": []
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .errors
+            .iter()
+            .any(|d| d.rule == Rule::DanglingBeginEnd
+                && d.message.contains("beginNodeId")));
+    }
+
+    #[test]
+    fn begin_end_mapping_dangling_end() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "metadata": {
+                "beginEndMapping": {
+                    "beginNodeId": "n1",
+                    "endNodeId": "nonexistent"
+                }
+            },
+            "nodes": [
+                {"id": "n1", "content": "Only node"}
+            ],
+            "edges": []
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .errors
+            .iter()
+            .any(|d| d.rule == Rule::DanglingBeginEnd
+                && d.message.contains("endNodeId")));
+    }
+
+    #[test]
+    fn no_begin_end_mapping_no_errors() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "metadata": {"title": "No mapping here"},
+            "nodes": [{"id": "n1", "content": "Only node"}],
+            "edges": []
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid);
+        assert!(!result
+            .errors
+            .iter()
+            .any(|d| d.rule == Rule::DanglingBeginEnd));
+    }
+
+    #[test]
+    fn valid_begin_end_example() {
+        let json = include_str!("../../../examples/begin-to-end.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "errors: {:?}", result.errors);
+        assert_eq!(result.stats.tier, 1);
+    }
+
+    #[test]
+    fn self_loop_detected() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Self-looping node"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n1"}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "self-loops are warnings, not errors");
+        let self_loops: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|d| d.rule == Rule::SelfLoop)
+            .collect();
+        assert_eq!(self_loops.len(), 1);
+        assert!(self_loops[0].message.contains("n1"));
+    }
+
+    #[test]
+    fn missing_root_rejected() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "nowhere",
+            "nodes": [{"id": "n1", "content": "Only node"}],
+            "edges": []
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert!(result
+            .errors
+            .iter()
+            .any(|d| d.rule == Rule::MissingRoot && d.message.contains("nowhere")));
+    }
+
+    #[test]
+    fn ambiguous_trunk_warns() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "A"},
+                {"id": "n3", "content": "B"},
+                {"id": "n4", "content": "C"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n3", "isTrunk": true},
+                {"source": "n3", "target": "n4", "isTrunk": true}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid, "ambiguous trunks are warnings, not errors");
+        let ambiguous: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|d| d.rule == Rule::AmbiguousTrunk)
+            .collect();
+        assert_eq!(ambiguous.len(), 1);
+        assert!(ambiguous[0].message.contains("n2, n3"));
+        // First trunk edge in document order wins: n1 -> n2
         assert_eq!(result.stats.trunk_length, 1);
-        assert_eq!(result.stats.branch_count, 1);
     }
 
     #[test]
-    fn valid_story_passes() {
-        let json = include_str!("../../../examples/story.tree.json");
+    fn large_cycle_message_is_truncated() {
+        let ids: Vec<String> = (0..25).map(|i| format!("n{i:02}")).collect();
+        let nodes: Vec<String> = ids
+            .iter()
+            .map(|id| format!(r#"{{"id": "{id}", "content": "{id}"}}"#))
+            .collect();
+        let edges: Vec<String> = (0..ids.len())
+            .map(|i| {
+                let next = &ids[(i + 1) % ids.len()];
+                format!(r#"{{"source": "{}", "target": "{next}", "label": "On"}}"#, ids[i])
+            })
+            .collect();
+        let json = format!(
+            r#"{{"formatVersion": "1.0", "rootNodeId": "n00", "nodes": [{}], "edges": [{}]}}"#,
+            nodes.join(", "),
+            edges.join(", ")
+        );
+
+        let result = validate_document(&json).unwrap();
+        let cycle = result
+            .warnings
+            .iter()
+            .find(|d| d.rule == Rule::GeneralCycle)
+            .unwrap();
+        assert_eq!(
+            cycle.message,
+            "Cycle detected among 25 nodes: n00, n01, n02, n03, n04, n05, n06, n07, n08, n09 \
+             … and 15 more"
+        );
+        let Location::Path(path) = &cycle.location else {
+            panic!("expected a path location, got {:?}", cycle.location);
+        };
+        assert_eq!(path.len(), 25);
+    }
+
+    #[test]
+    fn converging_trunk_warns() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "A"},
+                {"id": "n3", "content": "B"},
+                {"id": "n4", "content": "End"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n3", "label": "Detour"},
+                {"source": "n2", "target": "n4", "isTrunk": true},
+                {"source": "n3", "target": "n4", "isTrunk": true}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid);
+        let converging: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|d| d.rule == Rule::TrunkNotLinear)
+            .collect();
+        assert_eq!(converging.len(), 1);
+        assert_eq!(converging[0].location, Location::Node("n4".to_string()));
+        assert!(converging[0].message.contains("(sources: n2, n3)"));
+
+        let story = include_str!("../../../examples/story.tree.json");
+        let result = validate_document(story).unwrap();
+        assert!(!result.warnings.iter().any(|d| d.rule == Rule::TrunkNotLinear));
+    }
+
+    #[test]
+    fn floating_trunk_segment_warns() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "End"},
+                {"id": "side", "content": "Side path"},
+                {"id": "more", "content": "More side path"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "side", "label": "Detour"},
+                {"source": "side", "target": "more", "isTrunk": true}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(result.is_valid);
+        let disconnected: Vec<_> = result
+            .warnings
+            .iter()
+            .filter(|d| d.rule == Rule::DisconnectedTrunk)
+            .collect();
+        assert_eq!(disconnected.len(), 1);
+        assert_eq!(
+            disconnected[0].location,
+            Location::Edge {
+                source: "side".to_string(),
+                target: "more".to_string(),
+                index: 2,
+            }
+        );
+        assert_eq!(
+            disconnected[0].message,
+            "Trunk edge 'side' -> 'more' is not connected to the trunk from root 'n1'"
+        );
+    }
+
+    #[test]
+    fn tree_roots_start_their_own_trunks() {
+        for json in [
+            include_str!("../../../examples/story.tree.json"),
+            include_str!("../../../examples/multi-tree.tree.json"),
+            include_str!("../../../examples/anthology.tree.json"),
+        ] {
+            let result = validate_document(json).unwrap();
+            assert!(!result.warnings.iter().any(|d| d.rule == Rule::DisconnectedTrunk));
+        }
+    }
+
+    #[test]
+    fn valid_multi_tree_example() {
+        let json = include_str!("../../../examples/multi-tree.tree.json");
         let result = validate_document(json).unwrap();
         assert!(result.is_valid, "errors: {:?}", result.errors);
-        assert_eq!(result.stats.node_count, 7);
-        assert_eq!(result.stats.tier, 1);
+        assert_eq!(result.stats.tier, 2);
     }
 
     #[test]
-    fn duplicate_ids_rejected() {
-        let json = include_str!("../../../examples/invalid/duplicate-ids.tree.json");
+    fn dangling_tree_root_rejected() {
+        let json = include_str!("../../../examples/invalid/dangling-tree-root.tree.json");
         let result = validate_document(json).unwrap();
         assert!(!result.is_valid);
         assert!(result
             .errors
             .iter()
-            .any(|d| d.rule == Rule::DuplicateNodeId));
+            .any(|d| d.rule == Rule::DanglingTreeRoot && d.message.contains("'cellar'")));
     }
 
     #[test]
-    fn dangling_edge_rejected() {
-        let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+    fn undefined_tree_rejected() {
+        let json = include_str!("../../../examples/invalid/undefined-tree.tree.json");
         let result = validate_document(json).unwrap();
         assert!(!result.is_valid);
-        let dangling: Vec<_> = result
+        let undefined: Vec<_> = result
             .errors
             .iter()
-            .filter(|d| d.rule == Rule::DanglingEdge)
+            .filter(|d| d.rule == Rule::UndefinedTree)
             .collect();
-        assert!(
-            dangling.len() >= 2,
-            "expected at least 2 dangling edge errors, got: {dangling:?}"
+        assert_eq!(undefined.len(), 1);
+        assert!(undefined[0].message.contains("'attic'"));
+    }
+
+    fn incomplete_branches(json: &str) -> Vec<(String, String)> {
+        validate_document(json)
+            .unwrap()
+            .advisories
+            .into_iter()
+            .filter(|d| d.rule == Rule::IncompleteBranch)
+            .map(|d| match d.location {
+                Location::Edge { source, target, .. } => (source, target),
+                other => panic!("unexpected location {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn branch_that_dead_ends_mid_draft_is_incomplete() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "start",
+            "nodes": [
+                {"id": "start", "content": "Start"},
+                {"id": "end", "content": "End", "status": "ending"},
+                {"id": "side", "content": "Side path"},
+                {"id": "rejoin", "content": "Back to the road"},
+                {"id": "draft", "content": "TODO"},
+                {"id": "draft2", "content": "TODO too"}
+            ],
+            "edges": [
+                {"source": "start", "target": "end", "isTrunk": true},
+                {"source": "start", "target": "side", "label": "Wander"},
+                {"source": "side", "target": "end"},
+                {"source": "start", "target": "rejoin", "label": "Turn back"},
+                {"source": "rejoin", "target": "start"},
+                {"source": "start", "target": "draft", "label": "Unwritten"},
+                {"source": "draft", "target": "draft2"}
+            ]
+        }"#;
+        // `side` reaches the ending directly and `rejoin` by way of the trunk;
+        // only the edge into the draft is flagged, not the one inside it
+        assert_eq!(
+            incomplete_branches(json),
+            [("start".to_string(), "draft".to_string())]
         );
     }
 
     #[test]
-    fn trunk_cycle_rejected() {
-        let json = include_str!("../../../examples/invalid/trunk-cycle.tree.json");
+    fn stray_trunk_edges_do_not_start_incomplete_branches() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "start",
+            "nodes": [
+                {"id": "start", "content": "Start"},
+                {"id": "end", "content": "End", "status": "ending"},
+                {"id": "draft", "content": "TODO"},
+                {"id": "draft2", "content": "TODO too"},
+                {"id": "draft3", "content": "TODO as well"}
+            ],
+            "edges": [
+                {"source": "start", "target": "end", "isTrunk": true},
+                {"source": "start", "target": "draft", "label": "Unwritten"},
+                {"source": "draft", "target": "draft2", "isTrunk": true},
+                {"source": "draft2", "target": "draft3", "label": "Later"}
+            ]
+        }"#;
+        // The trunk edge out of `draft` is not on the trunk from the root
+        // (Rule 25), so the branch leaving `draft2` is part of the same
+        // unfinished branch rather than a new one
+        let result = validate_document(json).unwrap();
+        assert!(result.warnings.iter().any(|d| d.rule == Rule::DisconnectedTrunk));
+        assert_eq!(
+            incomplete_branches(json),
+            [("start".to_string(), "draft".to_string())]
+        );
+    }
+
+    #[test]
+    fn incomplete_branches_need_marked_endings() {
+        // Nothing is marked as an ending, so completeness isn't judged
+        let json = include_str!("../../../examples/story.tree.json");
+        assert!(incomplete_branches(json).is_empty());
+
+        // The rejection branch only rejoins the trunk, whose end isn't marked
+        let json = include_str!("../../../examples/begin-to-end.tree.json");
+        assert_eq!(
+            incomplete_branches(json),
+            [(
+                "rejected_by_store".to_string(),
+                "fix_and_resubmit".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn undefined_edge_tree_rejected() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "trees": {"main": {"rootNodeId": "n1"}},
+            "nodes": [
+                {"id": "n1", "content": "Start", "treeIds": ["main"]},
+                {"id": "n2", "content": "End", "treeIds": ["main"]}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true, "treeId": "attic"}
+            ]
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(!result.is_valid);
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert_eq!(result.errors[0].rule, Rule::UndefinedTree);
+        assert!(result.errors[0].message.contains("'attic'"));
+        assert!(matches!(result.errors[0].location, Location::Edge { index: 0, .. }));
+    }
+
+    fn linked_trees(link: &str) -> String {
+        format!(
+            r#"{{
+            "formatVersion": "1.0",
+            "rootNodeId": "a1",
+            "features": ["labels"],
+            "trees": {{"a": {{"rootNodeId": "a1"}}, "b": {{"rootNodeId": "b1"}}}},
+            "nodes": [
+                {{"id": "a1", "content": "A", "treeIds": ["a"]}},
+                {{"id": "b1", "content": "B", "treeIds": ["b"], "status": "ending"}}
+            ],
+            "edges": [
+                {{"source": "a1", "target": "b1", "label": "Over"{link}}}
+            ]
+        }}"#
+        )
+    }
+
+    #[test]
+    fn cross_tree_links() {
+        let rules = |link: &str| -> Vec<Rule> {
+            let result = validate_document(&linked_trees(link)).unwrap();
+            assert!(result.is_valid, "{:?}", result.errors);
+            result
+                .warnings
+                .into_iter()
+                .chain(result.advisories)
+                .map(|d| d.rule)
+                .filter(|r| matches!(r, Rule::UnknownLinkType | Rule::UnmarkedCrossTreeEdge))
+                .collect()
+        };
+
+        assert!(rules(r#", "linkType": "cross""#).is_empty());
+        assert!(rules(r#", "type": "cross""#).is_empty());
+        assert_eq!(rules(""), [Rule::UnmarkedCrossTreeEdge]);
+        assert_eq!(rules(r#", "linkType": "wormhole""#), [Rule::UnknownLinkType]);
+
+        // The bundled multi-tree example marks both of its cross-tree edges
+        let json = include_str!("../../../examples/multi-tree.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(result
+            .warnings
+            .iter()
+            .chain(&result.advisories)
+            .all(|d| d.rule != Rule::UnmarkedCrossTreeEdge && d.rule != Rule::UnknownLinkType));
+    }
+
+    #[test]
+    fn tree_ids_without_trees_warn() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start", "treeIds": ["main"]},
+                {"id": "n2", "content": "End", "status": "ending"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true}
+            ]
+        }"#;
         let result = validate_document(json).unwrap();
-        assert!(!result.is_valid);
-        assert!(result.errors.iter().any(|d| d.rule == Rule::TrunkCycle));
-    }
+        assert!(result.is_valid);
+        assert_eq!(result.warnings.len(), 1, "{:?}", result.warnings);
+        assert_eq!(result.warnings[0].rule, Rule::IgnoredTreeIds);
+        assert_eq!(result.warnings[0].location, Location::Node("n1".to_string()));
 
-    #[test]
-    fn missing_fields_rejected() {
-        let json = include_str!("../../../examples/invalid/missing-fields.tree.json");
+        // An empty list claims no membership, so there is nothing to ignore
+        let json = json.replace(r#""treeIds": ["main"]"#, r#""treeIds": []"#);
+        let result = validate_document(&json).unwrap();
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+
+        // With `trees` defined, membership is checked instead of ignored
+        let json = include_str!("../../../examples/invalid/undefined-node-tree.tree.json");
         let result = validate_document(json).unwrap();
         assert!(!result.is_valid);
-        assert!(result
+        assert!(result.warnings.iter().all(|d| d.rule != Rule::IgnoredTreeIds));
+        let undefined: Vec<_> = result
             .errors
             .iter()
-            .any(|d| d.rule == Rule::SchemaValidation));
+            .map(|d| (d.rule.clone(), d.location.clone()))
+            .collect();
+        assert_eq!(
+            undefined,
+            [
+                (Rule::UndefinedTree, Location::Node("market".to_string())),
+                (Rule::UndefinedTree, Location::Node("lamp".to_string())),
+            ]
+        );
     }
 
     #[test]
-    fn general_cycle_warns() {
+    fn unknown_status_advisory() {
         let json = r#"{
             "formatVersion": "1.0",
             "rootNodeId": "n1",
             "nodes": [
-                {"id": "n1", "content": "Start"},
-                {"id": "n2", "content": "A"},
-                {"id": "n3", "content": "B"}
+                {"id": "n1", "content": "Start", "status": "active"},
+                {"id": "n2", "content": "Next", "status": "bogus"}
             ],
             "edges": [
-                {"source": "n1", "target": "n2", "isTrunk": true},
-                {"source": "n2", "target": "n3"},
-                {"source": "n3", "target": "n2"}
+                {"source": "n1", "target": "n2", "isTrunk": true, "status": "weird"}
             ]
         }"#;
         let result = validate_document(json).unwrap();
-        assert!(result.is_valid, "general cycles are warnings, not errors");
-        assert!(result
-            .warnings
+        assert!(result.is_valid, "unknown statuses are advisories, not errors");
+        let unknown: Vec<_> = result
+            .advisories
             .iter()
-            .any(|d| d.rule == Rule::GeneralCycle));
+            .filter(|d| d.rule == Rule::UnknownStatus)
+            .collect();
+        assert_eq!(unknown.len(), 2);
+        assert!(unknown[0].message.contains("'bogus'"));
+        assert!(unknown[1].message.contains("'weird'"));
     }
 
     #[test]
-    fn orphan_node_advisory() {
+    fn known_status_no_advisory() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [{"id": "n1", "content": "Start", "status": "active"}],
+            "edges": []
+        }"#;
+        let result = validate_document(json).unwrap();
+        assert!(!result
+            .advisories
+            .iter()
+            .any(|d| d.rule == Rule::UnknownStatus));
+    }
+
+    #[test]
+    fn unlabeled_branch_advisory() {
         let json = r#"{
             "formatVersion": "1.0",
             "rootNodeId": "n1",
             "nodes": [
                 {"id": "n1", "content": "Start"},
-                {"id": "n2", "content": "Connected"},
-                {"id": "orphan", "content": "I am unreachable"}
+                {"id": "n2", "content": "Trunk"},
+                {"id": "n3", "content": "Labeled"},
+                {"id": "n4", "content": "Unlabeled"}
             ],
             "edges": [
-                {"source": "n1", "target": "n2", "isTrunk": true}
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n3", "label": "Go left"},
+                {"source": "n1", "target": "n4"}
             ]
         }"#;
         let result = validate_document(json).unwrap();
-        assert!(result.is_valid, "orphans are advisories, not errors");
-        assert!(result
+        let unlabeled: Vec<_> = result
             .advisories
             .iter()
-            .any(|d| d.rule == Rule::OrphanNode));
+            .filter(|d| d.rule == Rule::UnlabeledBranch)
+            .collect();
+        assert_eq!(unlabeled.len(), 1);
+        assert!(unlabeled[0].message.contains("'n1' -> 'n4'"));
     }
 
     #[test]
-    fn single_node_no_edges() {
+    fn component_count_disjoint_subgraphs() {
         let json = r#"{
             "formatVersion": "1.0",
             "rootNodeId": "n1",
-            "nodes": [{"id": "n1", "content": "Only node"}],
-            "edges": []
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Next"},
+                {"id": "n3", "content": "Island"},
+                {"id": "n4", "content": "Island child"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n3", "target": "n4", "label": "Onward"}
+            ]
         }"#;
         let result = validate_document(json).unwrap();
-        assert!(result.is_valid);
-        assert_eq!(result.stats.node_count, 1);
-        assert_eq!(result.stats.edge_count, 0);
-        assert_eq!(result.stats.trunk_length, 0);
+        assert_eq!(result.stats.component_count, 2);
     }
 
     #[test]
-    fn begin_end_mapping_valid() {
+    fn longest_path_len_follows_longest_branch() {
         let json = r#"{
             "formatVersion": "1.0",
-            "rootNodeId": "start",
-            "metadata": {
-                "beginEndMapping": {
-                    "beginNodeId": "start",
-                    "endNodeId": "end",
-                    "includeDeadEnds": true
-                }
-            },
+            "rootNodeId": "a",
             "nodes": [
-                {"id": "start", "content": "Beginning"},
-                {"id": "middle", "content": "Middle"},
-                {"id": "end", "content": "End"},
-                {"id": "dead_end", "content": "Dead end", "status": "dead_end"}
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"},
+                {"id": "c", "content": "C"},
+                {"id": "d", "content": "D"},
+                {"id": "e", "content": "E"}
             ],
             "edges": [
-                {"source": "start", "target": "middle", "isTrunk": true},
-                {"source": "middle", "target": "end", "isTrunk": true},
-                {"source": "start", "target": "dead_end"}
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "c", "label": "Detour"},
+                {"source": "c", "target": "d", "label": "On"},
+                {"source": "c", "target": "e", "label": "Off"},
+                {"source": "d", "target": "e", "label": "Further"}
             ]
         }"#;
-        let result = validate_document(json).unwrap();
-        assert!(result.is_valid, "errors: {:?}", result.errors);
+        let stats = validate_document(json).unwrap().stats;
+        assert_eq!(stats.trunk_length, 1);
+        assert_eq!(stats.longest_path_len, 3);
+        assert_eq!(stats.max_out_degree, 2);
     }
 
     #[test]
-    fn begin_end_mapping_dangling_begin() {
+    fn longest_path_len_ignores_cycle_edges() {
+        let json = include_str!("../../../examples/invalid/general-cycle.tree.json");
+        let stats = validate_document(json).unwrap().stats;
+        // n1 -> n2 -> n3 -> n4; the edge back to n2 does not extend the path.
+        assert_eq!(stats.longest_path_len, 3);
+        assert_eq!(stats.max_out_degree, 2);
+    }
+
+    #[test]
+    fn duplicate_edge_warns() {
         let json = r#"{
             "formatVersion": "1.0",
             "rootNodeId": "n1",
-            "metadata": {
-                "beginEndMapping": {
-                    "beginNodeId": "nonexistent",
-                    "endNodeId": "n1"
-                }
-            },
             "nodes": [
-                {"id": "n1", "content": "Only node"}
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Next"}
             ],
-            "edges": []
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n2", "isTrunk": true}
+            ]
         }"#;
         let result = validate_document(json).unwrap();
-        assert!(!result.is_valid);
-        assert!(result
-            .errors
-            .iter()
-            .any(|d| d.rule == Rule::DanglingBeginEnd
-                && d.message.contains("beginNodeId")));
+        assert!(result.is_valid);
+        assert_eq!(result.warnings.len(), 2, "{:?}", result.warnings);
+        assert!(result.warnings.iter().any(|d| d.rule == Rule::DuplicateEdge));
+    }
+
+    /// A 50k-node trunk with a branch every tenth node, a few orphans, a
+    /// dangling edge, and a cycle, so every parallel check has output.
+    fn large_document() -> TreeDocument {
+        use crate::types::{Edge, Node};
+
+        const N: usize = 50_000;
+        let node = |i: usize| Node {
+            id: format!("n{i}"),
+            content: format!("Node {i}"),
+            metadata: None,
+            status: None,
+            tree_ids: None,
+            extra: Default::default(),
+        };
+        let edge = |source: String, target: String, is_trunk: bool| Edge {
+            source,
+            target,
+            is_trunk: is_trunk.then_some(true),
+            label: None,
+            edge_type: None,
+            status: None,
+            description: None,
+            tree_id: None,
+            link_type: None,
+            extra: Default::default(),
+        };
+
+        let mut nodes: Vec<Node> = (0..N).map(node).collect();
+        nodes.push(node(7)); // duplicate id
+        let mut edges = Vec::new();
+        for i in 0..N - 100 {
+            edges.push(edge(format!("n{i}"), format!("n{}", i + 1), true));
+            if i % 10 == 0 && i + 5 < N - 100 {
+                edges.push(edge(format!("n{i}"), format!("n{}", i + 5), false));
+            }
+        }
+        edges.push(edge("n500".into(), "n100".into(), false)); // cycle
+        edges.push(edge("n1".into(), "missing".into(), false)); // dangling
+
+        TreeDocument {
+            format_version: "1.0".into(),
+            root_node_id: Some("n0".into()),
+            nodes,
+            edges,
+            min_reader_version: None,
+            features: None,
+            metadata: None,
+            trees: None,
+            embedding_ref: None,
+            extra: Default::default(),
+        }
     }
 
     #[test]
-    fn begin_end_mapping_dangling_end() {
+    fn parallel_checks_match_sequential() {
+        let doc = large_document();
+
+        let index = GraphIndex::new(&doc);
+        let sequential = run_semantic_checks(&doc, &index, &ValidationOptions::default(), false);
+        let parallel = run_semantic_checks(&doc, &index, &ValidationOptions::default(), true);
+        assert_eq!(parallel, sequential);
+        for rule in [
+            Rule::DuplicateNodeId,
+            Rule::DanglingEdge,
+            Rule::GeneralCycle,
+            Rule::OrphanNode,
+        ] {
+            assert!(parallel.iter().any(|d| d.rule == rule), "no {rule} diagnostics");
+        }
+    }
+
+    #[test]
+    fn graph_rules_output_is_pinned() {
+        // Exercises every rule that reads the shared graph index. The expected
+        // strings were captured before the index was introduced.
         let json = r#"{
             "formatVersion": "1.0",
-            "rootNodeId": "n1",
-            "metadata": {
-                "beginEndMapping": {
-                    "beginNodeId": "n1",
-                    "endNodeId": "nonexistent"
-                }
-            },
+            "rootNodeId": "a",
             "nodes": [
-                {"id": "n1", "content": "Only node"}
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"},
+                {"id": "c", "content": "C"},
+                {"id": "d", "content": "D"},
+                {"id": "x", "content": "Orphan"},
+                {"id": "y", "content": "Orphan child", "status": "ending"}
             ],
-            "edges": []
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "b", "target": "c", "isTrunk": true},
+                {"source": "c", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "d", "label": "Side"},
+                {"source": "d", "target": "a", "label": "Back"},
+                {"source": "x", "target": "y", "label": "Down"}
+            ]
         }"#;
         let result = validate_document(json).unwrap();
-        assert!(!result.is_valid);
-        assert!(result
+        let rendered: Vec<String> = result
             .errors
             .iter()
-            .any(|d| d.rule == Rule::DanglingBeginEnd
-                && d.message.contains("endNodeId")));
+            .chain(&result.warnings)
+            .chain(&result.advisories)
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            rendered,
+            [
+                "[error] TDF004 trunk-cycle: Trunk path contains a cycle: b -> c \
+                 (at path: b -> c)",
+                "[warning] TDF005 general-cycle: Cycle detected among 2 nodes: b, c \
+                 (at path: b -> c)",
+                "[warning] TDF005 general-cycle: Cycle detected among 2 nodes: a, d \
+                 (at path: a -> d)",
+                "[warning] TDF029 trunk-not-linear: Node 'b' is entered by trunk edges from \
+                 2 nodes (sources: a, c); the trunk should be a single path (at node 'b')",
+                "[advisory] TDF006 orphan-node: Node 'x' is not reachable from root node 'a' \
+                 (at node 'x')",
+                "[advisory] TDF006 orphan-node: Node 'y' is not reachable from root node 'a' \
+                 (at node 'y')",
+                "[advisory] TDF022 off-trunk: Node 'd' is only reachable through branch edges \
+                 (at node 'd')",
+                "[advisory] TDF034 incomplete-branch: Branch 'a' -> 'd' never reaches a node \
+                 marked as an ending (at edge 'a' -> 'd' (edges[3]))",
+            ]
+        );
+        assert_eq!(result.stats.trunk_length, 3);
     }
 
     #[test]
-    fn no_begin_end_mapping_no_errors() {
+    fn allowed_rules_are_not_reported() {
+        let options = ValidationOptions {
+            allowed_rules: HashSet::from([Rule::OrphanNode, Rule::DanglingEdge]),
+            ..Default::default()
+        };
+
+        let json = include_str!("../../../examples/invalid/orphan-node.tree.json");
+        let result = validate_document_with_options(json, &options).unwrap();
+        assert!(!result.advisories.is_empty());
+        assert!(!result.advisories.iter().any(|d| d.rule == Rule::OrphanNode));
+
+        let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+        assert!(!validate_document(json).unwrap().is_valid);
+        let result = validate_document_with_options(json, &options).unwrap();
+        assert!(result.is_valid, "allowed errors do not invalidate: {:?}", result.errors);
+    }
+
+    #[test]
+    fn extra_statuses_and_features_are_known() {
         let json = r#"{
             "formatVersion": "1.0",
+            "features": ["labels", "audio"],
             "rootNodeId": "n1",
-            "metadata": {"title": "No mapping here"},
-            "nodes": [{"id": "n1", "content": "Only node"}],
-            "edges": []
+            "nodes": [
+                {"id": "n1", "content": "Start", "status": "review"},
+                {"id": "n2", "content": "End", "status": "ending"}
+            ],
+            "edges": [{"source": "n1", "target": "n2", "isTrunk": true}]
         }"#;
+        let rules = |result: &ValidationResult| -> Vec<Rule> {
+            result
+                .warnings
+                .iter()
+                .chain(&result.advisories)
+                .map(|d| d.rule.clone())
+                .collect()
+        };
+
+        let result = validate_document(json).unwrap();
+        assert_eq!(rules(&result), [Rule::UnknownFeature, Rule::UnknownStatus]);
+
+        let options = ValidationOptions {
+            extra_statuses: HashSet::from(["review".to_string()]),
+            extra_features: HashSet::from(["audio".to_string()]),
+            ..Default::default()
+        };
+        let result = validate_document_with_options(json, &options).unwrap();
+        assert!(rules(&result).is_empty(), "{:?}", rules(&result));
+    }
+
+    #[test]
+    fn empty_content_is_advisory() {
+        let json = include_str!("../../../examples/empty-document.tree.json");
         let result = validate_document(json).unwrap();
         assert!(result.is_valid);
-        assert!(!result
-            .errors
+        let empty: Vec<_> = result
+            .advisories
             .iter()
-            .any(|d| d.rule == Rule::DanglingBeginEnd));
+            .filter(|d| d.rule == Rule::EmptyContent)
+            .collect();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0].location, Location::Node("n1".to_string()));
+
+        let whitespace = json.replace("\"content\": \"\"", "\"content\": \"  \\n \"");
+        let result = validate_document(&whitespace).unwrap();
+        assert!(result.advisories.iter().any(|d| d.rule == Rule::EmptyContent));
     }
 
     #[test]
-    fn valid_begin_end_example() {
-        let json = include_str!("../../../examples/begin-to-end.tree.json");
+    fn metadata_with_wrong_types_is_advisory() {
+        let malformed = |result: &ValidationResult| -> Vec<(String, String)> {
+            result
+                .advisories
+                .iter()
+                .filter(|d| d.rule == Rule::MalformedMetadata)
+                .map(|d| (d.location.to_string(), d.message.clone()))
+                .collect()
+        };
+
+        let json = include_str!("../../../examples/story.tree.json");
+        assert!(malformed(&validate_document(json).unwrap()).is_empty());
+
+        let json = json.replace(
+            "\"title\": \"The Enchanted Garden\"",
+            "\"title\": 42, \"tags\": [\"garden\", null], \"custom\": 1",
+        );
+        let result = validate_document(&json).unwrap();
+        assert_eq!(
+            malformed(&result),
+            [
+                (
+                    "/metadata/title".to_string(),
+                    "metadata.title should be a string, found 42".to_string()
+                ),
+                (
+                    "/metadata/tags".to_string(),
+                    "metadata.tags should be an array of strings, found [\"garden\",null]"
+                        .to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlong_content_needs_a_limit() {
+        let json = include_str!("../../../examples/minimal.tree.json");
+        let too_long = |result: &ValidationResult| -> Vec<Location> {
+            result
+                .advisories
+                .iter()
+                .filter(|d| d.rule == Rule::ContentTooLong)
+                .map(|d| d.location.clone())
+                .collect()
+        };
+        assert!(too_long(&validate_document(json).unwrap()).is_empty());
+
+        // Node contents are 40, 54 and 47 characters long.
+        let options = ValidationOptions {
+            max_content_len: Some(45),
+            ..Default::default()
+        };
+        let result = validate_document_with_options(json, &options).unwrap();
+        assert_eq!(
+            too_long(&result),
+            [Location::Node("n2".to_string()), Location::Node("n3".to_string())]
+        );
+        assert!(result
+            .advisories
+            .iter()
+            .any(|d| d.message.contains("54 characters long (limit 45)")));
+    }
+
+    #[test]
+    fn branch_only_subtree_is_off_trunk() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B", "status": "ending"},
+                {"id": "side", "content": "Side"},
+                {"id": "deeper", "content": "Deeper", "status": "ending"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "side", "label": "Detour"},
+                {"source": "side", "target": "deeper", "isTrunk": true}
+            ]
+        }"#;
         let result = validate_document(json).unwrap();
-        assert!(result.is_valid, "errors: {:?}", result.errors);
-        assert_eq!(result.stats.tier, 1);
+        let rules_for = |id: &str| -> Vec<Rule> {
+            result
+                .advisories
+                .iter()
+                .filter(|d| d.location == Location::Node(id.to_string()))
+                .map(|d| d.rule.clone())
+                .collect()
+        };
+        assert_eq!(rules_for("side"), [Rule::OffTrunk]);
+        // A trunk edge inside a branch does not bring it back onto the trunk.
+        assert_eq!(rules_for("deeper"), [Rule::OffTrunk]);
+        assert!(rules_for("b").is_empty());
+        assert!(!result.advisories.iter().any(|d| d.rule == Rule::OrphanNode));
     }
 
     #[test]
-    fn self_loop_detected() {
+    fn dead_end_advisory_skips_intended_endings() {
         let json = r#"{
             "formatVersion": "1.0",
             "rootNodeId": "n1",
             "nodes": [
-                {"id": "n1", "content": "Self-looping node"}
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "The end", "status": "ending"},
+                {"id": "n3", "content": "Also over", "status": "terminal"},
+                {"id": "n4", "content": "Known dead end", "status": "dead_end"},
+                {"id": "n5", "content": "Forgot to continue"},
+                {"id": "n6", "content": "Draft", "status": "draft"}
             ],
             "edges": [
-                {"source": "n1", "target": "n1"}
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n3", "label": "a"},
+                {"source": "n1", "target": "n4", "label": "b"},
+                {"source": "n1", "target": "n5", "label": "c"},
+                {"source": "n1", "target": "n6", "label": "d"}
             ]
         }"#;
         let result = validate_document(json).unwrap();
-        // Self-loop is a general cycle (SCC of size 1 with self-edge isn't detected by tarjan_scc
-        // as len>1, but we should at least not crash)
+        let dead_ends: Vec<_> = result
+            .advisories
+            .iter()
+            .filter(|d| d.rule == Rule::DeadEndNode)
+            .map(|d| d.location.to_string())
+            .collect();
+        assert_eq!(dead_ends, ["node 'n5'", "node 'n6'"]);
+    }
+
+    #[test]
+    fn dead_end_root_is_exempt() {
+        let json = include_str!("../../../examples/empty-document.tree.json");
+        let result = validate_document(json).unwrap();
+        assert!(!result.advisories.iter().any(|d| d.rule == Rule::DeadEndNode));
+    }
+
+    fn version_doc(version: &str) -> String {
+        document_with(serde_json::json!({ "formatVersion": version }))
+    }
+
+    fn version_diagnostics(result: &ValidationResult) -> Vec<&Diagnostic> {
+        result
+            .errors
+            .iter()
+            .chain(&result.warnings)
+            .filter(|d| d.rule == Rule::UnsupportedVersion)
+            .collect()
+    }
+
+    #[test]
+    fn supported_version_passes() {
+        let result = validate_document(&version_doc("1.0")).unwrap();
+        assert!(result.is_valid);
+        assert!(version_diagnostics(&result).is_empty());
+    }
+
+    #[test]
+    fn newer_minor_version_warns() {
+        let result = validate_document(&version_doc("1.3")).unwrap();
+        assert!(result.is_valid);
+        let diags = version_diagnostics(&result);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn unknown_version_errors() {
+        let result = validate_document(&version_doc("9.9")).unwrap();
+        assert!(!result.is_valid);
+        let diags = version_diagnostics(&result);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].severity, Severity::Error);
+        assert!(diags[0].message.contains("not supported"));
+    }
+
+    #[test]
+    fn malformed_version_errors() {
+        let result = validate_document(&version_doc("one.point.oh")).unwrap();
+        assert!(!result.is_valid);
+        let diags = version_diagnostics(&result);
+        assert_eq!(diags.len(), 1);
+        assert!(diags[0].message.contains("not a valid major.minor version"));
+    }
+
+    fn reader_version_doc(version: &str) -> String {
+        document_with(serde_json::json!({ "minReaderVersion": version }))
+    }
+
+    #[test]
+    fn future_reader_version_errors() {
+        let result = validate_document(&reader_version_doc("1.1")).unwrap();
+        assert!(!result.is_valid);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].rule, Rule::ReaderTooOld);
+        assert!(result.errors[0].message.contains("requires reader version 1.1"));
+
+        let result = validate_document(&reader_version_doc("2.0")).unwrap();
+        assert_eq!(result.errors[0].rule, Rule::ReaderTooOld);
+    }
+
+    #[test]
+    fn current_reader_version_passes() {
+        let result = validate_document(&reader_version_doc("1.0")).unwrap();
+        assert!(result.is_valid);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn malformed_reader_version_warns() {
+        let result = validate_document(&reader_version_doc("soon")).unwrap();
+        assert!(result
+            .warnings
+            .iter()
+            .any(|d| d.rule == Rule::MalformedReaderVersion));
+        assert!(!result.errors.iter().any(|d| d.rule == Rule::ReaderTooOld));
+    }
+
+    #[test]
+    fn unknown_feature_warns() {
+        let json = document_with(serde_json::json!({ "features": ["labels", "teleport"] }));
+        let result = validate_document(&json).unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].rule, Rule::UnknownFeature);
+        assert!(result.warnings[0].message.contains("'teleport'"));
+    }
+
+    #[test]
+    fn undeclared_labels_warn() {
+        let labeled = |features: serde_json::Value| {
+            document_with(serde_json::json!({
+                "features": features,
+                "edges": [{"source": "a", "target": "b", "isTrunk": true, "label": "Go"}]
+            }))
+        };
+        let result = validate_document(&labeled(serde_json::json!([]))).unwrap();
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].rule, Rule::UndeclaredFeature);
+
+        let result = validate_document(&labeled(serde_json::json!(["labels"]))).unwrap();
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn self_loop_example_warns() {
+        let json = include_str!("../../../examples/invalid/self-loop.tree.json");
+        let result = validate_document(json).unwrap();
         assert!(result.is_valid);
+        assert!(result.warnings.iter().any(|d| d.rule == Rule::SelfLoop));
     }
 }
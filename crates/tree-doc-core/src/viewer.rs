@@ -1,116 +1,416 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::types::TreeDocument;
+use serde::Serialize;
 
-#[derive(Debug)]
+use crate::types::{Edge, Node, TreeDocument};
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TrunkView {
     pub title: String,
     pub stats: String,
     pub steps: Vec<TrunkStep>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct TrunkStep {
     pub node_id: String,
     pub content: String,
+    /// Every non-trunk edge leaving this node, in document order.
+    pub branches: Vec<BranchInfo>,
+    /// `branches.len()`.
     pub branch_count: usize,
-    pub branch_labels: Vec<String>,
+    /// The `label` of each entry in `branches`. Derived from `branches` and
+    /// kept only for existing consumers; read `branches` instead.
+    pub branch_labels: Vec<Option<String>>,
+    /// The `description` of each entry in `branches`. Derived from `branches`
+    /// and kept only for existing consumers; read `branches` instead.
+    pub branch_descriptions: Vec<Option<String>>,
+    /// The `target` of each entry in `branches`. Derived from `branches` and
+    /// kept only for existing consumers; read `branches` instead.
+    pub branch_targets: Vec<String>,
     pub is_terminal: bool,
     pub trunk_target: Option<String>,
 }
 
-pub fn build_trunk_view(doc: &TreeDocument) -> Result<TrunkView, String> {
-    let root_id = doc
-        .root_node_id
-        .as_deref()
-        .ok_or_else(|| "Document has no rootNodeId".to_string())?;
+/// A branch edge leaving a `TrunkStep`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchInfo {
+    pub target: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+}
 
-    // Build lookup maps
-    let node_map: HashMap<&str, &crate::types::Node> =
-        doc.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+/// The whole document as a tree rooted at `rootNodeId`, following all edges.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullView {
+    pub title: String,
+    pub stats: String,
+    pub root: FullViewNode,
+}
 
-    // Build trunk adjacency: source -> target for isTrunk edges
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FullViewNode {
+    pub node_id: String,
+    pub content: String,
+    pub depth: usize,
+    /// Label of the edge this node was reached through (`None` for the root).
+    pub via_label: Option<String>,
+    /// Description of the edge this node was reached through, if it has one.
+    pub via_description: Option<String>,
+    /// Whether the edge this node was reached through is a trunk edge.
+    pub via_trunk: bool,
+    /// The node was already shown elsewhere in the tree; its children are not
+    /// repeated here.
+    pub is_back_edge: bool,
+    pub children: Vec<FullViewNode>,
+}
+
+// The derived drop would recurse once per level, which overflows the stack
+// on very deep documents.
+impl Drop for FullViewNode {
+    fn drop(&mut self) {
+        let mut pending = std::mem::take(&mut self.children);
+        while let Some(mut node) = pending.pop() {
+            pending.append(&mut node.children);
+        }
+    }
+}
+
+/// Build the trunk adjacency map: source -> target for `isTrunk` edges.
+///
+/// When a node has several outgoing trunk edges, the first one in `doc.edges`
+/// order wins, so every trunk walk over the same document is deterministic.
+pub(crate) fn first_trunk_target(doc: &TreeDocument) -> HashMap<&str, &str> {
     let mut trunk_next: HashMap<&str, &str> = HashMap::new();
     for edge in &doc.edges {
         if edge.is_trunk == Some(true) {
-            trunk_next.insert(edge.source.as_str(), edge.target.as_str());
+            trunk_next
+                .entry(edge.source.as_str())
+                .or_insert(edge.target.as_str());
         }
     }
+    trunk_next
+}
 
-    // Build branch info: source -> list of (target, label) for non-trunk edges
-    let mut branches: HashMap<&str, Vec<(&str, Option<&str>)>> = HashMap::new();
-    for edge in &doc.edges {
-        if edge.is_trunk != Some(true) {
-            branches
-                .entry(edge.source.as_str())
-                .or_default()
-                .push((edge.target.as_str(), edge.label.as_deref()));
+/// Node ids along the trunk, starting at the root and stopping at the end of
+/// the trunk or just before a node would repeat.
+pub(crate) fn trunk_path(doc: &TreeDocument) -> Vec<&str> {
+    let Some(root_id) = doc.root_node_id.as_deref() else {
+        return Vec::new();
+    };
+    let trunk_next = first_trunk_target(doc);
+
+    let mut path = vec![root_id];
+    let mut visited = HashSet::from([root_id]);
+    let mut current = root_id;
+    while let Some(&next) = trunk_next.get(current) {
+        if !visited.insert(next) {
+            break;
         }
+        path.push(next);
+        current = next;
     }
+    path
+}
 
-    // Walk trunk from root
+pub fn build_trunk_view(doc: &TreeDocument) -> Result<TrunkView, String> {
+    build_trunk_view_filtered(doc, |_| true)
+}
+
+/// [`build_trunk_view`], listing only the branch edges `keep` accepts. Trunk
+/// edges are always followed.
+pub fn build_trunk_view_filtered(
+    doc: &TreeDocument,
+    keep: impl Fn(&Edge) -> bool,
+) -> Result<TrunkView, String> {
+    let root_id = doc
+        .root_node_id
+        .as_deref()
+        .ok_or_else(|| "Document has no rootNodeId".to_string())?;
+    if doc.node(root_id).is_none() {
+        return Err(format!("Root node '{}' not found in nodes array", root_id));
+    }
+
+    let walk = TrunkWalk::new(doc, &keep);
     let mut steps = Vec::new();
-    let mut current = root_id;
-    let mut visited = std::collections::HashSet::new();
+    walk.follow(root_id, &mut HashSet::new(), &mut steps)?;
 
-    loop {
-        if !visited.insert(current) {
-            break; // Avoid infinite loop on trunk cycles
+    Ok(TrunkView {
+        title: document_title(doc),
+        stats: document_stats(doc),
+        steps,
+    })
+}
+
+/// Build a trunk view of one tree in a multi-tree document, rooted at that
+/// tree's `rootNodeId` and titled with its label (or its id, without one).
+///
+/// Only nodes listing `tree_id` in `treeIds` are included, plus the tree's
+/// root. Edges are included if their `treeId` is `tree_id`, or if they have
+/// no `treeId` and both ends are in the tree, so cross links out of the tree
+/// are left out.
+pub fn build_tree_view(doc: &TreeDocument, tree_id: &str) -> Result<TrunkView, String> {
+    let tree = doc
+        .trees
+        .as_ref()
+        .and_then(|trees| trees.get(tree_id))
+        .ok_or_else(|| format!("Tree '{tree_id}' not found in trees"))?;
+    let root_id = tree.root_node_id.as_str();
+
+    let in_tree = |node: &Node| {
+        node.id == root_id
+            || node
+                .tree_ids
+                .as_ref()
+                .is_some_and(|ids| ids.iter().any(|id| id == tree_id))
+    };
+    let nodes: Vec<Node> = doc.nodes.iter().filter(|n| in_tree(n)).cloned().collect();
+    let node_ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let edges = doc
+        .edges
+        .iter()
+        .filter(|e| match e.tree_id.as_deref() {
+            Some(id) => id == tree_id,
+            None => node_ids.contains(e.source.as_str()) && node_ids.contains(e.target.as_str()),
+        })
+        .cloned()
+        .collect();
+
+    let tree_doc = TreeDocument {
+        root_node_id: Some(root_id.to_string()),
+        nodes,
+        edges,
+        ..doc.clone()
+    };
+    let mut view = build_trunk_view(&tree_doc)?;
+    view.title = tree.label.clone().unwrap_or_else(|| tree_id.to_string());
+    Ok(view)
+}
+
+/// Preview the path a reader takes by choosing one branch: the step at `from`,
+/// then the branch edge to `via_target`, then trunk edges onward from there.
+///
+/// Fails if `from` is not a node, or if no branch (non-trunk) edge leads from
+/// `from` to `via_target`.
+pub fn build_branch_view(
+    doc: &TreeDocument,
+    from: &str,
+    via_target: &str,
+) -> Result<TrunkView, String> {
+    if doc.node(from).is_none() {
+        return Err(format!("Node '{from}' not found in nodes array"));
+    }
+    if !doc
+        .outgoing(from)
+        .any(|e| e.is_trunk != Some(true) && e.target == via_target)
+    {
+        return Err(format!("No branch from '{from}' to '{via_target}'"));
+    }
+
+    let walk = TrunkWalk::new(doc, &|_| true);
+    let mut visited = HashSet::from([from]);
+    let mut steps = vec![walk.step(from)?];
+    walk.follow(via_target, &mut visited, &mut steps)?;
+
+    Ok(TrunkView {
+        title: document_title(doc),
+        stats: document_stats(doc),
+        steps,
+    })
+}
+
+/// Lookups for building `TrunkStep`s.
+struct TrunkWalk<'a> {
+    node_map: HashMap<&'a str, &'a Node>,
+    trunk_next: HashMap<&'a str, &'a str>,
+    /// source -> non-trunk edges accepted by the filter, in document order
+    branches: HashMap<&'a str, Vec<&'a Edge>>,
+}
+
+impl<'a> TrunkWalk<'a> {
+    fn new(doc: &'a TreeDocument, keep: &dyn Fn(&Edge) -> bool) -> Self {
+        let node_map = doc.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+
+        let mut branches: HashMap<&str, Vec<&Edge>> = HashMap::new();
+        for edge in &doc.edges {
+            if edge.is_trunk != Some(true) && keep(edge) {
+                branches.entry(edge.source.as_str()).or_default().push(edge);
+            }
         }
 
-        let node = node_map
-            .get(current)
-            .ok_or_else(|| format!("Root node '{}' not found in nodes array", current))?;
+        TrunkWalk {
+            node_map,
+            trunk_next: first_trunk_target(doc),
+            branches,
+        }
+    }
 
-        let node_branches = branches.get(current).cloned().unwrap_or_default();
-        let branch_labels: Vec<String> = node_branches
-            .iter()
-            .filter_map(|(_, label)| label.map(|l| l.to_string()))
+    fn step(&self, id: &str) -> Result<TrunkStep, String> {
+        let node = self
+            .node_map
+            .get(id)
+            .ok_or_else(|| format!("Node '{}' not found in nodes array", id))?;
+
+        let branches: Vec<BranchInfo> = self
+            .branches
+            .get(id)
+            .into_iter()
+            .flatten()
+            .map(|e| BranchInfo {
+                target: e.target.clone(),
+                label: e.label.clone(),
+                description: e.description.clone(),
+            })
             .collect();
 
-        let next = trunk_next.get(current).copied();
-        let is_terminal = next.is_none();
+        let next = self.trunk_next.get(id).copied();
 
-        steps.push(TrunkStep {
-            node_id: current.to_string(),
+        Ok(TrunkStep {
+            node_id: id.to_string(),
             content: node.content.clone(),
-            branch_count: node_branches.len(),
-            branch_labels,
-            is_terminal,
+            branch_count: branches.len(),
+            branch_labels: branches.iter().map(|b| b.label.clone()).collect(),
+            branch_descriptions: branches.iter().map(|b| b.description.clone()).collect(),
+            branch_targets: branches.iter().map(|b| b.target.clone()).collect(),
+            branches,
+            is_terminal: next.is_none(),
             trunk_target: next.map(|s| s.to_string()),
-        });
+        })
+    }
 
-        match next {
-            Some(n) => current = n,
-            None => break,
+    /// Append steps along trunk edges starting at `start`, stopping at the
+    /// end of the trunk or on revisiting a node.
+    fn follow<'s>(
+        &self,
+        start: &'s str,
+        visited: &mut HashSet<&'s str>,
+        steps: &mut Vec<TrunkStep>,
+    ) -> Result<(), String>
+    where
+        'a: 's,
+    {
+        let mut current = start;
+        while visited.insert(current) {
+            steps.push(self.step(current)?);
+            match self.trunk_next.get(current) {
+                Some(&next) => current = next,
+                None => break,
+            }
         }
+        Ok(())
     }
+}
 
-    let title = doc
-        .metadata
-        .as_ref()
-        .and_then(|m| m.get("title"))
-        .and_then(|t| t.as_str())
-        .unwrap_or("Untitled Document")
-        .to_string();
+/// Build a view of every node reachable from the root via a depth-first walk
+/// over all edges, in document order.
+///
+/// Each node is expanded once. Later edges into an already-shown node produce
+/// a leaf marked `is_back_edge`, which keeps cyclic documents finite.
+pub fn build_full_view(doc: &TreeDocument) -> Result<FullView, String> {
+    build_full_view_filtered(doc, |_| true)
+}
+
+/// [`build_full_view`], following trunk edges and only the other edges `keep`
+/// accepts.
+pub fn build_full_view_filtered(
+    doc: &TreeDocument,
+    keep: impl Fn(&Edge) -> bool,
+) -> Result<FullView, String> {
+    let root_id = doc
+        .root_node_id
+        .as_deref()
+        .ok_or_else(|| "Document has no rootNodeId".to_string())?;
 
-    let stats = format!(
-        "{} nodes, {} edges",
-        doc.nodes.len(),
-        doc.edges.len()
-    );
+    let node_map: HashMap<&str, &Node> = doc.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let root = node_map
+        .get(root_id)
+        .ok_or_else(|| format!("Root node '{}' not found in nodes array", root_id))?;
 
-    Ok(TrunkView {
-        title,
-        stats,
-        steps,
+    let keep = |e: &Edge| e.is_trunk == Some(true) || keep(e);
+    let root = expand_full_view(&doc.edges_by_source(), &node_map, &keep, root);
+
+    Ok(FullView {
+        title: document_title(doc),
+        stats: document_stats(doc),
+        root,
     })
 }
 
+/// Depth-first expansion from `root`, using an explicit stack so deep
+/// documents cannot overflow the call stack.
+fn expand_full_view<'a>(
+    outgoing: &HashMap<&str, Vec<&'a Edge>>,
+    node_map: &HashMap<&str, &'a Node>,
+    keep: &dyn Fn(&Edge) -> bool,
+    root: &'a Node,
+) -> FullViewNode {
+    let edges_of = |id: &str| outgoing.get(id).map_or(&[][..], Vec::as_slice).iter();
+
+    let mut visited = HashSet::from([root.id.as_str()]);
+    let mut stack = vec![(full_view_node(root, 0, None, false), edges_of(&root.id))];
+    loop {
+        let (view, edges) = stack.last_mut().expect("the root stays on the stack");
+        let Some(&edge) = edges.find(|e| keep(e)) else {
+            let (done, _) = stack.pop().expect("the root stays on the stack");
+            match stack.last_mut() {
+                Some((parent, _)) => parent.children.push(done),
+                None => return done,
+            }
+            continue;
+        };
+
+        // `edges_by_source` leaves out dangling targets, which validation
+        // reports.
+        let target = node_map[edge.target.as_str()];
+        let depth = view.depth + 1;
+        if visited.insert(target.id.as_str()) {
+            let child = full_view_node(target, depth, Some(edge), false);
+            stack.push((child, edges_of(&target.id)));
+        } else {
+            view.children
+                .push(full_view_node(target, depth, Some(edge), true));
+        }
+    }
+}
+
+fn full_view_node(
+    node: &Node,
+    depth: usize,
+    via: Option<&Edge>,
+    is_back_edge: bool,
+) -> FullViewNode {
+    FullViewNode {
+        node_id: node.id.clone(),
+        content: node.content.clone(),
+        depth,
+        via_label: via.and_then(|e| e.label.clone()),
+        via_description: via.and_then(|e| e.description.clone()),
+        via_trunk: via.is_some_and(|e| e.is_trunk == Some(true)),
+        is_back_edge,
+        children: Vec::new(),
+    }
+}
+
+fn document_title(doc: &TreeDocument) -> String {
+    doc.metadata_typed()
+        .and_then(|m| m.title)
+        .unwrap_or_else(|| "Untitled Document".to_string())
+}
+
+fn document_stats(doc: &TreeDocument) -> String {
+    format!("{} nodes, {} edges", doc.nodes.len(), doc.edges.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::parse;
+    use crate::types::EdgeType;
 
     #[test]
     fn minimal_trunk_view() {
@@ -140,7 +440,10 @@ mod tests {
         assert_eq!(view.steps.len(), 5);
         assert_eq!(view.steps[0].node_id, "start");
         assert_eq!(view.steps[0].branch_count, 1); // "climb" branch
-        assert_eq!(view.steps[0].branch_labels, vec!["Climb the wall"]);
+        assert_eq!(
+            view.steps[0].branch_labels,
+            [Some("Climb the wall".to_string())]
+        );
         assert_eq!(view.steps[4].node_id, "ending");
         assert!(view.steps[4].is_terminal);
     }
@@ -160,4 +463,229 @@ mod tests {
         assert!(view.steps[0].is_terminal);
         assert_eq!(view.steps[0].branch_count, 0);
     }
+
+    #[test]
+    fn story_full_view_includes_branches() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let view = build_full_view(&doc).unwrap();
+
+        let root = &view.root;
+        assert_eq!(root.node_id, "start");
+        assert_eq!(root.depth, 0);
+        let child_ids: Vec<_> = root.children.iter().map(|c| c.node_id.as_str()).collect();
+        assert_eq!(child_ids, vec!["enter", "climb"]);
+
+        let climb = &root.children[1];
+        assert_eq!(climb.depth, 1);
+        assert_eq!(climb.via_label.as_deref(), Some("Climb the wall"));
+        assert!(!climb.via_trunk);
+
+        let enter = &root.children[0];
+        assert!(enter.via_trunk);
+        let explore = enter.children.iter().find(|c| c.node_id == "explore").unwrap();
+        assert_eq!(explore.depth, 2);
+        // "ending" was already reached through the trunk
+        assert_eq!(explore.children.len(), 1);
+        assert!(explore.children[0].is_back_edge);
+        assert_eq!(explore.children[0].depth, 3);
+    }
+
+    #[test]
+    fn full_view_handles_deep_documents() {
+        const DEPTH: usize = 100_000;
+        let nodes: Vec<_> = (0..DEPTH)
+            .map(|i| serde_json::json!({"id": format!("n{i}"), "content": ""}))
+            .collect();
+        let edges: Vec<_> = (1..DEPTH)
+            .map(|i| {
+                serde_json::json!({
+                    "source": format!("n{}", i - 1),
+                    "target": format!("n{i}"),
+                    "isTrunk": true
+                })
+            })
+            .collect();
+        let doc: TreeDocument = serde_json::from_value(serde_json::json!({
+            "formatVersion": "1.0",
+            "rootNodeId": "n0",
+            "nodes": nodes,
+            "edges": edges
+        }))
+        .unwrap();
+        let view = build_full_view(&doc).unwrap();
+
+        let mut node = &view.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        assert_eq!(node.node_id, format!("n{}", DEPTH - 1));
+        assert_eq!(node.depth, DEPTH - 1);
+    }
+
+    #[test]
+    fn full_view_handles_cycles() {
+        let json = include_str!("../../../examples/invalid/trunk-cycle.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let view = build_full_view(&doc).unwrap();
+
+        let n2 = &view.root.children[0];
+        let n3 = &n2.children[0];
+        assert_eq!(n3.node_id, "n3");
+        assert_eq!(n3.children.len(), 1);
+        assert_eq!(n3.children[0].node_id, "n1");
+        assert!(n3.children[0].is_back_edge);
+        assert!(n3.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn unlabeled_branches_are_kept() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "n1",
+            "nodes": [
+                {"id": "n1", "content": "Start"},
+                {"id": "n2", "content": "Next"},
+                {"id": "n3", "content": "Labeled"},
+                {"id": "n4", "content": "Unlabeled"}
+            ],
+            "edges": [
+                {"source": "n1", "target": "n2", "isTrunk": true},
+                {"source": "n1", "target": "n3", "label": "a"},
+                {"source": "n1", "target": "n4"}
+            ]
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let step = &build_trunk_view(&doc).unwrap().steps[0];
+
+        let branches: Vec<_> = step
+            .branches
+            .iter()
+            .map(|b| (b.target.as_str(), b.label.as_deref()))
+            .collect();
+        assert_eq!(branches, [("n3", Some("a")), ("n4", None)]);
+        assert_eq!(step.branch_count, 2);
+        assert_eq!(step.branch_labels, [Some("a".to_string()), None]);
+    }
+
+    #[test]
+    fn filtered_views_follow_only_matching_branches() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"},
+                {"id": "cross", "content": "Cross"},
+                {"id": "plain", "content": "Plain"},
+                {"id": "deep", "content": "Deep"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "cross", "linkType": "cross", "label": "Jump"},
+                {"source": "a", "target": "plain", "label": "Walk"},
+                {"source": "cross", "target": "deep", "type": "cross"},
+                {"source": "cross", "target": "plain"}
+            ]
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let cross = |e: &Edge| e.has_type(&EdgeType::Cross);
+
+        let view = build_trunk_view_filtered(&doc, cross).unwrap();
+        let ids: Vec<_> = view.steps.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(view.steps[0].branch_count, 1);
+        assert_eq!(view.steps[0].branch_labels, [Some("Jump".to_string())]);
+
+        let view = build_full_view_filtered(&doc, cross).unwrap();
+        let child_ids: Vec<_> = view.root.children.iter().map(|c| c.node_id.as_str()).collect();
+        assert_eq!(child_ids, ["b", "cross"]);
+        let grandchildren: Vec<_> =
+            view.root.children[1].children.iter().map(|c| c.node_id.as_str()).collect();
+        assert_eq!(grandchildren, ["deep"]);
+    }
+
+    #[test]
+    fn tree_view_stays_inside_the_tree() {
+        let json = include_str!("../../../examples/anthology.tree.json");
+        let doc = parse::parse(json).unwrap();
+
+        let view = build_tree_view(&doc, "market").unwrap();
+        assert_eq!(view.title, "Market Day");
+        assert_eq!(view.stats, "4 nodes, 4 edges");
+        let ids: Vec<_> = view.steps.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(ids, ["stalls", "haggle", "supper"]);
+        assert_eq!(view.steps[0].branch_labels, [Some("Wander the stalls".to_string())]);
+
+        // The cross link from `relight` into the market tree is dropped
+        let view = build_tree_view(&doc, "lighthouse").unwrap();
+        let ids: Vec<_> = view.steps.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(ids, ["harbor", "climb", "relight"]);
+        assert!(view.steps[2].is_terminal);
+        assert_eq!(view.steps[2].branch_count, 0);
+    }
+
+    #[test]
+    fn tree_view_errors() {
+        let json = include_str!("../../../examples/anthology.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let err = build_tree_view(&doc, "nowhere").unwrap_err();
+        assert_eq!(err, "Tree 'nowhere' not found in trees");
+
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        assert!(build_tree_view(&doc, "garden").is_err());
+    }
+
+    #[test]
+    fn branch_view_follows_chosen_branch() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+        let view = build_branch_view(&doc, "enter", "explore").unwrap();
+
+        let ids: Vec<_> = view.steps.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(ids, ["enter", "explore"]);
+        assert_eq!(view.steps[0].trunk_target.as_deref(), Some("fountain"));
+        assert_eq!(view.steps[0].branch_labels, [Some("Wander the paths".to_string())]);
+        assert!(view.steps[1].is_terminal);
+        assert_eq!(view.title, "The Enchanted Garden");
+    }
+
+    #[test]
+    fn branch_view_continues_along_trunk() {
+        let json = r#"{
+            "formatVersion": "1.0",
+            "rootNodeId": "a",
+            "nodes": [
+                {"id": "a", "content": "A"},
+                {"id": "b", "content": "B"},
+                {"id": "c", "content": "C"},
+                {"id": "d", "content": "D"}
+            ],
+            "edges": [
+                {"source": "a", "target": "b", "isTrunk": true},
+                {"source": "a", "target": "c", "label": "Detour"},
+                {"source": "c", "target": "d", "isTrunk": true}
+            ]
+        }"#;
+        let doc = parse::parse(json).unwrap();
+        let view = build_branch_view(&doc, "a", "c").unwrap();
+        let ids: Vec<_> = view.steps.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "d"]);
+    }
+
+    #[test]
+    fn branch_view_errors() {
+        let json = include_str!("../../../examples/story.tree.json");
+        let doc = parse::parse(json).unwrap();
+
+        let err = build_branch_view(&doc, "nowhere", "climb").unwrap_err();
+        assert_eq!(err, "Node 'nowhere' not found in nodes array");
+
+        let err = build_branch_view(&doc, "start", "fountain").unwrap_err();
+        assert_eq!(err, "No branch from 'start' to 'fountain'");
+
+        // The trunk edge is not a branch
+        assert!(build_branch_view(&doc, "start", "enter").is_err());
+    }
 }
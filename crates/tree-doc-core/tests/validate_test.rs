@@ -1,5 +1,15 @@
+use std::io::Cursor;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
 use tree_doc_core::error::Rule;
-use tree_doc_core::validate_document;
+use tree_doc_core::parse::ParseError;
+use tree_doc_core::types::{Edge, TreeDocument};
+use tree_doc_core::{
+    diagnostics_for_node, parse, revalidate_node, sorted_diagnostics, validate_and_parse,
+    validate_bytes_with_options, validate_document, validate_document_with_options, validate_files,
+    validate_reader, ValidationCache, ValidationOptions, ValidationResult,
+};
 
 #[test]
 fn valid_minimal() {
@@ -104,6 +114,108 @@ fn malformed_json_returns_error() {
     assert!(result.is_err());
 }
 
+#[test]
+fn validate_from_reader() {
+    let json = include_str!("../../../examples/story.tree.json");
+    let from_reader = validate_reader(Cursor::new(json)).unwrap();
+    let from_str = validate_document(json).unwrap();
+    assert!(from_reader.is_valid);
+    assert_eq!(
+        serde_json::to_value(&from_reader).unwrap(),
+        serde_json::to_value(&from_str).unwrap()
+    );
+
+    let err = validate_reader(Cursor::new(b"{\"formatVersion\": \"\xff\"}")).unwrap_err();
+    assert!(matches!(err, ParseError::Io(_)), "{err}");
+    assert!(validate_reader(Cursor::new("{not valid json}")).is_err());
+}
+
+/// A per-test directory in the system temp directory, removed on drop so a
+/// failing assertion doesn't leave it behind.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("tree-doc-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn validate_files_reports_each_file() {
+    let dir = TempDir::new("batch");
+    let valid = dir.join("valid.tree.json");
+    let malformed = dir.join("malformed.tree.json");
+    std::fs::write(&valid, include_str!("../../../examples/minimal.tree.json")).unwrap();
+    std::fs::write(&malformed, "{not valid json}").unwrap();
+    let missing = dir.join("missing.tree.json");
+
+    let results = validate_files(&[valid.clone(), malformed.clone(), missing.clone()]);
+
+    let paths: Vec<_> = results.iter().map(|(path, _)| path.clone()).collect();
+    assert_eq!(paths, [valid, malformed, missing]);
+    assert!(results[0].1.as_ref().unwrap().is_valid);
+    assert!(matches!(results[1].1, Err(ParseError::InvalidJson { .. })));
+    assert!(matches!(results[2].1, Err(ParseError::Io(_))));
+}
+
+#[test]
+fn validate_bytes_decompresses_gzip() {
+    use std::io::Write;
+
+    let json = include_str!("../../../examples/story.tree.json");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(json.as_bytes()).unwrap();
+    let gzipped = encoder.finish().unwrap();
+
+    let options = ValidationOptions::default();
+    let expected = serde_json::to_value(validate_document(json).unwrap()).unwrap();
+    for bytes in [gzipped.clone(), json.as_bytes().to_vec()] {
+        let result = validate_bytes_with_options(bytes, &options).unwrap();
+        assert_eq!(serde_json::to_value(result).unwrap(), expected);
+    }
+
+    let truncated = gzipped[..gzipped.len() / 2].to_vec();
+    let err = validate_bytes_with_options(truncated, &options).unwrap_err();
+    assert!(matches!(err, ParseError::Io(_)));
+}
+
+#[test]
+fn validate_and_parse_returns_the_document() {
+    let json = include_str!("../../../examples/story.tree.json");
+    let (result, doc) = validate_and_parse(json).unwrap();
+    assert_eq!(
+        serde_json::to_value(&result).unwrap(),
+        serde_json::to_value(validate_document(json).unwrap()).unwrap()
+    );
+    assert_eq!(
+        doc.unwrap().to_canonical_json(),
+        parse(json).unwrap().to_canonical_json()
+    );
+
+    // Schema errors that stop the typed parse leave no document
+    let (result, doc) = validate_and_parse(r#"[{"id": "n1"}]"#).unwrap();
+    assert!(!result.is_valid);
+    assert!(doc.is_none());
+
+    assert!(validate_and_parse("{not valid json}").is_err());
+}
+
 #[test]
 fn wrong_shape_array_not_object() {
     let json = r#"[{"id": "n1"}]"#;
@@ -124,9 +236,34 @@ fn extra_fields_accepted() {
     assert!(result.is_valid);
 }
 
+#[test]
+fn strict_mode_flags_unknown_fields() {
+    let json = r#"{
+        "formatVersion": "1.0",
+        "rootNodId": "n1",
+        "nodes": [{"id": "n1", "content": "hi", "customField": 42}],
+        "edges": []
+    }"#;
+    let strict = ValidationOptions {
+        strict: true,
+        ..Default::default()
+    };
+    let result = validate_document_with_options(json, &strict).unwrap();
+    let unknown: Vec<_> = result
+        .warnings
+        .iter()
+        .filter(|d| d.rule == Rule::UnknownField)
+        .map(|d| d.location.to_string())
+        .collect();
+    assert_eq!(unknown, ["/rootNodId", "/nodes/0/customField"]);
+
+    let lenient = validate_document(json).unwrap();
+    assert!(lenient.warnings.iter().all(|d| d.rule != Rule::UnknownField));
+}
+
 #[test]
 fn multiple_trunk_edges_from_one_node() {
-    // Only the last trunk edge wins in our HashMap, but this should still validate
+    // The first trunk edge wins; the ambiguity is a warning, not an error
     let json = r#"{
         "formatVersion": "1.0",
         "rootNodeId": "n1",
@@ -142,6 +279,10 @@ fn multiple_trunk_edges_from_one_node() {
     }"#;
     let result = validate_document(json).unwrap();
     assert!(result.is_valid);
+    assert!(result
+        .warnings
+        .iter()
+        .any(|d| d.rule == Rule::AmbiguousTrunk));
 }
 
 #[test]
@@ -164,3 +305,234 @@ fn linear_document_no_branches() {
     assert_eq!(result.stats.trunk_length, 2);
     assert_eq!(result.stats.branch_count, 0);
 }
+
+#[test]
+fn validation_result_serializes_camel_case() {
+    let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+    let result = validate_document(json).unwrap();
+    let value = serde_json::to_value(&result).unwrap();
+
+    assert_eq!(value["isValid"], false);
+    for key in [
+        "nodeCount",
+        "edgeCount",
+        "trunkLength",
+        "branchCount",
+        "componentCount",
+        "longestPathLen",
+        "maxOutDegree",
+        "tier",
+    ] {
+        assert!(value["stats"].get(key).is_some(), "missing stats.{key}");
+    }
+
+    let error = &value["errors"][0];
+    assert_eq!(error["rule"], "dangling-edge");
+    assert_eq!(error["severity"], "error");
+    assert!(error["message"].is_string());
+    assert_eq!(error["location"]["kind"], "edge");
+    assert!(error["location"]["source"].is_string());
+    assert!(error["location"]["target"].is_string());
+    assert!(error["location"]["index"].is_u64());
+}
+
+fn story() -> TreeDocument {
+    parse(include_str!("../../../examples/story.tree.json")).unwrap()
+}
+
+/// Every diagnostic as `(code, location, message)`, in a stable order.
+fn diagnostic_keys(result: &ValidationResult) -> Vec<(String, String, String)> {
+    let all = result.errors.iter().chain(&result.warnings).chain(&result.advisories);
+    let mut keys: Vec<_> = all
+        .map(|d| (d.rule.code().to_string(), d.location.to_string(), d.message.clone()))
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Edit one node and check the incremental result against a full run.
+fn assert_revalidates_like_full(node_id: &str, edit: impl FnOnce(&mut TreeDocument)) {
+    let mut doc = story();
+    let cache = ValidationCache::new(&doc);
+    edit(&mut doc);
+
+    let incremental = revalidate_node(&doc, &cache, node_id);
+    let full = validate_document(&doc.to_canonical_json()).unwrap();
+    assert_eq!(diagnostic_keys(&incremental), diagnostic_keys(&full));
+    assert_eq!(incremental.is_valid, full.is_valid);
+    assert_eq!(
+        serde_json::to_value(&incremental.stats).unwrap(),
+        serde_json::to_value(&full.stats).unwrap()
+    );
+}
+
+#[test]
+fn revalidate_content_edit() {
+    assert_revalidates_like_full("explore", |doc| {
+        let node = doc.nodes.iter_mut().find(|n| n.id == "explore").unwrap();
+        node.content = " ".to_string();
+        node.status = Some("mystery".into());
+    });
+}
+
+#[test]
+fn revalidate_trunk_edge_converging() {
+    assert_revalidates_like_full("explore", |doc| {
+        let edge = doc
+            .edges
+            .iter_mut()
+            .find(|e| e.source == "explore" && e.target == "ending")
+            .unwrap();
+        edge.is_trunk = Some(true);
+    });
+}
+
+#[test]
+fn revalidate_new_edge_creating_cycle() {
+    assert_revalidates_like_full("ending", |doc| {
+        let mut edge: Edge = doc.edges[1].clone();
+        edge.source = "ending".to_string();
+        edge.target = "start".to_string();
+        doc.edges.push(edge);
+    });
+}
+
+#[test]
+fn revalidate_shifts_edge_indices() {
+    // A new unlabeled edge at the front moves every other edge back by one
+    assert_revalidates_like_full("climb", |doc| {
+        let mut edge: Edge = doc.edges[0].clone();
+        edge.source = "climb".to_string();
+        edge.target = "ending".to_string();
+        edge.is_trunk = None;
+        edge.label = None;
+        doc.edges.insert(0, edge);
+    });
+}
+
+#[test]
+fn revalidate_removed_node() {
+    // The edge into "climb" is left behind and becomes dangling
+    assert_revalidates_like_full("climb", |doc| {
+        doc.nodes.retain(|n| n.id != "climb");
+    });
+}
+
+#[test]
+fn revalidate_duplicate_node() {
+    assert_revalidates_like_full("wish", |doc| {
+        let copy = doc.nodes.iter().find(|n| n.id == "wish").unwrap().clone();
+        doc.nodes.push(copy);
+    });
+}
+
+#[test]
+fn revalidate_clears_fixed_diagnostics() {
+    let mut doc = story();
+    doc.nodes.retain(|n| n.id != "climb");
+    let cache = ValidationCache::new(&doc);
+
+    doc.nodes.push(story().nodes.into_iter().find(|n| n.id == "climb").unwrap());
+    let result = revalidate_node(&doc, &cache, "climb");
+    assert!(result.is_valid, "{:?}", result.errors);
+}
+
+#[test]
+fn diagnostics_sorted_by_location() {
+    let json = r#"{
+        "formatVersion": "1.0",
+        "rootNodeId": "a",
+        "features": ["sparkles"],
+        "nodes": [
+            {"id": "a", "content": "Start"},
+            {"id": "b", "content": " ", "status": "mystery"},
+            {"id": "c", "content": "End", "status": "ending"}
+        ],
+        "edges": [
+            {"source": "b", "target": "c", "isTrunk": true},
+            {"source": "a", "target": "b", "isTrunk": true},
+            {"source": "a", "target": "c"},
+            {"source": "a", "target": "ghost", "label": "Boo"}
+        ]
+    }"#;
+    let result = validate_document(json).unwrap();
+
+    let order: Vec<_> = sorted_diagnostics(&result)
+        .iter()
+        .map(|d| format!("{} {}", d.location, d.rule))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            "(document root) unknown-feature",
+            "(document root) undeclared-feature",
+            "edge 'a' -> 'c' (edges[2]) unlabeled-branch",
+            "edge 'a' -> 'ghost' (edges[3]) dangling-edge",
+            "node 'b' unknown-status",
+            "node 'b' empty-content",
+        ]
+    );
+    assert_eq!(
+        order.len(),
+        result.errors.len() + result.warnings.len() + result.advisories.len()
+    );
+}
+
+#[test]
+fn diagnostics_for_dangling_edge_endpoint() {
+    let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+    let result = validate_document(json).unwrap();
+
+    let rules: Vec<_> = diagnostics_for_node(&result, "n99")
+        .iter()
+        .map(|d| d.rule.code())
+        .collect();
+    assert_eq!(rules, ["TDF003", "TDF003", "TDF014"]);
+
+    let rules: Vec<_> = diagnostics_for_node(&result, "n2")
+        .iter()
+        .map(|d| d.rule.code())
+        .collect();
+    assert_eq!(rules, ["TDF016"]);
+    assert!(diagnostics_for_node(&result, "n1").is_empty());
+}
+
+#[test]
+fn diagnostics_for_node_in_cycle_path() {
+    let json = include_str!("../../../examples/invalid/general-cycle.tree.json");
+    let result = validate_document(json).unwrap();
+
+    let n3: Vec<_> = diagnostics_for_node(&result, "n3")
+        .iter()
+        .map(|d| d.rule.code())
+        .collect();
+    // The cycle path, the edges into and out of n3, and n3 itself
+    assert_eq!(n3, ["TDF005", "TDF014", "TDF014", "TDF022"]);
+    let n5: Vec<_> = diagnostics_for_node(&result, "n5")
+        .iter()
+        .map(|d| d.rule.code())
+        .collect();
+    assert_eq!(n5, ["TDF016"]);
+}
+
+#[test]
+fn trunk_length_cap() {
+    let json = include_str!("../../../examples/story.tree.json");
+    let capped = |max| ValidationOptions {
+        max_trunk_length: Some(max),
+        ..Default::default()
+    };
+    let too_long = |result: &ValidationResult| {
+        result.warnings.iter().any(|d| d.rule == Rule::TrunkTooLong)
+    };
+
+    // The story's trunk has 4 edges
+    let under = validate_document_with_options(json, &capped(4)).unwrap();
+    assert!(!too_long(&under));
+
+    let over = validate_document_with_options(json, &capped(3)).unwrap();
+    assert!(too_long(&over));
+    assert!(over.is_valid);
+
+    assert!(!too_long(&validate_document(json).unwrap()));
+}
@@ -1,4 +1,4 @@
-use tree_doc_core::{build_trunk_view, parse};
+use tree_doc_core::{build_full_view, build_trunk_view, parse, BranchInfo};
 
 #[test]
 fn minimal_trunk_view() {
@@ -107,6 +107,140 @@ fn trunk_view_with_branch_labels() {
     let view = build_trunk_view(&doc).unwrap();
 
     assert_eq!(view.steps[0].branch_count, 2);
-    assert!(view.steps[0].branch_labels.contains(&"Go left".to_string()));
-    assert!(view.steps[0].branch_labels.contains(&"Go right".to_string()));
+    assert!(view.steps[0].branch_labels.contains(&Some("Go left".to_string())));
+    assert!(view.steps[0].branch_labels.contains(&Some("Go right".to_string())));
+}
+
+#[test]
+fn branch_targets_align_with_labels() {
+    let json = r#"{
+        "formatVersion": "1.0",
+        "rootNodeId": "n1",
+        "nodes": [
+            {"id": "n1", "content": "Choose your path"},
+            {"id": "n2", "content": "Main path"},
+            {"id": "n3", "content": "Side quest A"},
+            {"id": "n4", "content": "Side quest B"}
+        ],
+        "edges": [
+            {"source": "n1", "target": "n2", "isTrunk": true},
+            {"source": "n1", "target": "n3"},
+            {"source": "n1", "target": "n4", "label": "Go right",
+             "description": "Past the mill"}
+        ]
+    }"#;
+    let doc = parse(json).unwrap();
+    let step = &build_trunk_view(&doc).unwrap().steps[0];
+
+    assert_eq!(
+        step.branches,
+        [
+            BranchInfo {
+                target: "n3".to_string(),
+                label: None,
+                description: None,
+            },
+            BranchInfo {
+                target: "n4".to_string(),
+                label: Some("Go right".to_string()),
+                description: Some("Past the mill".to_string()),
+            },
+        ]
+    );
+    assert_eq!(step.branch_count, 2);
+    assert_eq!(step.branch_targets, ["n3", "n4"]);
+    assert_eq!(step.branch_labels, [None, Some("Go right".to_string())]);
+    assert_eq!(
+        step.branch_descriptions,
+        [None, Some("Past the mill".to_string())]
+    );
+}
+
+#[test]
+fn branch_descriptions_in_views() {
+    let json = r#"{
+        "formatVersion": "1.0",
+        "rootNodeId": "n1",
+        "nodes": [
+            {"id": "n1", "content": "Choose your path"},
+            {"id": "n2", "content": "Main path"},
+            {"id": "n3", "content": "Side quest A"},
+            {"id": "n4", "content": "Side quest B"}
+        ],
+        "edges": [
+            {"source": "n1", "target": "n2", "isTrunk": true},
+            {"source": "n1", "target": "n3", "label": "Go left",
+             "description": "A narrow trail into the woods"},
+            {"source": "n1", "target": "n4", "label": "Go right"}
+        ]
+    }"#;
+    let doc = parse(json).unwrap();
+
+    let view = build_trunk_view(&doc).unwrap();
+    assert_eq!(
+        view.steps[0].branch_labels,
+        [Some("Go left".to_string()), Some("Go right".to_string())]
+    );
+    assert_eq!(
+        view.steps[0].branch_descriptions,
+        [Some("A narrow trail into the woods".to_string()), None]
+    );
+
+    let full = build_full_view(&doc).unwrap();
+    let descriptions: Vec<_> = full
+        .root
+        .children
+        .iter()
+        .map(|c| c.via_description.as_deref())
+        .collect();
+    assert_eq!(descriptions, [None, Some("A narrow trail into the woods"), None]);
+}
+
+#[test]
+fn multiple_trunk_edges_follow_first() {
+    let json = r#"{
+        "formatVersion": "1.0",
+        "rootNodeId": "n1",
+        "nodes": [
+            {"id": "n1", "content": "Start"},
+            {"id": "n2", "content": "A"},
+            {"id": "n3", "content": "B"}
+        ],
+        "edges": [
+            {"source": "n1", "target": "n2", "isTrunk": true},
+            {"source": "n1", "target": "n3", "isTrunk": true}
+        ]
+    }"#;
+    let doc = parse(json).unwrap();
+    let view = build_trunk_view(&doc).unwrap();
+
+    assert_eq!(view.steps[0].trunk_target, Some("n2".to_string()));
+    assert_eq!(view.steps[1].node_id, "n2");
+}
+
+#[test]
+fn trunk_view_is_deterministic() {
+    let json = r#"{
+        "formatVersion": "1.0",
+        "rootNodeId": "n1",
+        "nodes": [
+            {"id": "n1", "content": "Start"},
+            {"id": "n2", "content": "A"},
+            {"id": "n3", "content": "B"},
+            {"id": "n4", "content": "C"}
+        ],
+        "edges": [
+            {"source": "n1", "target": "n3", "isTrunk": true},
+            {"source": "n1", "target": "n2", "isTrunk": true},
+            {"source": "n3", "target": "n4", "isTrunk": true},
+            {"source": "n2", "target": "n4", "isTrunk": true}
+        ]
+    }"#;
+    let doc = parse(json).unwrap();
+    let first = build_trunk_view(&doc).unwrap();
+    let second = build_trunk_view(&doc).unwrap();
+
+    assert_eq!(first, second);
+    let ids: Vec<_> = first.steps.iter().map(|s| s.node_id.as_str()).collect();
+    assert_eq!(ids, vec!["n1", "n3", "n4"]);
 }
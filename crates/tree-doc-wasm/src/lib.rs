@@ -1,4 +1,5 @@
 use serde::Serialize;
+use tree_doc_core::{DocumentStats, FullView, TreeDiff};
 use wasm_bindgen::prelude::*;
 
 fn to_js<T: Serialize>(value: &T) -> JsValue {
@@ -7,46 +8,27 @@ fn to_js<T: Serialize>(value: &T) -> JsValue {
         .unwrap_or(JsValue::NULL)
 }
 
+/// Payload for `info()`: the document stats plus the overall verdict.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InfoPayload<'a> {
+    #[serde(flatten)]
+    stats: &'a DocumentStats,
+    is_valid: bool,
+    /// Left out when the document does not parse as a tree document.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trunk_coverage: Option<f64>,
+}
+
 #[wasm_bindgen]
 pub fn validate(json_str: &str) -> JsValue {
-    let result = match tree_doc_core::validate_document(json_str) {
-        Ok(r) => r,
-        Err(e) => {
-            return to_js(&serde_json::json!({
-                "error": format!("{e}"),
-                "isValid": false,
-            }));
-        }
-    };
-
-    to_js(&serde_json::json!({
-        "isValid": result.is_valid,
-        "errors": result.errors.iter().map(|d| serde_json::json!({
-            "rule": d.rule.to_string(),
-            "message": d.message,
-            "location": d.location.to_string(),
-            "severity": d.severity.to_string(),
-        })).collect::<Vec<_>>(),
-        "warnings": result.warnings.iter().map(|d| serde_json::json!({
-            "rule": d.rule.to_string(),
-            "message": d.message,
-            "location": d.location.to_string(),
-            "severity": d.severity.to_string(),
-        })).collect::<Vec<_>>(),
-        "advisories": result.advisories.iter().map(|d| serde_json::json!({
-            "rule": d.rule.to_string(),
-            "message": d.message,
-            "location": d.location.to_string(),
-            "severity": d.severity.to_string(),
-        })).collect::<Vec<_>>(),
-        "stats": serde_json::json!({
-            "nodeCount": result.stats.node_count,
-            "edgeCount": result.stats.edge_count,
-            "trunkLength": result.stats.trunk_length,
-            "branchCount": result.stats.branch_count,
-            "tier": result.stats.tier,
-        }),
-    }))
+    match tree_doc_core::validate_document(json_str) {
+        Ok(result) => to_js(&result),
+        Err(e) => to_js(&serde_json::json!({
+            "error": format!("{e}"),
+            "isValid": false,
+        })),
+    }
 }
 
 #[wasm_bindgen]
@@ -58,42 +40,178 @@ pub fn view(json_str: &str) -> JsValue {
         }
     };
 
-    let trunk_view = match tree_doc_core::build_trunk_view(&doc) {
-        Ok(v) => v,
-        Err(e) => {
-            return to_js(&serde_json::json!({ "error": e }));
-        }
-    };
+    match tree_doc_core::build_trunk_view(&doc) {
+        Ok(trunk_view) => to_js(&trunk_view),
+        Err(e) => to_js(&serde_json::json!({ "error": e })),
+    }
+}
+
+/// Exposed to JS as `fullView`: every edge from the root, not just the trunk.
+#[wasm_bindgen(js_name = fullView)]
+pub fn full_view(json_str: &str) -> JsValue {
+    match full_view_document(json_str) {
+        Ok(full_view) => to_js(&full_view),
+        Err(e) => to_js(&serde_json::json!({ "error": e })),
+    }
+}
 
-    to_js(&serde_json::json!({
-        "title": trunk_view.title,
-        "stats": trunk_view.stats,
-        "steps": trunk_view.steps.iter().map(|s| serde_json::json!({
-            "nodeId": s.node_id,
-            "content": s.content,
-            "branchCount": s.branch_count,
-            "branchLabels": s.branch_labels,
-            "isTerminal": s.is_terminal,
-            "trunkTarget": s.trunk_target,
-        })).collect::<Vec<_>>(),
-    }))
+fn full_view_document(json_str: &str) -> Result<FullView, String> {
+    let doc = tree_doc_core::parse(json_str).map_err(|e| format!("{e}"))?;
+    tree_doc_core::build_full_view(&doc)
 }
 
 #[wasm_bindgen]
 pub fn info(json_str: &str) -> JsValue {
-    let result = match tree_doc_core::validate_document(json_str) {
-        Ok(r) => r,
+    let (result, doc) = match tree_doc_core::validate_and_parse(json_str) {
+        Ok(parsed) => parsed,
         Err(e) => {
             return to_js(&serde_json::json!({ "error": format!("{e}") }));
         }
     };
 
-    to_js(&serde_json::json!({
-        "nodeCount": result.stats.node_count,
-        "edgeCount": result.stats.edge_count,
-        "trunkLength": result.stats.trunk_length,
-        "branchCount": result.stats.branch_count,
-        "tier": result.stats.tier,
-        "isValid": result.is_valid,
-    }))
+    to_js(&InfoPayload {
+        stats: &result.stats,
+        is_valid: result.is_valid,
+        trunk_coverage: doc.as_ref().map(tree_doc_core::trunk_coverage),
+    })
+}
+
+#[wasm_bindgen]
+pub fn diff(old_json: &str, new_json: &str) -> JsValue {
+    match diff_documents(old_json, new_json) {
+        Ok(changes) => to_js(&changes),
+        Err(e) => to_js(&serde_json::json!({ "error": e })),
+    }
+}
+
+/// Parse both documents and compare them, naming the input that failed.
+fn diff_documents(old_json: &str, new_json: &str) -> Result<TreeDiff, String> {
+    let old = tree_doc_core::parse(old_json).map_err(|e| format!("old document: {e}"))?;
+    let new = tree_doc_core::parse(new_json).map_err(|e| format!("new document: {e}"))?;
+    Ok(tree_doc_core::diff(&old, &new))
+}
+
+/// Exposed to JS as `exportDocument`, since `export` is a reserved word there.
+#[wasm_bindgen(js_name = exportDocument)]
+pub fn export(json_str: &str, format: &str) -> JsValue {
+    match export_document(json_str, format) {
+        Ok(output) => to_js(&serde_json::json!({ "output": output })),
+        Err(e) => to_js(&serde_json::json!({ "error": e })),
+    }
+}
+
+/// Validate `json_str` and render it as `dot`, `mermaid`, `markdown`, `graphml`, or `html`.
+fn export_document(json_str: &str, format: &str) -> Result<String, String> {
+    let result = tree_doc_core::validate_document(json_str).map_err(|e| format!("{e}"))?;
+    if !result.is_valid {
+        return Err(format!(
+            "Document has {} validation error(s); fix them before exporting",
+            result.errors.len()
+        ));
+    }
+    let doc = tree_doc_core::parse(json_str).map_err(|e| format!("{e}"))?;
+
+    match format {
+        "dot" => Ok(tree_doc_core::to_dot(&doc)),
+        "mermaid" => Ok(tree_doc_core::to_mermaid(&doc)),
+        "markdown" => tree_doc_core::to_markdown(&doc),
+        "graphml" => Ok(tree_doc_core::to_graphml(&doc)),
+        "html" => tree_doc_core::to_html(&doc),
+        other => Err(format!(
+            "Unknown export format '{other}' (expected one of: dot, mermaid, markdown, graphml, html)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORY: &str = include_str!("../../../examples/story.tree.json");
+
+    #[test]
+    fn export_dot() {
+        let out = export_document(STORY, "dot").unwrap();
+        assert!(out.starts_with("digraph tree {"));
+    }
+
+    #[test]
+    fn export_mermaid() {
+        let out = export_document(STORY, "mermaid").unwrap();
+        assert!(out.starts_with("flowchart TD"));
+    }
+
+    #[test]
+    fn export_markdown() {
+        let out = export_document(STORY, "markdown").unwrap();
+        assert!(out.starts_with("# The Enchanted Garden"));
+    }
+
+    #[test]
+    fn export_graphml() {
+        let out = export_document(STORY, "graphml").unwrap();
+        assert!(out.contains("<graphml"));
+    }
+
+    #[test]
+    fn export_unknown_format() {
+        let err = export_document(STORY, "svg").unwrap_err();
+        assert!(err.contains("Unknown export format 'svg'"), "{err}");
+    }
+
+    #[test]
+    fn diff_json_shape() {
+        let edited = STORY
+            .replace("\"id\": \"climb\"", "\"id\": \"scale\"")
+            .replace("\"target\": \"climb\"", "\"target\": \"scale\"")
+            .replace("The water shimmers.", "The water glows.");
+        let changes = diff_documents(STORY, &edited).unwrap();
+        let value = serde_json::to_value(&changes).unwrap();
+
+        assert_eq!(value["addedNodes"], serde_json::json!(["scale"]));
+        assert_eq!(value["removedNodes"], serde_json::json!(["climb"]));
+        assert_eq!(
+            value["addedEdges"],
+            serde_json::json!([{ "source": "start", "target": "scale" }])
+        );
+        assert_eq!(
+            value["removedEdges"],
+            serde_json::json!([{ "source": "start", "target": "climb" }])
+        );
+        assert_eq!(value["changedContent"], serde_json::json!(["wish"]));
+        assert_eq!(value["trunkChange"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn full_view_carries_edge_descriptions() {
+        let json = include_str!("../../../examples/multi-tree.tree.json").replace(
+            r#""label": "Take the cellar stairs""#,
+            r#""label": "Take the cellar stairs", "description": "Mind the damp steps""#,
+        );
+        let view = serde_json::to_value(full_view_document(&json).unwrap()).unwrap();
+        let stairs = view["root"]["children"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|child| child["nodeId"] == "stairs")
+            .unwrap();
+        assert_eq!(stairs["viaLabel"], "Take the cellar stairs");
+        assert_eq!(stairs["viaDescription"], "Mind the damp steps");
+        assert_eq!(stairs["viaTrunk"], false);
+        assert_eq!(view["root"]["viaDescription"], serde_json::Value::Null);
+
+        assert!(full_view_document("{").is_err());
+    }
+
+    #[test]
+    fn diff_names_the_unparseable_input() {
+        let err = diff_documents(STORY, "{").unwrap_err();
+        assert!(err.starts_with("new document: "), "{err}");
+    }
+
+    #[test]
+    fn export_rejects_invalid_document() {
+        let json = include_str!("../../../examples/invalid/dangling-edge.tree.json");
+        assert!(export_document(json, "dot").is_err());
+    }
 }
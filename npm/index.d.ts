@@ -1,8 +1,19 @@
+/** Where in the document a diagnostic applies. */
+export type Location =
+  | { kind: "root" }
+  | { kind: "node"; id: string }
+  | { kind: "edge"; source: string; target: string; index: number }
+  | { kind: "path"; nodes: string[] }
+  /** JSON Pointer into the raw document; used by schema errors. */
+  | { kind: "pointer"; pointer: string };
+
 /** A single validation diagnostic (error, warning, or advisory). */
 export interface Diagnostic {
   rule: string;
+  /** Stable rule code, e.g. `"TDF003"`. */
+  code: string;
   message: string;
-  location: string;
+  location: Location;
   severity: "error" | "warning" | "advisory";
 }
 
@@ -12,6 +23,10 @@ export interface ValidationStats {
   edgeCount: number;
   trunkLength: number;
   branchCount: number;
+  componentCount: number;
+  /** Edges on the longest path from the root, ignoring cycle-closing edges. */
+  longestPathLen: number;
+  maxOutDegree: number;
   tier: number;
 }
 
@@ -26,12 +41,26 @@ export interface ValidateResult {
   error?: string;
 }
 
+/** A non-trunk edge leaving a trunk step. */
+export interface BranchInfo {
+  target: string;
+  label: string | null;
+  description: string | null;
+}
+
 /** A single step along the trunk path. */
 export interface TrunkStep {
   nodeId: string;
   content: string;
+  /** Every branch edge leaving this node, in document order. */
+  branches: BranchInfo[];
   branchCount: number;
-  branchLabels: string[];
+  /** @deprecated Read `label` from `branches` instead. */
+  branchLabels: (string | null)[];
+  /** @deprecated Read `description` from `branches` instead. */
+  branchDescriptions: (string | null)[];
+  /** @deprecated Read `target` from `branches` instead. */
+  branchTargets: string[];
   isTerminal: boolean;
   trunkTarget: string | null;
 }
@@ -45,23 +74,92 @@ export interface ViewResult {
   error?: string;
 }
 
+/** A node in the full view, with the edge it was reached through. */
+export interface FullViewNode {
+  nodeId: string;
+  content: string;
+  depth: number;
+  /** Label of the edge this node was reached through; `null` for the root. */
+  viaLabel: string | null;
+  /** Description of the edge this node was reached through, if it has one. */
+  viaDescription: string | null;
+  /** Whether the edge this node was reached through is a trunk edge. */
+  viaTrunk: boolean;
+  /** Already shown elsewhere in the tree; its children are not repeated. */
+  isBackEdge: boolean;
+  children: FullViewNode[];
+}
+
+/** Result of `fullView()`. */
+export interface FullViewResult {
+  title: string;
+  stats: string;
+  root: FullViewNode;
+  /** Present only on error. */
+  error?: string;
+}
+
 /** Result of `info()`. */
 export interface InfoResult {
   nodeCount: number;
   edgeCount: number;
   trunkLength: number;
   branchCount: number;
+  componentCount: number;
+  /** Edges on the longest path from the root, ignoring cycle-closing edges. */
+  longestPathLen: number;
+  maxOutDegree: number;
   tier: number;
   isValid: boolean;
+  /** Fraction of nodes on the trunk (0 to 1); absent if the document does not parse. */
+  trunkCoverage?: number;
   /** Present only on error. */
   error?: string;
 }
 
+/** Result of `exportDocument()`: `output` on success, `error` otherwise. */
+export interface ExportResult {
+  output?: string;
+  error?: string;
+}
+
+/** An edge identified by its endpoints. */
+export interface EdgeRef {
+  source: string;
+  target: string;
+}
+
+/** Result of `diff()`. Node ids are listed in document order. */
+export interface DiffResult {
+  addedNodes?: string[];
+  removedNodes?: string[];
+  addedEdges?: EdgeRef[];
+  removedEdges?: EdgeRef[];
+  /** Ids of nodes in both documents whose content differs. */
+  changedContent?: string[];
+  /** Node ids along each trunk, or null when the trunk is unchanged. */
+  trunkChange?: { old: string[]; new: string[] } | null;
+  /** Present only when either document fails to parse. */
+  error?: string;
+}
+
+/** Formats accepted by `exportDocument()`. */
+export type ExportFormat = "dot" | "mermaid" | "markdown" | "graphml" | "html";
+
 /** Validate a `.tree.json` document string. */
 export function validate(json_str: string): ValidateResult;
 
 /** Build a trunk-path view of a `.tree.json` document string. */
 export function view(json_str: string): ViewResult;
 
+/** Build a view of every edge from the root of a `.tree.json` document string. */
+export function fullView(json_str: string): FullViewResult;
+
 /** Get summary info for a `.tree.json` document string. */
 export function info(json_str: string): InfoResult;
+
+/** Render a valid `.tree.json` document string in another format. */
+export function exportDocument(json_str: string, format: ExportFormat): ExportResult;
+
+/** Compare two `.tree.json` document strings. */
+export function diff(old_json: string, new_json: string): DiffResult;
@@ -1 +1 @@
-export { validate, view, info } from "./tree_doc_wasm.js";
+export { validate, view, fullView, info, exportDocument, diff } from "./tree_doc_wasm.js";
@@ -94,7 +94,7 @@ function renderValidation(result) {
             html += `<li class="diag-item ${d.cls}">
                 <div>${escapeHtml(d.message)}</div>
                 <div class="diag-rule">${escapeHtml(d.rule)}</div>
-                <div class="diag-location">${escapeHtml(d.location)}</div>
+                <div class="diag-location">${escapeHtml(formatLocation(d.location))}</div>
             </li>`;
         }
         html += '</ul>';
@@ -103,6 +103,16 @@ function renderValidation(result) {
     el.innerHTML = html;
 }
 
+function formatLocation(loc) {
+    switch (loc.kind) {
+        case 'node': return `node '${loc.id}'`;
+        case 'edge': return `edge '${loc.source}' -> '${loc.target}' (edges[${loc.index}])`;
+        case 'path': return `path: ${loc.nodes.join(' -> ')}`;
+        case 'pointer': return loc.pointer;
+        default: return '(document root)';
+    }
+}
+
 function renderInfo(result) {
     const el = document.getElementById('info-result');
 
@@ -111,12 +121,19 @@ function renderInfo(result) {
         return;
     }
 
+    const coverage = result.trunkCoverage === undefined ? '' :
+        `<span class="info-label">Trunk coverage</span><span class="info-value">${(result.trunkCoverage * 100).toFixed(1)}%</span>`;
+
     el.innerHTML = `<div class="info-grid">
         <span class="info-label">Tier</span><span class="info-value">${result.tier}</span>
         <span class="info-label">Nodes</span><span class="info-value">${result.nodeCount}</span>
         <span class="info-label">Edges</span><span class="info-value">${result.edgeCount}</span>
         <span class="info-label">Trunk length</span><span class="info-value">${result.trunkLength}</span>
         <span class="info-label">Branches</span><span class="info-value">${result.branchCount}</span>
+        <span class="info-label">Components</span><span class="info-value">${result.componentCount}</span>
+        <span class="info-label">Longest path</span><span class="info-value">${result.longestPathLen}</span>
+        <span class="info-label">Max out-degree</span><span class="info-value">${result.maxOutDegree}</span>
+        ${coverage}
     </div>`;
 }
 
@@ -142,11 +159,17 @@ function renderView(result) {
 
         if (step.branchCount > 0) {
             html += `<span class="branch-badge">+${step.branchCount} branch${step.branchCount === 1 ? '' : 'es'}</span>`;
-            if (step.branchLabels && step.branchLabels.length > 0) {
+            if (step.branches.some(branch => branch.label)) {
                 html += '<div class="branch-labels">';
-                for (const label of step.branchLabels) {
-                    html += `&middot; ${escapeHtml(label)}<br>`;
-                }
+                step.branches.forEach(branch => {
+                    if (!branch.label) {
+                        return;
+                    }
+                    html += `&middot; ${escapeHtml(branch.label)}<br>`;
+                    if (branch.description) {
+                        html += `<span class="branch-description">${escapeHtml(branch.description)}</span><br>`;
+                    }
+                });
                 html += '</div>';
             }
         }